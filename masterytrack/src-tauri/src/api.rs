@@ -0,0 +1,222 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::db::{count_sessions, fetch_dashboard_stats, list_sessions};
+use crate::errors::{AppError, AppResult};
+use crate::models::{AppSettings, SessionFilter, SessionPage};
+use crate::AppState;
+
+const CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Status code and JSON body sent back for one request.
+type Reply = (u16, String);
+
+struct Running {
+    port: u16,
+    server: Arc<Server>,
+    thread: JoinHandle<()>,
+}
+
+/// The read-only localhost API, started and stopped as the `api_*` settings change.
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+}
+
+impl ApiServer {
+    fn running(&self) -> MutexGuard<'_, Option<Running>> {
+        self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn port(&self) -> Option<u16> {
+        self.running().as_ref().map(|r| r.port)
+    }
+
+    fn start(&self, state: AppState, port: u16) -> AppResult<()> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| AppError::Custom(format!("Unable to start the local API on port {port}: {e}")))?;
+        let server = Arc::new(server);
+        let listener = server.clone();
+        let thread = std::thread::spawn(move || {
+            for request in listener.incoming_requests() {
+                handle_request(&state, request);
+            }
+        });
+        *self.running() = Some(Running { port, server, thread });
+        Ok(())
+    }
+
+    /// Stops accepting connections and waits for the request in flight to finish.
+    pub fn stop(&self) {
+        let Some(running) = self.running().take() else {
+            return;
+        };
+        running.server.unblock();
+        if running.thread.join().is_err() {
+            warn!("Local API thread panicked while shutting down");
+        }
+        info!("Local API on port {} stopped", running.port);
+    }
+}
+
+fn wanted_port(settings: &AppSettings) -> Option<u16> {
+    if settings.api_enabled {
+        u16::try_from(settings.api_port).ok()
+    } else {
+        None
+    }
+}
+
+pub async fn api_worker(app: AppHandle, state: AppState) {
+    // Remember a port that failed to bind so a busy port is not retried every few seconds
+    let mut failed_port = None;
+    loop {
+        let wanted = wanted_port(&*state.settings.read().await);
+        let server = app.state::<ApiServer>();
+        if server.port() != wanted {
+            server.stop();
+            match wanted {
+                Some(port) if failed_port != Some(port) => match server.start(state.clone(), port) {
+                    Ok(()) => {
+                        info!("Local API listening on http://127.0.0.1:{port}");
+                        failed_port = None;
+                    }
+                    Err(e) => {
+                        warn!("{e}");
+                        failed_port = Some(port);
+                    }
+                },
+                _ => {}
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+fn handle_request(state: &AppState, request: Request) {
+    let (status, body) = async_runtime::block_on(respond(state, &request));
+    let content_type = Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to answer local API request: {e}");
+    }
+}
+
+async fn respond(state: &AppState, request: &Request) -> Reply {
+    let token = state.settings.read().await.api_token.clone();
+    let header = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str());
+    if !authorized(header, token.as_deref()) {
+        return failure(401, "Missing or invalid API token");
+    }
+    if request.method() != &Method::Get {
+        return failure(405, "The local API is read-only");
+    }
+    if state.lock.is_locked() {
+        return failure(403, "MasteryTrack is locked");
+    }
+
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    match path {
+        "/dashboard" => {
+            let settings = state.settings.read().await.clone();
+            let active = state.timer.active_seconds().await;
+            reply(fetch_dashboard_stats(&state.pool.get(), &settings, active).await)
+        }
+        "/sessions" => {
+            let filter = match session_filter(query) {
+                Ok(filter) => filter,
+                Err(message) => return failure(400, &message),
+            };
+            let pool = state.pool.get();
+            let page = async {
+                Ok(SessionPage {
+                    sessions: list_sessions(&pool, &filter).await?,
+                    total: count_sessions(&pool, &filter).await?,
+                })
+            };
+            reply(page.await)
+        }
+        _ => failure(404, "Unknown endpoint; try /dashboard or /sessions"),
+    }
+}
+
+/// Requires `Authorization: Bearer <token>`; with no token configured every request is refused.
+fn authorized(header: Option<&str>, token: Option<&str>) -> bool {
+    let Some(token) = token.map(str::trim).filter(|t| !t.is_empty()) else {
+        return false;
+    };
+    let Some(given) = header.and_then(|h| h.trim().strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let given = given.trim();
+    // Compare every byte so the response time does not reveal how much of the token matched
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Parses `limit` and `offset` from the query string of `/sessions`.
+fn session_filter(query: &str) -> Result<SessionFilter, String> {
+    let mut filter = SessionFilter::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let number = || {
+            value
+                .parse::<i64>()
+                .ok()
+                .filter(|n| *n >= 0)
+                .ok_or_else(|| format!("'{key}' must be a non-negative whole number"))
+        };
+        match key {
+            "limit" => filter.limit = Some(number()?),
+            "offset" => filter.offset = Some(number()?),
+            _ => return Err(format!("Unknown query parameter '{key}'")),
+        }
+    }
+    Ok(filter)
+}
+
+fn reply<T: Serialize>(result: AppResult<T>) -> Reply {
+    match result.and_then(|value| Ok(serde_json::to_string(&value)?)) {
+        Ok(body) => (200, body),
+        Err(e) => failure(500, &e.to_string()),
+    }
+}
+
+fn failure(status: u16, message: &str) -> Reply {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_matching_bearer_token() {
+        assert!(authorized(Some("Bearer s3cret"), Some("s3cret")));
+        assert!(!authorized(Some("Bearer s3cre"), Some("s3cret")));
+        assert!(!authorized(Some("s3cret"), Some("s3cret")));
+        assert!(!authorized(None, Some("s3cret")));
+        assert!(!authorized(Some("Bearer "), Some("")));
+        assert!(!authorized(Some("Bearer anything"), None));
+    }
+
+    #[test]
+    fn parses_paging_from_query() {
+        let filter = session_filter("limit=50&offset=100").unwrap();
+        assert_eq!((filter.limit, filter.offset), (Some(50), Some(100)));
+        assert!(session_filter("").unwrap().limit.is_none());
+        assert!(session_filter("limit=-1").is_err());
+        assert!(session_filter("skill=3").is_err());
+    }
+}
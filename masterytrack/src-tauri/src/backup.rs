@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use log::{info, warn};
+use tauri::Emitter;
+use tokio::sync::RwLock;
+
+use crate::db::{backup_database, SharedPool};
+use crate::errors::AppResult;
+use crate::models::AppSettings;
+
+const BACKUP_PREFIX: &str = "masterytrack-";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Backs up into `dir`, then deletes all but the newest `keep` backups (0 keeps everything).
+pub async fn create_rotated_backup(
+    pool: &SharedPool,
+    db_path: &Path,
+    dir: &Path,
+    keep: i64,
+    compress: bool,
+) -> AppResult<PathBuf> {
+    let path = backup_database(&pool.get(), db_path, dir, compress).await?;
+    if keep > 0 {
+        for (_, stale) in list_backups(dir).await?.into_iter().skip(keep as usize) {
+            if let Err(e) = tokio::fs::remove_file(&stale).await {
+                warn!("Failed to remove old backup {}: {}", stale.display(), e);
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Backups in `dir` with the time encoded in their name, newest first.
+async fn list_backups(dir: &Path) -> AppResult<Vec<(DateTime<Utc>, PathBuf)>> {
+    let mut backups = Vec::new();
+    if !dir.exists() {
+        return Ok(backups);
+    }
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if let Some(taken_at) = path.file_name().and_then(|n| n.to_str()).and_then(backup_timestamp) {
+            backups.push((taken_at, path));
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    Ok(backups)
+}
+
+fn backup_timestamp(file_name: &str) -> Option<DateTime<Utc>> {
+    let stamp = file_name.strip_prefix(BACKUP_PREFIX)?.split('.').next()?;
+    let naive = NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT).ok()?;
+    Some(Utc.from_utc_datetime(&naive))
+}
+
+/// Latest write to the database, including pages still sitting in the WAL file.
+fn database_modified(db_path: &Path) -> Option<DateTime<Utc>> {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path.to_path_buf(), PathBuf::from(wal)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .map(DateTime::<Utc>::from)
+        .max()
+}
+
+fn backup_due(
+    last_backup: Option<DateTime<Utc>>,
+    db_modified: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    interval_hours: i64,
+) -> bool {
+    match last_backup {
+        None => true,
+        Some(last) => {
+            let changed = !matches!(db_modified, Some(modified) if modified <= last);
+            changed && now - last >= chrono::Duration::hours(interval_hours)
+        }
+    }
+}
+
+pub async fn backup_worker(
+    app: tauri::AppHandle,
+    settings: Arc<RwLock<AppSettings>>,
+    pool: SharedPool,
+    db_path: PathBuf,
+) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+
+        let (dir, interval_hours, keep, compress) = {
+            let settings = settings.read().await;
+            match settings.auto_backup_path.as_deref().filter(|p| !p.trim().is_empty()) {
+                Some(dir) if settings.auto_backup_interval_hours > 0 => (
+                    PathBuf::from(dir),
+                    settings.auto_backup_interval_hours,
+                    settings.auto_backup_keep,
+                    settings.backup_compress,
+                ),
+                _ => continue,
+            }
+        };
+
+        let last_backup = match list_backups(&dir).await {
+            Ok(backups) => backups.first().map(|b| b.0),
+            Err(e) => {
+                warn!("Failed to read backup folder {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        if !backup_due(last_backup, database_modified(&db_path), Utc::now(), interval_hours) {
+            continue;
+        }
+
+        match create_rotated_backup(&pool, &db_path, &dir, keep, compress).await {
+            Ok(path) => {
+                info!("Scheduled backup written to {}", path.display());
+                app.emit("backup:created", &path.to_string_lossy()).ok();
+            }
+            Err(e) => warn!("Scheduled backup failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_timestamp_from_backup_name() {
+        assert_eq!(
+            backup_timestamp("masterytrack-20240301-221500.db"),
+            Some(utc("2024-03-01T22:15:00Z"))
+        );
+        assert_eq!(
+            backup_timestamp("masterytrack-20240301-221500.db.gz"),
+            Some(utc("2024-03-01T22:15:00Z"))
+        );
+        assert_eq!(backup_timestamp("notes.txt"), None);
+    }
+
+    #[test]
+    fn backup_waits_for_interval_and_changes() {
+        let last = Some(utc("2024-03-01T00:00:00Z"));
+        let changed = Some(utc("2024-03-01T05:00:00Z"));
+        let unchanged = Some(utc("2024-02-29T23:00:00Z"));
+
+        assert!(backup_due(None, unchanged, utc("2024-03-01T01:00:00Z"), 24));
+        assert!(!backup_due(last, changed, utc("2024-03-01T12:00:00Z"), 24));
+        assert!(backup_due(last, changed, utc("2024-03-02T00:00:00Z"), 24));
+        assert!(!backup_due(last, unchanged, utc("2024-03-05T00:00:00Z"), 24));
+    }
+}
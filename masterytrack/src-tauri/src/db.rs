@@ -1,38 +1,276 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Timelike, Utc};
+use futures::TryStreamExt;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
-use sqlx::{Row, SqlitePool};
+use sqlx::{FromRow, Row, SqliteConnection, SqlitePool};
 use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
 
 use crate::errors::{AppError, AppResult};
 use crate::models::{
-    AppSettings, DashboardStats, ReflectionInput, SessionEditPayload, SessionHistoryRow,
-    SessionRecord, SettingsRow,
+    AppSettings, DailyTotal, DashboardDisplay, DashboardStats, ExportFormat, ExportPreview, FactoryResetReport, GoalProgress, ImportSummary, MaintenanceReport, MIN_MASTERY_TARGET_HOURS, PracticeRecords, ReflectionInput, SessionEditPayload,
+    SessionEvent, SessionEventKind, SessionFilter, SessionHistoryRow, SessionRecord, SessionScreenshot, SessionSearchHit, SettingsRow, Skill, SkillMergeReport, SkillSummary, TagHours, WeekRecord, WeeklySummary, HourlyTotal,
 };
+use crate::timezone::DayZone;
 
-pub async fn init_pool(app: &AppHandle) -> AppResult<(SqlitePool, PathBuf)> {
-    let data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| AppError::Custom(format!("Unable to resolve app data directory: {e}")))?;
-    tokio::fs::create_dir_all(&data_dir).await?;
+/// Environment variable holding the SQLCipher passphrase; unset means the database is not encrypted.
+pub const PASSPHRASE_ENV: &str = "MASTERYTRACK_DB_PASSPHRASE";
+
+/// Environment variable pointing at a directory to keep the database in instead of the app data dir.
+pub const DATA_DIR_ENV: &str = "MASTERYTRACK_DATA_DIR";
+/// File in the default app data dir holding the data directory chosen in settings.
+const DATA_DIR_POINTER: &str = "data-dir.txt";
+
+pub async fn init_pool(app: &AppHandle) -> AppResult<(SharedPool, PathBuf)> {
+    let default_dir = default_data_dir(app)?;
+    tokio::fs::create_dir_all(&default_dir).await?;
+    let data_dir = resolve_data_dir(&default_dir, std::env::var(DATA_DIR_ENV).ok()).await;
     let db_path = data_dir.join("masterytrack.db");
+    let passphrase = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty());
+    let pool = open_pool(&db_path, passphrase.as_deref()).await?;
+    Ok((SharedPool::new(pool, passphrase), db_path))
+}
+
+pub fn default_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::Custom(format!("Unable to resolve app data directory: {e}")))
+}
+
+/// Picks the directory holding the database, and with it the default screenshot, export and
+/// backup folders: `env_dir` first, then the saved choice, then `default_dir`. An override that
+/// cannot be written to is skipped with a warning rather than failing the launch.
+async fn resolve_data_dir(default_dir: &Path, env_dir: Option<String>) -> PathBuf {
+    let saved = tokio::fs::read_to_string(default_dir.join(DATA_DIR_POINTER)).await.ok();
+    let candidates = [(env_dir, DATA_DIR_ENV), (saved, DATA_DIR_POINTER)];
+    for (dir, source) in candidates {
+        let Some(dir) = dir.as_deref().map(str::trim).filter(|d| !d.is_empty()).map(portable_path) else {
+            continue;
+        };
+        match ensure_writable_dir(&dir).await {
+            Ok(()) => return dir,
+            Err(e) => log::warn!("Ignoring data directory {} from {source}: {e}", dir.display()),
+        }
+    }
+    default_dir.to_path_buf()
+}
+
+/// Relative paths are taken from the executable's folder, so a copy on a USB stick can point at
+/// a directory beside itself.
+fn portable_path(dir: &str) -> PathBuf {
+    let dir = PathBuf::from(dir);
+    if dir.is_absolute() {
+        return dir;
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|parent| parent.join(&dir)))
+        .unwrap_or(dir)
+}
+
+/// Creates `dir` if needed and checks a file can be written there.
+async fn ensure_writable_dir(dir: &Path) -> AppResult<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let scratch = dir.join(".masterytrack-write-test");
+    tokio::fs::write(&scratch, b"ok").await?;
+    tokio::fs::remove_file(&scratch).await?;
+    Ok(())
+}
+
+/// Saves the data directory to use from the next launch, or forgets it with `None`. Existing data
+/// is not moved. `MASTERYTRACK_DATA_DIR` still wins when set.
+pub async fn save_data_dir(default_dir: &Path, dir: Option<&str>) -> AppResult<()> {
+    let pointer = default_dir.join(DATA_DIR_POINTER);
+    match dir.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(dir) => {
+            ensure_writable_dir(&portable_path(dir)).await.map_err(|e| {
+                AppError::Custom(format!("Cannot use {dir} as the data directory: {e}"))
+            })?;
+            tokio::fs::create_dir_all(default_dir).await?;
+            tokio::fs::write(&pointer, dir).await?;
+        }
+        None => match tokio::fs::remove_file(&pointer).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        },
+    }
+    Ok(())
+}
 
-    let connect_opts = SqliteConnectOptions::new()
-        .filename(&db_path)
-        .create_if_missing(true)
-        .journal_mode(SqliteJournalMode::Wal)
-        .busy_timeout(std::time::Duration::from_secs(5));
+async fn open_pool(db_path: &Path, passphrase: Option<&str>) -> AppResult<SqlitePool> {
+    let connect_opts = keyed(
+        SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(std::time::Duration::from_secs(5)),
+        passphrase,
+    );
 
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
         .connect_with(connect_opts)
-        .await?;
+        .await
+        .map_err(key_error)?;
+    if passphrase.is_some() {
+        ensure_sqlcipher(&pool).await?;
+    }
+
+    run_migrations(&pool).await.map_err(|e| match e {
+        AppError::Database(msg) if msg.contains("file is not a database") => AppError::DatabaseEncrypted,
+        other => other,
+    })?;
+
+    Ok(pool)
+}
+
+/// Adds the SQLCipher key; sqlx sends `key` before every other pragma on each new connection.
+fn keyed(opts: SqliteConnectOptions, passphrase: Option<&str>) -> SqliteConnectOptions {
+    match passphrase {
+        Some(passphrase) => opts.pragma("key", sql_string(passphrase)),
+        None => opts,
+    }
+}
+
+/// Quotes a value for pragmas and statements that cannot take bound parameters.
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
 
-    run_migrations(&pool).await?;
+/// SQLite reports a missing or wrong SQLCipher key as "file is not a database" (SQLITE_NOTADB).
+fn key_error(e: sqlx::Error) -> AppError {
+    match &e {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("26") => AppError::DatabaseEncrypted,
+        _ => e.into(),
+    }
+}
+
+/// Plain SQLite silently ignores `PRAGMA key`, which would leave the journal unencrypted.
+async fn ensure_sqlcipher(pool: &SqlitePool) -> AppResult<()> {
+    let version: Option<String> = sqlx::query_scalar("PRAGMA cipher_version").fetch_optional(pool).await?;
+    if version.is_none() {
+        return Err(AppError::Custom(format!(
+            "{PASSPHRASE_ENV} is set but this build has no SQLCipher support (build with --features sqlcipher)"
+        )));
+    }
+    Ok(())
+}
+
+/// Pool handle shared by commands and the timer, swapped out when a backup is restored or the
+/// database is re-encrypted.
+#[derive(Clone)]
+pub struct SharedPool {
+    pool: Arc<std::sync::RwLock<SqlitePool>>,
+    /// SQLCipher passphrase the pool was opened with, reused whenever it is reopened.
+    passphrase: Arc<std::sync::RwLock<Option<String>>>,
+}
+
+impl SharedPool {
+    pub fn new(pool: SqlitePool, passphrase: Option<String>) -> Self {
+        Self {
+            pool: Arc::new(std::sync::RwLock::new(pool)),
+            passphrase: Arc::new(std::sync::RwLock::new(passphrase)),
+        }
+    }
+
+    pub fn get(&self) -> SqlitePool {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn passphrase(&self) -> Option<String> {
+        self.passphrase.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Opens `db_path` with `passphrase` and makes it the live pool.
+    async fn reopen(&self, db_path: &Path, passphrase: Option<String>) -> AppResult<()> {
+        let pool = open_pool(db_path, passphrase.as_deref()).await?;
+        *self.pool.write().unwrap_or_else(|e| e.into_inner()) = pool;
+        *self.passphrase.write().unwrap_or_else(|e| e.into_inner()) = passphrase;
+        Ok(())
+    }
+}
 
-    Ok((pool, db_path))
+/// Schema changes applied on top of the `CREATE TABLE` statements, in order. Applied versions are
+/// recorded in `schema_version`; add new steps at the end with the next number and never edit old ones.
+const MIGRATIONS: &[(i64, &str)] = &[
+    (1, "ALTER TABLE sessions ADD COLUMN tags TEXT"),
+    (2, "ALTER TABLE settings ADD COLUMN screenshot_enabled INTEGER DEFAULT 0"),
+    (3, "ALTER TABLE settings ADD COLUMN screenshot_storage_path TEXT"),
+    (4, "ALTER TABLE settings ADD COLUMN screenshot_retention_days INTEGER DEFAULT 7"),
+    (5, "ALTER TABLE settings ADD COLUMN music_enabled INTEGER DEFAULT 0"),
+    (6, "ALTER TABLE settings ADD COLUMN music_playlist_type TEXT DEFAULT 'focus'"),
+    (7, "ALTER TABLE settings ADD COLUMN music_volume REAL DEFAULT 0.5"),
+    (8, "ALTER TABLE settings ADD COLUMN music_auto_play INTEGER DEFAULT 0"),
+    (9, "ALTER TABLE settings ADD COLUMN music_custom_playlist_url TEXT"),
+    (10, "ALTER TABLE settings ADD COLUMN app_pin_hash TEXT"),
+    (11, "ALTER TABLE settings ADD COLUMN screenshot_privacy_mode INTEGER DEFAULT 0"),
+    (12, "ALTER TABLE settings ADD COLUMN quick_note_target TEXT DEFAULT 'recent_session'"),
+    (13, "ALTER TABLE settings ADD COLUMN display_hours_precision TEXT DEFAULT 'two_decimals'"),
+    (14, "ALTER TABLE settings ADD COLUMN weekly_goal_minutes INTEGER"),
+    (15, "UPDATE settings SET weekly_goal_minutes = daily_goal_minutes * 7 WHERE weekly_goal_minutes IS NULL"),
+    (16, "ALTER TABLE settings ADD COLUMN screenshot_interval_min_minutes INTEGER DEFAULT 10"),
+    (17, "ALTER TABLE settings ADD COLUMN screenshot_interval_max_minutes INTEGER DEFAULT 20"),
+    (18, "ALTER TABLE settings ADD COLUMN screenshot_format TEXT DEFAULT 'jpeg'"),
+    (19, "ALTER TABLE settings ADD COLUMN screenshot_blur INTEGER DEFAULT 0"),
+    (20, "ALTER TABLE settings ADD COLUMN timezone TEXT"),
+    (21, "ALTER TABLE settings ADD COLUMN streak_grace_days INTEGER DEFAULT 0"),
+    (22, "ALTER TABLE settings ADD COLUMN auto_backup_interval_hours INTEGER DEFAULT 24"),
+    (23, "ALTER TABLE settings ADD COLUMN auto_backup_keep INTEGER DEFAULT 10"),
+    (24, "ALTER TABLE settings ADD COLUMN backup_compress INTEGER DEFAULT 0"),
+    (25, "ALTER TABLE settings ADD COLUMN productivity_active_from TEXT"),
+    (26, "ALTER TABLE settings ADD COLUMN productivity_active_to TEXT"),
+    (27, "ALTER TABLE settings ADD COLUMN productivity_grace_seconds INTEGER DEFAULT 15"),
+    (28, "ALTER TABLE settings ADD COLUMN pomodoro_enabled INTEGER DEFAULT 0"),
+    (29, "ALTER TABLE settings ADD COLUMN pomodoro_work_minutes INTEGER DEFAULT 25"),
+    (30, "ALTER TABLE settings ADD COLUMN pomodoro_break_minutes INTEGER DEFAULT 5"),
+    (31, "ALTER TABLE settings ADD COLUMN minimum_session_minutes INTEGER DEFAULT 0"),
+    (32, "CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time)"),
+    (33, "CREATE INDEX IF NOT EXISTS idx_sessions_skill_id ON sessions(skill_id)"),
+    (34, "ALTER TABLE settings ADD COLUMN api_enabled INTEGER DEFAULT 0"),
+    (35, "ALTER TABLE settings ADD COLUMN api_port INTEGER DEFAULT 4587"),
+    (36, "ALTER TABLE settings ADD COLUMN api_token TEXT"),
+    (37, "ALTER TABLE settings ADD COLUMN goal_webhook_url TEXT"),
+    (38, "ALTER TABLE settings ADD COLUMN reflection_prompts TEXT NOT NULL DEFAULT '[]'"),
+    (39, "ALTER TABLE sessions ADD COLUMN reflection_extra TEXT"),
+    (40, "ALTER TABLE settings ADD COLUMN mastery_target_hours INTEGER DEFAULT 10000"),
+    (41, "ALTER TABLE sessions ADD COLUMN deleted_at TEXT"),
+    (42, "ALTER TABLE settings ADD COLUMN goal_reminder_percent INTEGER DEFAULT 0"),
+    (43, "ALTER TABLE settings ADD COLUMN reminder_enabled INTEGER DEFAULT 0"),
+    (44, "ALTER TABLE settings ADD COLUMN reminder_time TEXT"),
+    (45, "ALTER TABLE settings ADD COLUMN idle_behavior TEXT DEFAULT 'pause'"),
+    (46, "ALTER TABLE settings ADD COLUMN idle_prompt_timeout_minutes INTEGER DEFAULT 10"),
+    (47, "ALTER TABLE settings ADD COLUMN active_requires_focus_app INTEGER DEFAULT 0"),
+    (48, "CREATE INDEX IF NOT EXISTS idx_screenshots_session_id ON screenshots(session_id)"),
+    (49, "ALTER TABLE settings ADD COLUMN screenshot_max_storage_mb INTEGER DEFAULT 0"),
+    (50, "ALTER TABLE settings ADD COLUMN streak_milestones TEXT NOT NULL DEFAULT '[7,30,60,100,365]'"),
+    (51, "ALTER TABLE settings ADD COLUMN streak_milestone_reached INTEGER NOT NULL DEFAULT 0"),
+    (52, "ALTER TABLE settings ADD COLUMN weekly_summary_enabled INTEGER DEFAULT 0"),
+    (53, "ALTER TABLE settings ADD COLUMN weekly_summary_weekday INTEGER DEFAULT 6"),
+    (54, "ALTER TABLE settings ADD COLUMN weekly_summary_time TEXT DEFAULT '18:00'"),
+    (55, "ALTER TABLE settings ADD COLUMN weekly_summary_delivery TEXT DEFAULT 'file'"),
+    (56, "ALTER TABLE settings ADD COLUMN weekly_summary_last_sent TEXT"),
+    (57, "ALTER TABLE settings ADD COLUMN idle_poll_seconds INTEGER DEFAULT 30"),
+    (58, "ALTER TABLE settings ADD COLUMN week_start_day TEXT DEFAULT 'monday'"),
+    (59, "ALTER TABLE settings ADD COLUMN max_session_minutes INTEGER DEFAULT 0"),
+    (60, "ALTER TABLE settings ADD COLUMN daily_goal_by_weekday TEXT"),
+];
+
+/// Whether an `ALTER TABLE .. ADD COLUMN` step is already reflected in the table, as it is for fresh
+/// databases and for ones upgraded before versions were recorded.
+async fn column_already_added(conn: &mut SqliteConnection, sql: &str) -> AppResult<bool> {
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    let ["ALTER", "TABLE", table, "ADD", "COLUMN", column, ..] = words.as_slice() else {
+        return Ok(false);
+    };
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2")
+        .bind(table)
+        .bind(column)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(count > 0)
 }
 
 async fn run_migrations(pool: &SqlitePool) -> AppResult<()> {
@@ -55,6 +293,8 @@ async fn run_migrations(pool: &SqlitePool) -> AppResult<()> {
             what_practiced TEXT,
             what_learned TEXT,
             next_focus TEXT,
+            tags TEXT,
+            reflection_extra TEXT,
             FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE
         );
     "#;
@@ -76,33 +316,179 @@ async fn run_migrations(pool: &SqlitePool) -> AppResult<()> {
             music_playlist_type TEXT DEFAULT 'focus',
             music_volume REAL DEFAULT 0.5,
             music_auto_play INTEGER DEFAULT 0,
-            music_custom_playlist_url TEXT
+            music_custom_playlist_url TEXT,
+            app_pin_hash TEXT,
+            screenshot_privacy_mode INTEGER DEFAULT 0,
+            quick_note_target TEXT DEFAULT 'recent_session',
+            display_hours_precision TEXT DEFAULT 'two_decimals',
+            weekly_goal_minutes INTEGER,
+            screenshot_interval_min_minutes INTEGER DEFAULT 10,
+            screenshot_interval_max_minutes INTEGER DEFAULT 20,
+            screenshot_format TEXT DEFAULT 'jpeg',
+            screenshot_blur INTEGER DEFAULT 0,
+            timezone TEXT,
+            streak_grace_days INTEGER DEFAULT 0,
+            auto_backup_interval_hours INTEGER DEFAULT 24,
+            auto_backup_keep INTEGER DEFAULT 10,
+            backup_compress INTEGER DEFAULT 0,
+            productivity_active_from TEXT,
+            productivity_active_to TEXT,
+            productivity_grace_seconds INTEGER DEFAULT 15,
+            pomodoro_enabled INTEGER DEFAULT 0,
+            pomodoro_work_minutes INTEGER DEFAULT 25,
+            pomodoro_break_minutes INTEGER DEFAULT 5,
+            minimum_session_minutes INTEGER DEFAULT 0,
+            api_enabled INTEGER DEFAULT 0,
+            api_port INTEGER DEFAULT 4587,
+            api_token TEXT,
+            goal_webhook_url TEXT,
+            reflection_prompts TEXT NOT NULL DEFAULT '[]',
+            mastery_target_hours INTEGER DEFAULT 10000,
+            goal_reminder_percent INTEGER DEFAULT 0,
+            reminder_enabled INTEGER DEFAULT 0,
+            reminder_time TEXT,
+            idle_behavior TEXT DEFAULT 'pause',
+            idle_prompt_timeout_minutes INTEGER DEFAULT 10,
+            active_requires_focus_app INTEGER DEFAULT 0,
+            screenshot_max_storage_mb INTEGER DEFAULT 0,
+            streak_milestones TEXT NOT NULL DEFAULT '[7,30,60,100,365]',
+            streak_milestone_reached INTEGER NOT NULL DEFAULT 0,
+            weekly_summary_enabled INTEGER DEFAULT 0,
+            weekly_summary_weekday INTEGER DEFAULT 6,
+            weekly_summary_time TEXT DEFAULT '18:00',
+            weekly_summary_delivery TEXT DEFAULT 'file',
+            weekly_summary_last_sent TEXT,
+            idle_poll_seconds INTEGER DEFAULT 30,
+            week_start_day TEXT DEFAULT 'monday',
+            max_session_minutes INTEGER DEFAULT 0,
+            daily_goal_by_weekday TEXT
         );
     "#;
     
-    // Migrate existing settings table if new columns don't exist
-    let migrations = [
-        "ALTER TABLE settings ADD COLUMN screenshot_enabled INTEGER DEFAULT 0",
-        "ALTER TABLE settings ADD COLUMN screenshot_storage_path TEXT",
-        "ALTER TABLE settings ADD COLUMN screenshot_retention_days INTEGER DEFAULT 7",
-        "ALTER TABLE settings ADD COLUMN music_enabled INTEGER DEFAULT 0",
-        "ALTER TABLE settings ADD COLUMN music_playlist_type TEXT DEFAULT 'focus'",
-        "ALTER TABLE settings ADD COLUMN music_volume REAL DEFAULT 0.5",
-        "ALTER TABLE settings ADD COLUMN music_auto_play INTEGER DEFAULT 0",
-        "ALTER TABLE settings ADD COLUMN music_custom_playlist_url TEXT",
-    ];
-    
-    for migration in migrations.iter() {
-        let _ = sqlx::query(migration).execute(pool).await;
-    }
+    let create_session_events = r#"
+        CREATE TABLE IF NOT EXISTS session_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            at TEXT NOT NULL,
+            detail TEXT,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+    "#;
+
+    let create_screenshots = r#"
+        CREATE TABLE IF NOT EXISTS screenshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            captured_at TEXT NOT NULL,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        );
+    "#;
+
+    let create_daily_notes = r#"
+        CREATE TABLE IF NOT EXISTS daily_notes (
+            day TEXT PRIMARY KEY,
+            notes TEXT NOT NULL
+        );
+    "#;
+
+    let create_schema_version = r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        );
+    "#;
 
     sqlx::query(create_skills).execute(pool).await?;
     sqlx::query(create_sessions).execute(pool).await?;
     sqlx::query(create_settings).execute(pool).await?;
+    sqlx::query(create_daily_notes).execute(pool).await?;
+    sqlx::query(create_session_events).execute(pool).await?;
+    sqlx::query(create_screenshots).execute(pool).await?;
+    sqlx::query(create_schema_version).execute(pool).await?;
+
+    let applied: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    for (version, sql) in MIGRATIONS.iter().filter(|(version, _)| *version > applied) {
+        let mut tx = pool.begin().await?;
+        if !column_already_added(&mut tx, sql).await? {
+            sqlx::query(sql).execute(&mut *tx).await?;
+        }
+        sqlx::query("INSERT INTO schema_version (version, applied_at) VALUES (?1, ?2)")
+            .bind(version)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    if let Err(e) = ensure_search_index(pool).await {
+        log::warn!("Full-text search is unavailable, searching with LIKE instead: {e}");
+    }
+
+    Ok(())
+}
+
+const SEARCH_INDEX_TRIGGERS: [&str; 3] = [
+    r#"
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_insert AFTER INSERT ON sessions BEGIN
+            INSERT INTO sessions_fts (rowid, notes, what_practiced, what_learned, next_focus)
+            VALUES (new.id, new.notes, new.what_practiced, new.what_learned, new.next_focus);
+        END
+    "#,
+    r#"
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_delete AFTER DELETE ON sessions BEGIN
+            INSERT INTO sessions_fts (sessions_fts, rowid, notes, what_practiced, what_learned, next_focus)
+            VALUES ('delete', old.id, old.notes, old.what_practiced, old.what_learned, old.next_focus);
+        END
+    "#,
+    r#"
+        CREATE TRIGGER IF NOT EXISTS sessions_fts_update
+        AFTER UPDATE OF notes, what_practiced, what_learned, next_focus ON sessions BEGIN
+            INSERT INTO sessions_fts (sessions_fts, rowid, notes, what_practiced, what_learned, next_focus)
+            VALUES ('delete', old.id, old.notes, old.what_practiced, old.what_learned, old.next_focus);
+            INSERT INTO sessions_fts (rowid, notes, what_practiced, what_learned, next_focus)
+            VALUES (new.id, new.notes, new.what_practiced, new.what_learned, new.next_focus);
+        END
+    "#,
+];
 
+/// Creates the FTS5 index over the reflection columns, kept in step by triggers. Sessions that
+/// predate the index are backfilled once when it is first created. Fails on SQLite builds without FTS5.
+async fn ensure_search_index(pool: &SqlitePool) -> AppResult<()> {
+    let existed = search_index_available(pool).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS sessions_fts USING fts5(
+            notes, what_practiced, what_learned, next_focus,
+            content = 'sessions', content_rowid = 'id'
+        )
+    "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    for trigger in SEARCH_INDEX_TRIGGERS {
+        sqlx::query(trigger).execute(&mut *tx).await?;
+    }
+    if !existed {
+        sqlx::query("INSERT INTO sessions_fts (sessions_fts) VALUES ('rebuild')")
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
     Ok(())
 }
 
+async fn search_index_available(pool: &SqlitePool) -> AppResult<bool> {
+    let found: Option<i64> = sqlx::query_scalar("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sessions_fts'")
+        .fetch_optional(pool)
+        .await?;
+    Ok(found.is_some())
+}
+
 pub async fn ensure_settings(pool: &SqlitePool) -> AppResult<AppSettings> {
     let row = sqlx::query_as::<_, SettingsRow>("SELECT * FROM settings WHERE id = 1")
         .fetch_optional(pool)
@@ -118,16 +504,34 @@ pub async fn ensure_settings(pool: &SqlitePool) -> AppResult<AppSettings> {
 }
 
 pub async fn save_settings(pool: &SqlitePool, settings: &AppSettings) -> AppResult<()> {
-    let (id, name, daily_goal, idle_timeout, productivity, allowed, blocked, backup, screenshot_enabled, screenshot_path, screenshot_retention, music_enabled, music_playlist_type, music_volume, music_auto_play, music_custom_url) =
-        settings.to_row()?;
+    let allowed_apps = serde_json::to_string(&settings.allowed_apps)?;
+    let blocked_apps = serde_json::to_string(&settings.blocked_apps)?;
+    let reflection_prompts = serde_json::to_string(&settings.reflection_prompts)?;
+    let streak_milestones = serde_json::to_string(&settings.streak_milestones)?;
+    let daily_goal_by_weekday = settings
+        .daily_goal_by_weekday
+        .map(|goals| serde_json::to_string(&goals))
+        .transpose()?;
 
     sqlx::query(
         r#"
         INSERT INTO settings (id, skill_name, daily_goal_minutes, idle_timeout_minutes,
             productivity_mode_enabled, allowed_apps, blocked_apps, auto_backup_path,
             screenshot_enabled, screenshot_storage_path, screenshot_retention_days,
-            music_enabled, music_playlist_type, music_volume, music_auto_play, music_custom_playlist_url)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+            music_enabled, music_playlist_type, music_volume, music_auto_play, music_custom_playlist_url,
+            screenshot_privacy_mode, quick_note_target, display_hours_precision,
+            weekly_goal_minutes, screenshot_interval_min_minutes,
+            screenshot_interval_max_minutes, screenshot_format, screenshot_blur, timezone,
+            streak_grace_days, auto_backup_interval_hours, auto_backup_keep, backup_compress,
+            productivity_active_from, productivity_active_to, productivity_grace_seconds,
+            pomodoro_enabled, pomodoro_work_minutes, pomodoro_break_minutes,
+            minimum_session_minutes, api_enabled, api_port, api_token, goal_webhook_url,
+            reflection_prompts, mastery_target_hours, goal_reminder_percent, reminder_enabled,
+            reminder_time, idle_behavior, idle_prompt_timeout_minutes, active_requires_focus_app,
+            screenshot_max_storage_mb, streak_milestones, weekly_summary_enabled,
+            weekly_summary_weekday, weekly_summary_time, weekly_summary_delivery,
+            idle_poll_seconds, week_start_day, max_session_minutes, daily_goal_by_weekday)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39, ?40, ?41, ?42, ?43, ?44, ?45, ?46, ?47, ?48, ?49, ?50, ?51, ?52, ?53, ?54, ?55, ?56, ?57, ?58)
         ON CONFLICT(id) DO UPDATE SET
             skill_name = excluded.skill_name,
             daily_goal_minutes = excluded.daily_goal_minutes,
@@ -143,41 +547,180 @@ pub async fn save_settings(pool: &SqlitePool, settings: &AppSettings) -> AppResu
             music_playlist_type = excluded.music_playlist_type,
             music_volume = excluded.music_volume,
             music_auto_play = excluded.music_auto_play,
-            music_custom_playlist_url = excluded.music_custom_playlist_url;
+            music_custom_playlist_url = excluded.music_custom_playlist_url,
+            screenshot_privacy_mode = excluded.screenshot_privacy_mode,
+            quick_note_target = excluded.quick_note_target,
+            display_hours_precision = excluded.display_hours_precision,
+            weekly_goal_minutes = excluded.weekly_goal_minutes,
+            screenshot_interval_min_minutes = excluded.screenshot_interval_min_minutes,
+            screenshot_interval_max_minutes = excluded.screenshot_interval_max_minutes,
+            screenshot_format = excluded.screenshot_format,
+            screenshot_blur = excluded.screenshot_blur,
+            timezone = excluded.timezone,
+            streak_grace_days = excluded.streak_grace_days,
+            auto_backup_interval_hours = excluded.auto_backup_interval_hours,
+            auto_backup_keep = excluded.auto_backup_keep,
+            backup_compress = excluded.backup_compress,
+            productivity_active_from = excluded.productivity_active_from,
+            productivity_active_to = excluded.productivity_active_to,
+            productivity_grace_seconds = excluded.productivity_grace_seconds,
+            pomodoro_enabled = excluded.pomodoro_enabled,
+            pomodoro_work_minutes = excluded.pomodoro_work_minutes,
+            pomodoro_break_minutes = excluded.pomodoro_break_minutes,
+            minimum_session_minutes = excluded.minimum_session_minutes,
+            api_enabled = excluded.api_enabled,
+            api_port = excluded.api_port,
+            api_token = excluded.api_token,
+            goal_webhook_url = excluded.goal_webhook_url,
+            reflection_prompts = excluded.reflection_prompts,
+            mastery_target_hours = excluded.mastery_target_hours,
+            goal_reminder_percent = excluded.goal_reminder_percent,
+            reminder_enabled = excluded.reminder_enabled,
+            reminder_time = excluded.reminder_time,
+            idle_behavior = excluded.idle_behavior,
+            idle_prompt_timeout_minutes = excluded.idle_prompt_timeout_minutes,
+            active_requires_focus_app = excluded.active_requires_focus_app,
+            screenshot_max_storage_mb = excluded.screenshot_max_storage_mb,
+            streak_milestones = excluded.streak_milestones,
+            weekly_summary_enabled = excluded.weekly_summary_enabled,
+            weekly_summary_weekday = excluded.weekly_summary_weekday,
+            weekly_summary_time = excluded.weekly_summary_time,
+            weekly_summary_delivery = excluded.weekly_summary_delivery,
+            idle_poll_seconds = excluded.idle_poll_seconds,
+            week_start_day = excluded.week_start_day,
+            max_session_minutes = excluded.max_session_minutes,
+            daily_goal_by_weekday = excluded.daily_goal_by_weekday;
     "#,
     )
-    .bind(id)
-    .bind(name)
-    .bind(daily_goal)
-    .bind(idle_timeout)
-    .bind(productivity)
-    .bind(allowed)
-    .bind(blocked)
-    .bind(backup)
-    .bind(screenshot_enabled)
-    .bind(screenshot_path)
-    .bind(screenshot_retention)
-    .bind(music_enabled)
-    .bind(music_playlist_type)
-    .bind(music_volume)
-    .bind(music_auto_play)
-    .bind(music_custom_url)
+    .bind(1)
+    .bind(&settings.skill_name)
+    .bind(settings.daily_goal_minutes)
+    .bind(settings.idle_timeout_minutes)
+    .bind(i64::from(settings.productivity_mode_enabled))
+    .bind(&allowed_apps)
+    .bind(&blocked_apps)
+    .bind(&settings.auto_backup_path)
+    .bind(i64::from(settings.screenshot_enabled))
+    .bind(&settings.screenshot_storage_path)
+    .bind(settings.screenshot_retention_days)
+    .bind(i64::from(settings.music_enabled))
+    .bind(&settings.music_playlist_type)
+    .bind(settings.music_volume)
+    .bind(i64::from(settings.music_auto_play))
+    .bind(&settings.music_custom_playlist_url)
+    .bind(i64::from(settings.screenshot_privacy_mode))
+    .bind(settings.quick_note_target.to_string())
+    .bind(settings.display_hours_precision.to_string())
+    .bind(settings.weekly_goal_minutes)
+    .bind(settings.screenshot_interval_min_minutes)
+    .bind(settings.screenshot_interval_max_minutes)
+    .bind(settings.screenshot_format.to_string())
+    .bind(i64::from(settings.screenshot_blur))
+    .bind(&settings.timezone)
+    .bind(settings.streak_grace_days)
+    .bind(settings.auto_backup_interval_hours)
+    .bind(settings.auto_backup_keep)
+    .bind(i64::from(settings.backup_compress))
+    .bind(&settings.productivity_active_from)
+    .bind(&settings.productivity_active_to)
+    .bind(settings.productivity_grace_seconds)
+    .bind(i64::from(settings.pomodoro_enabled))
+    .bind(settings.pomodoro_work_minutes)
+    .bind(settings.pomodoro_break_minutes)
+    .bind(settings.minimum_session_minutes)
+    .bind(i64::from(settings.api_enabled))
+    .bind(settings.api_port)
+    .bind(&settings.api_token)
+    .bind(&settings.goal_webhook_url)
+    .bind(&reflection_prompts)
+    .bind(settings.mastery_target_hours)
+    .bind(settings.goal_reminder_percent)
+    .bind(i64::from(settings.reminder_enabled))
+    .bind(&settings.reminder_time)
+    .bind(settings.idle_behavior.to_string())
+    .bind(settings.idle_prompt_timeout_minutes)
+    .bind(i64::from(settings.active_requires_focus_app))
+    .bind(settings.screenshot_max_storage_mb)
+    .bind(&streak_milestones)
+    .bind(i64::from(settings.weekly_summary_enabled))
+    .bind(settings.weekly_summary_weekday)
+    .bind(&settings.weekly_summary_time)
+    .bind(settings.weekly_summary_delivery.to_string())
+    .bind(settings.idle_poll_seconds)
+    .bind(settings.week_start_day.to_string())
+    .bind(settings.max_session_minutes)
+    .bind(&daily_goal_by_weekday)
     .execute(pool)
     .await?;
 
     // keep skills table name in sync
     sqlx::query("INSERT INTO skills (id, skill_name) VALUES (1, ?1) ON CONFLICT(id) DO UPDATE SET skill_name = excluded.skill_name")
-        .bind(name)
+        .bind(&settings.skill_name)
         .execute(pool)
         .await?;
 
     Ok(())
 }
 
+pub async fn load_pin_hash(pool: &SqlitePool) -> AppResult<Option<String>> {
+    let hash = sqlx::query_scalar::<_, Option<String>>("SELECT app_pin_hash FROM settings WHERE id = 1")
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    Ok(hash)
+}
+
+pub async fn save_pin_hash(pool: &SqlitePool, hash: &str) -> AppResult<()> {
+    sqlx::query("UPDATE settings SET app_pin_hash = ?1 WHERE id = 1")
+        .bind(hash)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// The highest streak milestone already celebrated. Kept out of `AppSettings` so saving settings
+/// from a stale form cannot lower it and trigger the same notification again.
+pub async fn load_streak_milestone(pool: &SqlitePool) -> AppResult<i64> {
+    let reached = sqlx::query_scalar::<_, i64>("SELECT streak_milestone_reached FROM settings WHERE id = 1")
+        .fetch_optional(pool)
+        .await?;
+    Ok(reached.unwrap_or(0))
+}
+
+pub async fn save_streak_milestone(pool: &SqlitePool, milestone_days: i64) -> AppResult<()> {
+    sqlx::query("UPDATE settings SET streak_milestone_reached = MAX(streak_milestone_reached, ?1) WHERE id = 1")
+        .bind(milestone_days)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Last day of the most recent week summarized, so each week is only sent once.
+pub async fn load_weekly_summary_sent(pool: &SqlitePool) -> AppResult<Option<NaiveDate>> {
+    let sent = sqlx::query_scalar::<_, Option<String>>("SELECT weekly_summary_last_sent FROM settings WHERE id = 1")
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    Ok(sent.and_then(|day| day.parse().ok()))
+}
+
+pub async fn save_weekly_summary_sent(pool: &SqlitePool, week_end: NaiveDate) -> AppResult<()> {
+    sqlx::query("UPDATE settings SET weekly_summary_last_sent = ?1 WHERE id = 1")
+        .bind(week_end.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 pub async fn ensure_skill(pool: &SqlitePool, name: &str) -> AppResult<i64> {
+    let mut conn = pool.acquire().await?;
+    ensure_skill_on(&mut conn, name).await
+}
+
+async fn ensure_skill_on(conn: &mut SqliteConnection, name: &str) -> AppResult<i64> {
     let existing = sqlx::query("SELECT id FROM skills WHERE skill_name = ?1 LIMIT 1")
         .bind(name)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
     if let Some(row) = existing {
@@ -186,12 +729,117 @@ pub async fn ensure_skill(pool: &SqlitePool, name: &str) -> AppResult<i64> {
 
     let result = sqlx::query("INSERT INTO skills (skill_name) VALUES (?1)")
         .bind(name)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
     Ok(result.last_insert_rowid())
 }
 
+/// Renames a skill in place, so its sessions follow it, and keeps the tracked skill setting in step.
+pub async fn rename_skill(pool: &SqlitePool, old_name: &str, new_name: &str) -> AppResult<()> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(AppError::Custom("Skill name cannot be empty".into()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let id: i64 = sqlx::query_scalar("SELECT id FROM skills WHERE skill_name = ?1")
+        .bind(old_name)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Skill '{old_name}' no longer exists")))?;
+    let clash: Option<i64> = sqlx::query_scalar("SELECT id FROM skills WHERE skill_name = ?1 AND id != ?2")
+        .bind(new_name)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if clash.is_some() {
+        return Err(AppError::Custom(format!("A skill named '{new_name}' already exists")));
+    }
+
+    sqlx::query("UPDATE skills SET skill_name = ?2 WHERE id = ?1")
+        .bind(id)
+        .bind(new_name)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE settings SET skill_name = ?2 WHERE id = 1 AND skill_name = ?1")
+        .bind(old_name)
+        .bind(new_name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Moves every session of `source_id` onto `target_id` and deletes the source skill, in one transaction.
+/// If the source was the tracked skill, the setting switches to the target.
+pub async fn merge_skills(pool: &SqlitePool, source_id: i64, target_id: i64) -> AppResult<SkillMergeReport> {
+    if source_id == target_id {
+        return Err(AppError::Custom("A skill cannot be merged into itself".into()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let find = |id: i64| sqlx::query_as::<_, Skill>("SELECT id, skill_name FROM skills WHERE id = ?1").bind(id);
+    let source = find(source_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Skill {source_id} no longer exists")))?;
+    let target = find(target_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Skill {target_id} no longer exists")))?;
+
+    let moved = sqlx::query("UPDATE sessions SET skill_id = ?2 WHERE skill_id = ?1")
+        .bind(source_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query("DELETE FROM skills WHERE id = ?1")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE settings SET skill_name = ?2 WHERE id = 1 AND skill_name = ?1")
+        .bind(&source.skill_name)
+        .bind(&target.skill_name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(SkillMergeReport { target, moved_sessions: moved })
+}
+
+pub async fn list_skills(pool: &SqlitePool) -> AppResult<Vec<Skill>> {
+    let skills = sqlx::query_as::<_, Skill>("SELECT id, skill_name FROM skills ORDER BY skill_name ASC")
+        .fetch_all(pool)
+        .await?;
+    Ok(skills)
+}
+
+/// Every skill with the minutes logged against it; skills without sessions report 0.
+pub async fn list_skill_summaries(pool: &SqlitePool) -> AppResult<Vec<SkillSummary>> {
+    let summaries = sqlx::query_as::<_, SkillSummary>(
+        r#"
+        SELECT skills.id, skills.skill_name, COALESCE(SUM(sessions.duration_minutes), 0.0) as total_minutes
+        FROM skills
+        LEFT JOIN sessions ON sessions.skill_id = skills.id AND sessions.deleted_at IS NULL
+        GROUP BY skills.id
+        ORDER BY skills.skill_name ASC
+    "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(summaries)
+}
+
+pub async fn fetch_skill(pool: &SqlitePool, skill_id: i64) -> AppResult<Option<Skill>> {
+    let skill = sqlx::query_as::<_, Skill>("SELECT id, skill_name FROM skills WHERE id = ?1")
+        .bind(skill_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(skill)
+}
+
 pub async fn insert_session(pool: &SqlitePool, skill_id: i64, start_time: DateTime<Utc>) -> AppResult<i64> {
     let result = sqlx::query(
         "INSERT INTO sessions (skill_id, start_time) VALUES (?1, ?2)",
@@ -204,23 +852,120 @@ pub async fn insert_session(pool: &SqlitePool, skill_id: i64, start_time: DateTi
     Ok(result.last_insert_rowid())
 }
 
+/// Logs practice that happened away from the timer as an already-finished session.
+pub async fn create_manual_session(
+    pool: &SqlitePool,
+    skill_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    reflections: &ReflectionInput,
+) -> AppResult<SessionHistoryRow> {
+    if end <= start {
+        return Err(AppError::Custom("End time must be after start time".into()));
+    }
+    ensure_no_overlap(pool, start, end, None).await?;
+
+    let minutes = (end - start).num_seconds() as f64 / 60.0;
+    let id = sqlx::query(
+        r#"
+        INSERT INTO sessions (
+            skill_id, start_time, end_time, duration_minutes, notes, what_practiced, what_learned, next_focus, tags,
+            reflection_extra
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+    "#,
+    )
+    .bind(skill_id)
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .bind(minutes)
+    .bind(reflections.notes.as_ref())
+    .bind(reflections.what_practiced.as_ref())
+    .bind(reflections.what_learned.as_ref())
+    .bind(reflections.next_focus.as_ref())
+    .bind(encode_tags(&reflections.tags))
+    .bind(encode_extra(&reflections.extra))
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    let record = sqlx::query_as::<_, SessionRecord>("SELECT * FROM sessions WHERE id = ?1")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    to_history_row(record).ok_or_else(|| AppError::Custom(format!("Session {id} could not be read back")))
+}
+
+/// Rejects a range that would double-count time already covered by another session.
+async fn ensure_no_overlap(
+    pool: &SqlitePool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_id: Option<i64>,
+) -> AppResult<()> {
+    let conflicts = overlapping_sessions(pool, start, end, exclude_id).await?;
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    let ids = conflicts.iter().map(|id| format!("#{id}")).collect::<Vec<_>>().join(", ");
+    Err(AppError::Custom(format!(
+        "{} to {} overlaps session {ids}",
+        start.to_rfc3339(),
+        end.to_rfc3339()
+    )))
+}
+
+/// Ids of sessions intersecting `[start, end)`; a session still open counts as running until now.
+pub async fn overlapping_sessions(
+    pool: &SqlitePool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_id: Option<i64>,
+) -> AppResult<Vec<i64>> {
+    let ids = sqlx::query_scalar(
+        r#"
+        SELECT id FROM sessions
+        WHERE deleted_at IS NULL
+          AND (?3 IS NULL OR id != ?3)
+          AND start_time < ?2
+          AND COALESCE(end_time, ?4) > ?1
+        ORDER BY start_time
+    "#,
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .bind(exclude_id)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+/// Saves a stopped session; `ended_at` is usually now, but earlier when a session is cut off at a cap.
 pub async fn finalize_session(
     pool: &SqlitePool,
     session_id: i64,
+    ended_at: DateTime<Utc>,
     duration_minutes: f64,
     reflections: &ReflectionInput,
 ) -> AppResult<()> {
-    let end_time = Utc::now().to_rfc3339();
+    let end_time = ended_at.to_rfc3339();
 
     sqlx::query(
         r#"
         UPDATE sessions
         SET end_time = ?2,
             duration_minutes = ?3,
-            notes = ?4,
+            notes = CASE
+                WHEN ?4 IS NULL OR ?4 = '' THEN notes
+                WHEN notes IS NULL OR notes = '' THEN ?4
+                ELSE notes || char(10) || ?4
+            END,
             what_practiced = ?5,
             what_learned = ?6,
-            next_focus = ?7
+            next_focus = ?7,
+            tags = ?8,
+            reflection_extra = ?9
         WHERE id = ?1
     "#,
     )
@@ -231,423 +976,3211 @@ pub async fn finalize_session(
     .bind(reflections.what_practiced.as_ref())
     .bind(reflections.what_learned.as_ref())
     .bind(reflections.next_focus.as_ref())
+    .bind(encode_tags(&reflections.tags))
+    .bind(encode_extra(&reflections.extra))
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
-pub async fn fetch_dashboard_stats(
-    pool: &SqlitePool,
-    settings: &AppSettings,
-    active_seconds: i64,
-) -> AppResult<DashboardStats> {
-    let now = Utc::now();
-    let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap();
-    let week_start = today_start - Duration::days(7);
-    let month_start = today_start - Duration::days(30);
-
-    let today_minutes = sum_minutes_since(pool, today_start).await?;
-    let week_minutes = sum_minutes_since(pool, week_start).await?;
-    let month_minutes = sum_minutes_since(pool, month_start).await?;
-    let total_minutes = sum_all_minutes(pool).await?;
-
-    let with_active = total_minutes + (active_seconds as f64 / 60.0);
-    let today_with_active = today_minutes + (active_seconds as f64 / 60.0);
-
-    let goal_progress = (with_active / (10_000.0 * 60.0)).min(1.0);
-    let daily_goal_hours = settings.daily_goal_minutes as f64 / 60.0;
-    let todays_goal_hours = (today_with_active / 60.0).min(daily_goal_hours);
-
-    let streak = compute_streak(pool, settings.daily_goal_minutes).await?;
+/// Saves the running total of an open session so a crash loses at most one checkpoint.
+pub async fn checkpoint_session(pool: &SqlitePool, session_id: i64, duration_minutes: f64) -> AppResult<()> {
+    sqlx::query("UPDATE sessions SET duration_minutes = ?2 WHERE id = ?1 AND end_time IS NULL")
+        .bind(session_id)
+        .bind(duration_minutes)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
 
-    Ok(DashboardStats {
-        today_hours: today_with_active / 60.0,
-        week_hours: week_minutes / 60.0,
-        month_hours: month_minutes / 60.0,
-        total_hours: with_active / 60.0,
-        goal_progress,
-        total_hours_target: 10_000.0,
-        daily_goal_hours,
-        todays_goal_hours,
-        streak_days: streak,
-    })
+/// Most recent session left open by a crash or forced quit.
+pub async fn fetch_unfinished_session(pool: &SqlitePool) -> AppResult<Option<SessionHistoryRow>> {
+    let record = sqlx::query_as::<_, SessionRecord>(
+        "SELECT * FROM sessions WHERE end_time IS NULL AND deleted_at IS NULL ORDER BY start_time DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(record.and_then(to_history_row))
 }
 
-async fn sum_minutes_since(pool: &SqlitePool, start: NaiveDateTime) -> AppResult<f64> {
-    let query = r#"
-        SELECT CAST(COALESCE(SUM(duration_minutes), 0) AS REAL) as total
-        FROM sessions
-        WHERE start_time >= ?1
-    "#;
-    let total: f64 = sqlx::query_scalar::<_, f64>(query)
-        .bind(Utc.from_utc_datetime(&start).to_rfc3339())
-        .fetch_one(pool)
+/// Closes an open session at its last checkpointed duration.
+pub async fn close_unfinished_session(pool: &SqlitePool, session_id: i64) -> AppResult<Option<SessionHistoryRow>> {
+    let Some(record) = sqlx::query_as::<_, SessionRecord>("SELECT * FROM sessions WHERE id = ?1 AND end_time IS NULL")
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let minutes = record.duration_minutes.unwrap_or(0.0).max(0.0);
+    let end_time = record.start_instant()? + Duration::seconds((minutes * 60.0).round() as i64);
+    sqlx::query("UPDATE sessions SET end_time = ?2, duration_minutes = ?3 WHERE id = ?1")
+        .bind(session_id)
+        .bind(end_time.to_rfc3339())
+        .bind(minutes)
+        .execute(pool)
         .await?;
-    Ok(total)
-}
 
-async fn sum_all_minutes(pool: &SqlitePool) -> AppResult<f64> {
-    let total: f64 = sqlx::query_scalar::<_, f64>("SELECT CAST(COALESCE(SUM(duration_minutes), 0) AS REAL) FROM sessions")
+    let closed = sqlx::query_as::<_, SessionRecord>("SELECT * FROM sessions WHERE id = ?1")
+        .bind(session_id)
         .fetch_one(pool)
         .await?;
-    Ok(total)
+    Ok(to_history_row(closed))
 }
 
-async fn compute_streak(pool: &SqlitePool, goal_minutes: i64) -> AppResult<u32> {
-    let rows = sqlx::query(
+/// Appends a line to a session's notes in a single statement so concurrent appends can't clobber each other.
+pub async fn append_session_note(pool: &SqlitePool, session_id: i64, line: &str) -> AppResult<()> {
+    let result = sqlx::query(
         r#"
-        SELECT date(start_time) as day, SUM(duration_minutes) as minutes
-        FROM sessions
-        GROUP BY date(start_time)
-        ORDER BY day DESC
-        LIMIT 60
+        UPDATE sessions
+        SET notes = CASE
+            WHEN notes IS NULL OR notes = '' THEN ?2
+            ELSE notes || char(10) || ?2
+        END
+        WHERE id = ?1 AND deleted_at IS NULL
     "#,
     )
-    .fetch_all(pool)
+    .bind(session_id)
+    .bind(line)
+    .execute(pool)
     .await?;
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(session_id));
+    }
+    Ok(())
+}
 
-    let mut streak = 0;
-    let mut current_day = chrono::Utc::now().date_naive();
+/// Replaces a session's notes; `None` clears them.
+pub async fn set_session_notes(pool: &SqlitePool, session_id: i64, notes: Option<&str>) -> AppResult<()> {
+    let result = sqlx::query("UPDATE sessions SET notes = ?2 WHERE id = ?1 AND deleted_at IS NULL")
+        .bind(session_id)
+        .bind(notes)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(session_id));
+    }
+    Ok(())
+}
 
-    for row in rows {
-        let day_str: String = row.try_get("day").unwrap_or_default();
-        if let Ok(day) = chrono::NaiveDate::parse_from_str(&day_str, "%Y-%m-%d") {
-            if day < current_day {
-                let diff = current_day.signed_duration_since(day).num_days();
-                if diff > 1 {
-                    break;
-                }
-            }
-            let minutes: f64 = row.try_get("minutes").unwrap_or(0.0);
-            if minutes >= goal_minutes as f64 {
-                streak += 1;
-                current_day = day.pred_opt().unwrap_or(day);
-            } else {
-                break;
-            }
-        }
+/// Moves a session onto another skill.
+pub async fn set_session_skill(pool: &SqlitePool, session_id: i64, skill_id: i64) -> AppResult<()> {
+    let result = sqlx::query("UPDATE sessions SET skill_id = ?2 WHERE id = ?1 AND deleted_at IS NULL")
+        .bind(session_id)
+        .bind(skill_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(session_id));
     }
+    Ok(())
+}
 
-    Ok(streak)
+pub async fn latest_session_id(pool: &SqlitePool) -> AppResult<Option<i64>> {
+    let id = sqlx::query_scalar::<_, i64>("SELECT id FROM sessions WHERE deleted_at IS NULL ORDER BY start_time DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await?;
+    Ok(id)
 }
 
-pub async fn list_sessions(pool: &SqlitePool) -> AppResult<Vec<SessionHistoryRow>> {
-    let records = sqlx::query_as::<_, SessionRecord>(
-        "SELECT * FROM sessions ORDER BY start_time DESC LIMIT 200",
-    )
-    .fetch_all(pool)
+pub async fn append_daily_note(pool: &SqlitePool, day: NaiveDate, line: &str) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO daily_notes (day, notes) VALUES (?1, ?2)
+        ON CONFLICT(day) DO UPDATE SET notes = daily_notes.notes || char(10) || excluded.notes
+    "#,
+    )
+    .bind(day.format("%Y-%m-%d").to_string())
+    .bind(line)
+    .execute(pool)
     .await?;
+    Ok(())
+}
 
-    let mapped = records
-        .into_iter()
-        .filter_map(|row| {
-            let duration = row.duration_minutes.unwrap_or_else(|| {
-                row.end_time
-                    .as_deref()
-                    .and_then(|end| {
-                        let start = DateTime::parse_from_rfc3339(&row.start_time).ok()?;
-                        let end_dt = DateTime::parse_from_rfc3339(end).ok()?;
-                        Some((end_dt - start).num_minutes() as f64)
-                    })
-                    .unwrap_or(0.0)
-            });
-
-            let start = DateTime::parse_from_rfc3339(&row.start_time).ok()?.with_timezone(&Utc);
-            let end = row
-                .end_time
-                .as_deref()
-                .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
-                .map(|dt| dt.with_timezone(&Utc));
+pub async fn fetch_daily_note(pool: &SqlitePool, day: NaiveDate) -> AppResult<Option<String>> {
+    let notes = sqlx::query_scalar::<_, String>("SELECT notes FROM daily_notes WHERE day = ?1")
+        .bind(day.format("%Y-%m-%d").to_string())
+        .fetch_optional(pool)
+        .await?;
+    Ok(notes)
+}
 
-            Some(SessionHistoryRow {
-                id: row.id,
-                start,
-                end,
-                duration_minutes: duration,
-                notes: row.notes,
-                what_practiced: row.what_practiced,
-                what_learned: row.what_learned,
-                next_focus: row.next_focus,
-            })
-        })
-        .collect();
+pub async fn record_session_event(
+    pool: &SqlitePool,
+    session_id: i64,
+    kind: SessionEventKind,
+    detail: Option<&str>,
+) -> AppResult<()> {
+    sqlx::query("INSERT INTO session_events (session_id, event_type, at, detail) VALUES (?1, ?2, ?3, ?4)")
+        .bind(session_id)
+        .bind(kind.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(detail)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
 
-    Ok(mapped)
+pub async fn fetch_session_events(pool: &SqlitePool, session_id: i64) -> AppResult<Vec<SessionEvent>> {
+    let events = sqlx::query_as::<_, SessionEvent>(
+        "SELECT * FROM session_events WHERE session_id = ?1 ORDER BY at ASC, id ASC",
+    )
+    .bind(session_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(events)
 }
 
-pub async fn update_session(pool: &SqlitePool, payload: &SessionEditPayload) -> AppResult<()> {
-    sqlx::query(
-        r#"
-        UPDATE sessions
-        SET start_time = ?2,
-            end_time = ?3,
-            duration_minutes = ?4,
-            notes = ?5,
-            what_practiced = ?6,
-            what_learned = ?7,
-            next_focus = ?8
-        WHERE id = ?1
-    "#,
+/// Page size of `recent_session_events` when the caller does not ask for one.
+pub const DEFAULT_RECENT_EVENTS: i64 = 100;
+const MAX_RECENT_EVENTS: i64 = 1000;
+
+/// The latest timeline rows across all sessions, newest first, for diagnosing odd sessions.
+pub async fn recent_session_events(pool: &SqlitePool, limit: i64) -> AppResult<Vec<SessionEvent>> {
+    let events = sqlx::query_as::<_, SessionEvent>(
+        "SELECT * FROM session_events ORDER BY at DESC, id DESC LIMIT ?1",
     )
-    .bind(payload.id)
-    .bind(payload.start_time.to_rfc3339())
-    .bind(payload.end_time.map(|dt| dt.to_rfc3339()))
-    .bind(payload.duration_minutes)
-    .bind(payload.notes.as_ref())
-    .bind(payload.what_practiced.as_ref())
-    .bind(payload.what_learned.as_ref())
-    .bind(payload.next_focus.as_ref())
-    .execute(pool)
+    .bind(limit.clamp(1, MAX_RECENT_EVENTS))
+    .fetch_all(pool)
     .await?;
+    Ok(events)
+}
 
+/// Links a capture on disk to the session that was running when it was taken.
+pub async fn record_screenshot(pool: &SqlitePool, session_id: i64, path: &Path) -> AppResult<()> {
+    sqlx::query("INSERT INTO screenshots (session_id, path, captured_at) VALUES (?1, ?2, ?3)")
+        .bind(session_id)
+        .bind(path.to_string_lossy())
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
     Ok(())
 }
 
-pub async fn delete_session(pool: &SqlitePool, session_id: i64) -> AppResult<()> {
-    sqlx::query("DELETE FROM sessions WHERE id = ?1")
-        .bind(session_id)
+/// Drops the link for a capture whose file has been deleted.
+pub async fn forget_screenshot(pool: &SqlitePool, path: &Path) -> AppResult<()> {
+    sqlx::query("DELETE FROM screenshots WHERE path = ?1")
+        .bind(path.to_string_lossy())
         .execute(pool)
         .await?;
     Ok(())
 }
 
-pub async fn export_sessions(
+pub async fn fetch_session_screenshots(pool: &SqlitePool, session_id: i64) -> AppResult<Vec<SessionScreenshot>> {
+    let screenshots = sqlx::query_as::<_, SessionScreenshot>(
+        "SELECT * FROM screenshots WHERE session_id = ?1 ORDER BY captured_at ASC, id ASC",
+    )
+    .bind(session_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(screenshots)
+}
+
+pub async fn fetch_dashboard_stats(
     pool: &SqlitePool,
-    format: &str,
-    output: &Path,
-    include_settings: bool,
-) -> AppResult<PathBuf> {
-    let sessions = list_sessions(pool).await?;
-    match format {
-        "csv" => export_csv(&sessions, output, include_settings, pool).await,
-        "json" => export_json(&sessions, output, include_settings, pool).await,
-        _ => Err(AppError::UnsupportedExportFormat),
-    }
+    settings: &AppSettings,
+    active_seconds: i64,
+) -> AppResult<DashboardStats> {
+    dashboard_stats_scoped(pool, settings, active_seconds, None).await
 }
 
-async fn export_csv(data: &[SessionHistoryRow], output: &Path, include_settings: bool, pool: &SqlitePool) -> AppResult<PathBuf> {
-    let mut wtr = csv::Writer::from_writer(Vec::new());
-    
-    // Write metadata header if including settings
-    if include_settings {
-        let settings = ensure_settings(pool).await?;
-        wtr.write_record(["# MasteryTrack Export"])?;
-        wtr.write_record(["# Format: CSV with Settings"])?;
-        wtr.write_record(["# Version: 1.0"])?;
-        wtr.write_record(["# Settings JSON:", &serde_json::to_string(&settings)?])?;
-        wtr.write_record(["", ""])?; // Empty row separator
-    }
-    
-    wtr.write_record([
-        "id",
-        "start_time",
-        "end_time",
-        "duration_minutes",
-        "notes",
-        "what_practiced",
-        "what_learned",
-        "next_focus",
-    ])?;
+/// Same as `fetch_dashboard_stats` but only counts sessions logged against `skill_id`.
+pub async fn fetch_dashboard_stats_for_skill(
+    pool: &SqlitePool,
+    settings: &AppSettings,
+    active_seconds: i64,
+    skill_id: i64,
+) -> AppResult<DashboardStats> {
+    dashboard_stats_scoped(pool, settings, active_seconds, Some(skill_id)).await
+}
 
-    for row in data {
-        wtr.write_record([
-            row.id.to_string(),
-            row.start.to_rfc3339(),
-            row.end.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
-            format!("{:.2}", row.duration_minutes),
-            row.notes.clone().unwrap_or_default(),
-            row.what_practiced.clone().unwrap_or_default(),
-            row.what_learned.clone().unwrap_or_default(),
-            row.next_focus.clone().unwrap_or_default(),
-        ])?;
-    }
-
-    let bytes = wtr.into_inner()?;
-    tokio::fs::write(output, bytes).await?;
-    Ok(output.to_path_buf())
+/// Sums cover finished sessions only; the running one is counted through `active_seconds`, since
+/// its checkpointed duration would otherwise be added twice.
+async fn dashboard_stats_scoped(
+    pool: &SqlitePool,
+    settings: &AppSettings,
+    active_seconds: i64,
+    skill_id: Option<i64>,
+) -> AppResult<DashboardStats> {
+    let zone = DayZone::from_setting(settings.timezone.as_deref());
+    let today = zone.today();
+    let today_start = zone.start_of_day(today);
+    let week_start = zone.start_of_day(settings.week_start_day.week_containing(today));
+    let month_start = zone.start_of_day(today - Duration::days(30));
+
+    let today_minutes = sum_minutes_since(pool, today_start, skill_id).await?;
+    let week_minutes = sum_minutes_since(pool, week_start, skill_id).await?;
+    let month_minutes = sum_minutes_since(pool, month_start, skill_id).await?;
+    let total_minutes = sum_all_minutes(pool, skill_id).await?;
+
+    let active_minutes = active_seconds as f64 / 60.0;
+    let with_active = total_minutes + active_minutes;
+    let today_with_active = today_minutes + active_minutes;
+
+    let target_minutes = settings.mastery_target_hours.max(MIN_MASTERY_TARGET_HOURS) as f64 * 60.0;
+    let mastery_progress = (with_active / target_minutes).clamp(0.0, 1.0);
+    // A zero or negative goal has nothing to fill, so it reports no progress rather than a negative bar
+    let daily_goal_hours = settings.daily_goal_for(today).max(0) as f64 / 60.0;
+    let todays_goal_hours = (today_with_active / 60.0).clamp(0.0, daily_goal_hours);
+
+    let weekly_goal = GoalProgress::new(settings.weekly_goal_minutes, week_minutes + active_minutes);
+
+    // Streak and practice days share the heatmap's local-day buckets
+    let all_days = daily_totals(pool, skill_id, None, zone).await?;
+    let streak = streak_from_days(&all_days, |day| settings.streak_goal_for(day), today, settings.streak_grace_days.max(0));
+    let total_practice_days = all_days.iter().filter(|day| day.minutes > 0.0).count() as i64;
+    let today_by_tag = tag_minutes_since(pool, today_start, skill_id).await?;
+    let lengths = session_lengths_since(pool, None, skill_id).await?;
+    let this_week = session_lengths_since(pool, Some(week_start), skill_id).await?;
+    let pace_start = zone.start_of_day(today - Duration::days(PACE_WINDOW_DAYS - 1));
+    let recent = daily_totals(pool, skill_id, Some(pace_start), zone).await?;
+    let recent_minutes: f64 = recent.iter().map(|d| d.minutes).sum();
+    let eta = project_completion(target_minutes - with_active, recent_minutes, PACE_WINDOW_DAYS, today);
+
+    let mut stats = DashboardStats {
+        today_hours: today_with_active / 60.0,
+        week_hours: (week_minutes + active_minutes) / 60.0,
+        month_hours: (month_minutes + active_minutes) / 60.0,
+        total_hours: with_active / 60.0,
+        mastery_progress,
+        total_hours_target: target_minutes / 60.0,
+        daily_goal_hours,
+        todays_goal_hours,
+        streak_days: streak,
+        total_practice_days,
+        weekly_goal,
+        today_by_tag,
+        avg_session_minutes: lengths.average,
+        longest_session_minutes: lengths.longest,
+        sessions_this_week: this_week.count,
+        eta_date: eta.map(|(date, _)| date),
+        eta_days_remaining: eta.map(|(_, days)| days),
+        display: DashboardDisplay::default(),
+    };
+    stats.display = DashboardDisplay::from_stats(&stats, settings.display_hours_precision);
+
+    Ok(stats)
 }
 
-async fn export_json(data: &[SessionHistoryRow], output: &Path, include_settings: bool, pool: &SqlitePool) -> AppResult<PathBuf> {
-    use serde_json::json;
-    
-    let mut export_data = json!({
-        "version": "1.0",
-        "exported_at": chrono::Utc::now().to_rfc3339(),
-        "sessions": data,
-    });
-    
-    if include_settings {
-        let settings = ensure_settings(pool).await?;
-        export_data["settings"] = json!(settings);
+const PACE_WINDOW_DAYS: i64 = 30;
+
+/// Days left and the date reached at `recent_minutes / window_days` a day; `None` without recent practice.
+fn project_completion(
+    remaining_minutes: f64,
+    recent_minutes: f64,
+    window_days: i64,
+    today: NaiveDate,
+) -> Option<(NaiveDate, f64)> {
+    let per_day = recent_minutes / window_days as f64;
+    if per_day <= 0.0 {
+        return None;
     }
-    
-    let json = serde_json::to_vec_pretty(&export_data)?;
-    tokio::fs::write(output, json).await?;
-    Ok(output.to_path_buf())
+    let days = (remaining_minutes.max(0.0) / per_day).max(0.0);
+    let date = today.checked_add_signed(Duration::days(days.ceil() as i64))?;
+    Some((date, days))
 }
 
-pub async fn backup_database<P: AsRef<Path>>(db_path: P, target_dir: &Path) -> AppResult<PathBuf> {
-    tokio::fs::create_dir_all(target_dir).await?;
-    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string();
-    let backup_path = target_dir.join(format!("masterytrack-{timestamp}.db"));
-    tokio::fs::copy(db_path, &backup_path).await?;
-    Ok(backup_path)
+const SUM_MINUTES_SINCE_SQL: &str = r#"
+    SELECT CAST(COALESCE(SUM(duration_minutes), 0) AS REAL) as total
+    FROM sessions
+    WHERE start_time >= ?1
+      AND (?2 IS NULL OR skill_id = ?2)
+      AND end_time IS NOT NULL
+      AND deleted_at IS NULL
+"#;
+
+async fn sum_minutes_since(pool: &SqlitePool, start: DateTime<Utc>, skill_id: Option<i64>) -> AppResult<f64> {
+    let total: f64 = sqlx::query_scalar::<_, f64>(SUM_MINUTES_SINCE_SQL)
+        .bind(start.to_rfc3339())
+        .bind(skill_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(total)
 }
 
-pub async fn import_data(
+/// Minutes in sessions finished since local midnight, cheap enough for the per-second tick to poll.
+/// The open session is left out because its checkpointed duration lags the live timer.
+pub async fn todays_minutes(pool: &SqlitePool, zone: DayZone) -> AppResult<f64> {
+    let total: f64 = sqlx::query_scalar(
+        r#"
+        SELECT CAST(COALESCE(SUM(duration_minutes), 0) AS REAL) FROM sessions
+        WHERE start_time >= ?1
+          AND end_time IS NOT NULL
+          AND deleted_at IS NULL
+    "#,
+    )
+    .bind(zone.start_of_day(zone.today()).to_rfc3339())
+    .fetch_one(pool)
+    .await?;
+    Ok(total)
+}
+
+struct SessionLengths {
+    count: i64,
+    average: f64,
+    longest: f64,
+}
+
+/// Count, mean and max duration of finished sessions; the open session has no end_time yet.
+async fn session_lengths_since(
     pool: &SqlitePool,
-    file_path: &Path,
-    import_settings: bool,
-) -> AppResult<()> {
-    let content = tokio::fs::read_to_string(file_path).await?;
-    
-    // Try JSON first
-    if let Ok(json_data) = serde_json::from_str::<serde_json::Value>(&content) {
-        // Check if it's our export format
-        if json_data.get("sessions").is_some() {
-            // Import sessions
-            if let Some(sessions) = json_data.get("sessions").and_then(|s| s.as_array()) {
-                for session in sessions {
-                    if let Ok(session_row) = serde_json::from_value::<SessionHistoryRow>(session.clone()) {
-                        // Insert or update session
-                        sqlx::query(
-                            r#"
-                            INSERT INTO sessions (id, start_time, end_time, duration_minutes, notes, what_practiced, what_learned, next_focus)
-                            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                            ON CONFLICT(id) DO UPDATE SET
-                                start_time = excluded.start_time,
-                                end_time = excluded.end_time,
-                                duration_minutes = excluded.duration_minutes,
-                                notes = excluded.notes,
-                                what_practiced = excluded.what_practiced,
-                                what_learned = excluded.what_learned,
-                                next_focus = excluded.next_focus
-                            "#,
-                        )
-                        .bind(session_row.id)
-                        .bind(session_row.start.to_rfc3339())
-                        .bind(session_row.end.map(|dt| dt.to_rfc3339()))
-                        .bind(session_row.duration_minutes)
-                        .bind(session_row.notes.as_ref())
-                        .bind(session_row.what_practiced.as_ref())
-                        .bind(session_row.what_learned.as_ref())
-                        .bind(session_row.next_focus.as_ref())
-                        .execute(pool)
-                        .await?;
-                    }
-                }
-            }
-            
-            // Import settings if requested
-            if import_settings {
-                if let Some(settings_json) = json_data.get("settings") {
-                    if let Ok(settings) = serde_json::from_value::<AppSettings>(settings_json.clone()) {
-                        save_settings(pool, &settings).await?;
-                    }
-                }
-            }
-            
-            return Ok(());
-        }
-        
-        // Fallback: treat as array of sessions
-        if let Ok(sessions) = serde_json::from_value::<Vec<SessionHistoryRow>>(json_data.clone()) {
-            for session_row in sessions {
-                sqlx::query(
-                    r#"
-                    INSERT INTO sessions (id, start_time, end_time, duration_minutes, notes, what_practiced, what_learned, next_focus)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                    ON CONFLICT(id) DO UPDATE SET
-                        start_time = excluded.start_time,
-                        end_time = excluded.end_time,
-                        duration_minutes = excluded.duration_minutes,
-                        notes = excluded.notes,
-                        what_practiced = excluded.what_practiced,
-                        what_learned = excluded.what_learned,
-                        next_focus = excluded.next_focus
-                    "#,
-                )
-                .bind(session_row.id)
-                .bind(session_row.start.to_rfc3339())
-                .bind(session_row.end.map(|dt| dt.to_rfc3339()))
-                .bind(session_row.duration_minutes)
-                .bind(session_row.notes.as_ref())
-                .bind(session_row.what_practiced.as_ref())
-                .bind(session_row.what_learned.as_ref())
-                .bind(session_row.next_focus.as_ref())
-                .execute(pool)
-                .await?;
-            }
-            return Ok(());
+    since: Option<DateTime<Utc>>,
+    skill_id: Option<i64>,
+) -> AppResult<SessionLengths> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) as sessions,
+               AVG(duration_minutes) as average,
+               MAX(duration_minutes) as longest
+        FROM sessions
+        WHERE end_time IS NOT NULL
+          AND deleted_at IS NULL
+          AND start_time >= COALESCE(?1, '')
+          AND (?2 IS NULL OR skill_id = ?2)
+    "#,
+    )
+    .bind(since.map(|dt| dt.to_rfc3339()))
+    .bind(skill_id)
+    .fetch_one(pool)
+    .await?;
+
+    // AVG/MAX over zero rows are NULL rather than a division by zero
+    Ok(SessionLengths {
+        count: row.try_get("sessions")?,
+        average: row.try_get::<Option<f64>, _>("average")?.unwrap_or(0.0),
+        longest: row.try_get::<Option<f64>, _>("longest")?.unwrap_or(0.0),
+    })
+}
+
+async fn sum_all_minutes(pool: &SqlitePool, skill_id: Option<i64>) -> AppResult<f64> {
+    let total: f64 = sqlx::query_scalar::<_, f64>("SELECT CAST(COALESCE(SUM(duration_minutes), 0) AS REAL) FROM sessions WHERE (?1 IS NULL OR skill_id = ?1) AND end_time IS NOT NULL AND deleted_at IS NULL")
+        .bind(skill_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(total)
+}
+
+/// Per-day totals bucketed by the local date each session started on, oldest first.
+async fn daily_totals(
+    pool: &SqlitePool,
+    skill_id: Option<i64>,
+    since: Option<DateTime<Utc>>,
+    zone: DayZone,
+) -> AppResult<Vec<DailyTotal>> {
+    // An optional bound written as `?2 IS NULL OR ..` cannot use the start_time index; COALESCE can
+    let rows = sqlx::query(
+        r#"
+        SELECT start_time, COALESCE(duration_minutes, 0) as minutes
+        FROM sessions
+        WHERE (?1 IS NULL OR skill_id = ?1)
+          AND start_time >= COALESCE(?2, '')
+          AND deleted_at IS NULL
+    "#,
+    )
+    .bind(skill_id)
+    .bind(since.map(|dt| dt.to_rfc3339()))
+    .fetch_all(pool)
+    .await?;
+
+    let sessions = rows.into_iter().filter_map(|row| {
+        let start: String = row.try_get("start_time").ok()?;
+        let start = DateTime::parse_from_rfc3339(&start).ok()?.with_timezone(&Utc);
+        let minutes: f64 = row.try_get("minutes").unwrap_or(0.0);
+        Some((start, minutes))
+    });
+
+    Ok(bucket_by_local_day(sessions, zone))
+}
+
+fn bucket_by_local_day(sessions: impl Iterator<Item = (DateTime<Utc>, f64)>, zone: DayZone) -> Vec<DailyTotal> {
+    let mut by_day: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for (start, minutes) in sessions {
+        *by_day.entry(zone.date_of(start)).or_insert(0.0) += minutes;
+    }
+    by_day
+        .into_iter()
+        .map(|(date, minutes)| DailyTotal { date, minutes })
+        .collect()
+}
+
+/// Counts goal-meeting days back from today, tolerating gaps of up to `grace_days` missed days.
+/// Today is never a miss since it may still be in progress; grace days themselves are not counted.
+/// Days `goal_for` returns `None` for are rest days: they neither count nor break the streak.
+fn streak_from_days(
+    days: &[DailyTotal],
+    goal_for: impl Fn(NaiveDate) -> Option<i64>,
+    today: NaiveDate,
+    grace_days: i64,
+) -> u32 {
+    let mut streak = 0;
+    let mut last_counted: Option<NaiveDate> = None;
+
+    for total in days.iter().rev() {
+        if total.date > today {
+            continue;
+        }
+        match goal_for(total.date) {
+            Some(goal) if total.minutes >= goal as f64 => {}
+            _ => continue,
         }
+        let until = last_counted.unwrap_or(today);
+        let missed = total
+            .date
+            .iter_days()
+            .skip(1)
+            .take_while(|day| *day < until)
+            .filter(|day| goal_for(*day).is_some())
+            .count() as i64;
+        if missed > grace_days {
+            break;
+        }
+        streak += 1;
+        last_counted = Some(total.date);
     }
-    
-    // Try CSV
-    let mut reader = csv::Reader::from_reader(content.as_bytes());
-    
-    // Skip metadata rows (lines starting with #)
-    let mut headers_skipped = false;
-    for result in reader.records() {
-        let record = result?;
-        
-        // Skip metadata rows
-        if let Some(first_field) = record.get(0) {
-            if first_field.starts_with('#') {
-                // Check if this is a settings row
-                if import_settings && first_field.contains("Settings JSON:") {
-                    if let Some(settings_str) = record.get(1) {
-                        if let Ok(settings) = serde_json::from_str::<AppSettings>(settings_str) {
-                            save_settings(pool, &settings).await?;
-                        }
-                    }
-                }
-                continue;
-            }
+
+    streak
+}
+
+const DASHBOARD_EXPORT_DAYS: i64 = 30;
+const MAX_HEATMAP_DAYS: i64 = 3660;
+
+/// Minutes per local calendar day for the last `days` days, oldest first, with empty days as zero.
+pub async fn practice_heatmap(pool: &SqlitePool, days: i64, zone: DayZone) -> AppResult<Vec<DailyTotal>> {
+    let days = days.clamp(1, MAX_HEATMAP_DAYS);
+    let today = zone.today();
+    let first_day = today - Duration::days(days - 1);
+
+    let totals = daily_totals(pool, None, Some(zone.start_of_day(first_day)), zone).await?;
+    Ok(fill_daily_gaps(first_day, today, &totals))
+}
+
+/// Recap of the seven local days ending on `to`, with the streak as it stands now.
+pub async fn weekly_summary(pool: &SqlitePool, settings: &AppSettings, to: NaiveDate) -> AppResult<WeeklySummary> {
+    let zone = DayZone::from_setting(settings.timezone.as_deref());
+    let from = to - Duration::days(6);
+    let totals = daily_totals(pool, None, Some(zone.start_of_day(from)), zone).await?;
+    let days = fill_daily_gaps(from, to, &totals);
+    let practiced = || days.iter().filter(|day| day.minutes > 0.0);
+    Ok(WeeklySummary {
+        from,
+        to,
+        total_minutes: days.iter().map(|day| day.minutes).sum(),
+        days_practiced: practiced().count() as u32,
+        best_day: practiced().max_by(|a, b| a.minutes.total_cmp(&b.minutes)).cloned(),
+        streak_days: fetch_dashboard_stats(pool, settings, 0).await?.streak_days,
+        days,
+    })
+}
+
+/// Minutes practiced per local hour of the day across all finished sessions, always 24 entries.
+/// A session counts entirely toward the hour it started in, even when it runs past the hour.
+pub async fn hourly_distribution(pool: &SqlitePool, zone: DayZone) -> AppResult<Vec<HourlyTotal>> {
+    // The running session has no end time yet and is left out until it is saved
+    let rows = sqlx::query(
+        r#"
+        SELECT start_time, COALESCE(duration_minutes, 0) as minutes
+        FROM sessions
+        WHERE end_time IS NOT NULL
+          AND deleted_at IS NULL
+    "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut hours: Vec<HourlyTotal> = (0..24).map(|hour| HourlyTotal { hour, minutes: 0.0 }).collect();
+    for row in rows {
+        let start: String = row.try_get("start_time")?;
+        let Ok(start) = DateTime::parse_from_rfc3339(&start) else {
+            continue;
+        };
+        let hour = zone.time_of(start.with_timezone(&Utc)).hour() as usize;
+        hours[hour].minutes += row.try_get::<f64, _>("minutes").unwrap_or(0.0);
+    }
+    Ok(hours)
+}
+
+fn fill_daily_gaps(first_day: NaiveDate, last_day: NaiveDate, totals: &[DailyTotal]) -> Vec<DailyTotal> {
+    first_day
+        .iter_days()
+        .take_while(|day| *day <= last_day)
+        .map(|date| DailyTotal {
+            date,
+            minutes: totals
+                .iter()
+                .find(|t| t.date == date)
+                .map(|t| t.minutes)
+                .unwrap_or(0.0),
+        })
+        .collect()
+}
+
+pub async fn fetch_practice_records(pool: &SqlitePool, zone: DayZone) -> AppResult<PracticeRecords> {
+    let days = daily_totals(pool, None, None, zone).await?;
+    Ok(compute_records(&days))
+}
+
+/// Best single day and best 7-day rolling window; ties resolve to the most recent.
+fn compute_records(days: &[DailyTotal]) -> PracticeRecords {
+    let mut best_day: Option<DailyTotal> = None;
+    let mut best_week: Option<WeekRecord> = None;
+
+    for (idx, day) in days.iter().enumerate() {
+        if day.minutes <= 0.0 {
+            continue;
         }
-        
-        // Parse CSV record
-        if !headers_skipped {
-            headers_skipped = true;
-            continue; // Skip header row
-        }
-        
-        let id: i64 = record.get(0).and_then(|s| s.parse().ok()).unwrap_or(0);
-        let start_time = record.get(1).unwrap_or("");
-        let end_time = record.get(2);
-        let duration: f64 = record.get(3).and_then(|s| s.parse().ok()).unwrap_or(0.0);
-        let notes = record.get(4);
-        let what_practiced = record.get(5);
-        let what_learned = record.get(6);
-        let next_focus = record.get(7);
-        
-        if id > 0 && !start_time.is_empty() {
-            sqlx::query(
-                r#"
-                INSERT INTO sessions (id, start_time, end_time, duration_minutes, notes, what_practiced, what_learned, next_focus)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                ON CONFLICT(id) DO UPDATE SET
-                    start_time = excluded.start_time,
-                    end_time = excluded.end_time,
-                    duration_minutes = excluded.duration_minutes,
-                    notes = excluded.notes,
-                    what_practiced = excluded.what_practiced,
-                    what_learned = excluded.what_learned,
-                    next_focus = excluded.next_focus
-                "#,
-            )
-            .bind(id)
-            .bind(start_time)
-            .bind(end_time)
-            .bind(duration)
-            .bind(notes)
-            .bind(what_practiced)
-            .bind(what_learned)
-            .bind(next_focus)
-            .execute(pool)
-            .await?;
+        if !matches!(&best_day, Some(best) if day.minutes < best.minutes) {
+            best_day = Some(day.clone());
+        }
+
+        // A best window can always be shifted to begin on a practiced day.
+        let window_end = day.date + Duration::days(6);
+        let minutes: f64 = days[idx..]
+            .iter()
+            .take_while(|d| d.date <= window_end)
+            .map(|d| d.minutes)
+            .sum();
+        if !matches!(&best_week, Some(best) if minutes < best.minutes) {
+            best_week = Some(WeekRecord {
+                start_date: day.date,
+                minutes,
+            });
         }
     }
-    
-    Ok(())
+
+    PracticeRecords { best_day, best_week }
+}
+
+/// Page size used when the caller does not ask for one.
+pub const DEFAULT_SESSION_PAGE: i64 = 200;
+const MAX_SESSION_PAGE: i64 = 1000;
+
+pub async fn list_sessions(pool: &SqlitePool, filter: &SessionFilter) -> AppResult<Vec<SessionHistoryRow>> {
+    let limit = filter.limit.unwrap_or(DEFAULT_SESSION_PAGE).clamp(1, MAX_SESSION_PAGE);
+    query_sessions(pool, filter, limit).await
+}
+
+/// Every session matching the filter's date bounds, ignoring paging; used for exports.
+pub async fn all_sessions(pool: &SqlitePool, filter: &SessionFilter) -> AppResult<Vec<SessionHistoryRow>> {
+    // SQLite treats a negative LIMIT as no limit
+    query_sessions(pool, filter, -1).await
+}
+
+async fn query_sessions(pool: &SqlitePool, filter: &SessionFilter, limit: i64) -> AppResult<Vec<SessionHistoryRow>> {
+    let records = sqlx::query_as::<_, SessionRecord>(
+        r#"
+        SELECT * FROM sessions
+        WHERE (?1 IS NULL OR start_time >= ?1)
+          AND (?2 IS NULL OR start_time < ?2)
+          AND deleted_at IS NULL
+        ORDER BY start_time DESC
+        LIMIT ?3 OFFSET ?4
+    "#,
+    )
+    .bind(filter.from.map(|dt| dt.to_rfc3339()))
+    .bind(filter.to.map(|dt| dt.to_rfc3339()))
+    .bind(limit)
+    .bind(filter.offset.unwrap_or(0).max(0))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records.into_iter().filter_map(to_history_row).collect())
+}
+
+/// Sessions started since local midnight, newest first, without paging through the full history.
+pub async fn todays_sessions(pool: &SqlitePool, zone: DayZone) -> AppResult<Vec<SessionHistoryRow>> {
+    let filter = SessionFilter {
+        from: Some(zone.start_of_day(zone.today())),
+        ..SessionFilter::default()
+    };
+    all_sessions(pool, &filter).await
+}
+
+/// How many sessions fall within the filter's date bounds, for paging.
+pub async fn count_sessions(pool: &SqlitePool, filter: &SessionFilter) -> AppResult<i64> {
+    let total = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM sessions
+        WHERE (?1 IS NULL OR start_time >= ?1)
+          AND (?2 IS NULL OR start_time < ?2)
+          AND deleted_at IS NULL
+    "#,
+    )
+    .bind(filter.from.map(|dt| dt.to_rfc3339()))
+    .bind(filter.to.map(|dt| dt.to_rfc3339()))
+    .fetch_one(pool)
+    .await?;
+    Ok(total)
+}
+
+fn to_history_row(row: SessionRecord) -> Option<SessionHistoryRow> {
+    let duration = row.duration_minutes.unwrap_or_else(|| {
+        row.end_time
+            .as_deref()
+            .and_then(|end| {
+                let start = DateTime::parse_from_rfc3339(&row.start_time).ok()?;
+                let end_dt = DateTime::parse_from_rfc3339(end).ok()?;
+                Some((end_dt - start).num_seconds() as f64 / 60.0)
+            })
+            .unwrap_or(0.0)
+    });
+
+    let start = DateTime::parse_from_rfc3339(&row.start_time).ok()?.with_timezone(&Utc);
+    let end = row
+        .end_time
+        .as_deref()
+        .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+
+    let tags = decode_tags(row.tags.as_deref());
+    Some(SessionHistoryRow {
+        id: row.id,
+        start,
+        end,
+        duration_minutes: duration,
+        notes: row.notes,
+        what_practiced: row.what_practiced,
+        what_learned: row.what_learned,
+        next_focus: row.next_focus,
+        tags,
+        reflection_extra: decode_extra(row.reflection_extra.as_deref()),
+    })
+}
+
+/// Tags are stored as a JSON array; an empty list is stored as NULL.
+fn encode_tags(tags: &[String]) -> Option<String> {
+    let cleaned = normalize_tags(tags.iter().map(String::as_str));
+    if cleaned.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&cleaned).ok()
+    }
+}
+
+fn decode_tags(raw: Option<&str>) -> Vec<String> {
+    raw.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+/// Prompt answers are stored as a JSON object without blank answers; none at all is stored as NULL.
+fn encode_extra(extra: &HashMap<String, String>) -> Option<String> {
+    let answered: BTreeMap<&str, &str> = extra
+        .iter()
+        .map(|(prompt, answer)| (prompt.trim(), answer.trim()))
+        .filter(|(prompt, answer)| !prompt.is_empty() && !answer.is_empty())
+        .collect();
+    if answered.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&answered).ok()
+    }
+}
+
+fn decode_extra(raw: Option<&str>) -> HashMap<String, String> {
+    raw.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+fn decode_csv_tags(raw: &str) -> Vec<String> {
+    normalize_tags(raw.split(';'))
+}
+
+fn normalize_tags<'a>(tags: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags.map(str::trim).filter(|t| !t.is_empty()) {
+        if !cleaned.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+pub async fn list_sessions_by_tag(pool: &SqlitePool, tag: &str) -> AppResult<Vec<SessionHistoryRow>> {
+    let records = sqlx::query_as::<_, SessionRecord>(
+        r#"
+        SELECT * FROM sessions
+        WHERE deleted_at IS NULL
+          AND EXISTS (
+            SELECT 1 FROM json_each(sessions.tags) WHERE lower(json_each.value) = lower(?1)
+        )
+        ORDER BY start_time DESC
+        LIMIT 200
+    "#,
+    )
+    .bind(tag.trim())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records.into_iter().filter_map(to_history_row).collect())
+}
+
+/// Sessions whose reflections match `query`, best match first, with a highlighted snippet of the match.
+/// Uses the full-text index when there is one and a plain substring scan otherwise.
+pub async fn search_sessions(pool: &SqlitePool, query: &str) -> AppResult<Vec<SessionSearchHit>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    match fts_query(query) {
+        Some(terms) if search_index_available(pool).await? => full_text_search(pool, &terms).await,
+        // Also covers queries made only of punctuation, which the full-text tokenizer would drop
+        _ => substring_search(pool, query).await,
+    }
+}
+
+async fn full_text_search(pool: &SqlitePool, terms: &str) -> AppResult<Vec<SessionSearchHit>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT sessions.*, snippet(sessions_fts, -1, '[', ']', '…', 12) as snippet
+        FROM sessions_fts
+        JOIN sessions ON sessions.id = sessions_fts.rowid
+        WHERE sessions_fts MATCH ?1
+          AND sessions.deleted_at IS NULL
+        ORDER BY sessions_fts.rank, sessions.start_time DESC
+        LIMIT 200
+    "#,
+    )
+    .bind(terms)
+    .fetch_all(pool)
+    .await?;
+
+    let mut hits = Vec::with_capacity(rows.len());
+    for row in rows {
+        let snippet: Option<String> = row.try_get("snippet")?;
+        if let Some(session) = to_history_row(SessionRecord::from_row(&row)?) {
+            hits.push(SessionSearchHit { session, snippet });
+        }
+    }
+    Ok(hits)
+}
+
+async fn substring_search(pool: &SqlitePool, query: &str) -> AppResult<Vec<SessionSearchHit>> {
+    let pattern = format!("%{}%", escape_like(query));
+    let records = sqlx::query_as::<_, SessionRecord>(
+        r#"
+        SELECT * FROM sessions
+        WHERE deleted_at IS NULL
+          AND (notes LIKE ?1 ESCAPE '\'
+            OR what_practiced LIKE ?1 ESCAPE '\'
+            OR what_learned LIKE ?1 ESCAPE '\'
+            OR next_focus LIKE ?1 ESCAPE '\')
+        ORDER BY start_time DESC
+        LIMIT 200
+    "#,
+    )
+    .bind(pattern)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .filter_map(to_history_row)
+        .map(|session| SessionSearchHit { session, snippet: None })
+        .collect())
+}
+
+/// Turns free text into an FTS5 query where every word must appear, as a prefix. Words are quoted so
+/// FTS5 operators typed by the user are matched as text; `None` when no word has anything to index.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Makes `%`, `_` and the escape character itself match literally in a `LIKE .. ESCAPE '\'` pattern.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Minutes per tag since `start`; a session with several tags counts toward each.
+async fn tag_minutes_since(pool: &SqlitePool, start: DateTime<Utc>, skill_id: Option<i64>) -> AppResult<Vec<TagHours>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT json_each.value as tag, SUM(sessions.duration_minutes) as minutes
+        FROM sessions, json_each(sessions.tags)
+        WHERE sessions.start_time >= ?1
+          AND (?2 IS NULL OR sessions.skill_id = ?2)
+          AND sessions.end_time IS NOT NULL
+          AND sessions.deleted_at IS NULL
+        GROUP BY json_each.value
+        ORDER BY minutes DESC
+    "#,
+    )
+    .bind(start.to_rfc3339())
+    .bind(skill_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TagHours {
+            tag: row.get::<String, _>("tag"),
+            hours: row.get::<Option<f64>, _>("minutes").unwrap_or(0.0) / 60.0,
+        })
+        .collect())
+}
+
+pub async fn update_session(pool: &SqlitePool, payload: &SessionEditPayload) -> AppResult<()> {
+    let end = match payload.end_time {
+        Some(end) if end <= payload.start_time => {
+            return Err(AppError::Custom("End time must be after start time".into()));
+        }
+        Some(end) => end,
+        None => payload.start_time + Duration::seconds((payload.duration_minutes * 60.0) as i64),
+    };
+    ensure_no_overlap(pool, payload.start_time, end, Some(payload.id)).await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE sessions
+        SET start_time = ?2,
+            end_time = ?3,
+            duration_minutes = ?4,
+            notes = ?5,
+            what_practiced = ?6,
+            what_learned = ?7,
+            next_focus = ?8,
+            tags = ?9
+        WHERE id = ?1 AND deleted_at IS NULL
+    "#,
+    )
+    .bind(payload.id)
+    .bind(payload.start_time.to_rfc3339())
+    .bind(payload.end_time.map(|dt| dt.to_rfc3339()))
+    .bind(payload.duration_minutes)
+    .bind(payload.notes.as_ref())
+    .bind(payload.what_practiced.as_ref())
+    .bind(payload.what_learned.as_ref())
+    .bind(payload.next_focus.as_ref())
+    .bind(encode_tags(&payload.tags))
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(payload.id));
+    }
+    Ok(())
+}
+
+/// Moves a session to the trash; it drops out of history and totals until restored or purged.
+pub async fn delete_session(pool: &SqlitePool, session_id: i64) -> AppResult<()> {
+    let result = sqlx::query("UPDATE sessions SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL")
+        .bind(session_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(session_id));
+    }
+    Ok(())
+}
+
+/// Removes a session for good, skipping the trash; used for stops under the minimum length.
+pub async fn discard_session(pool: &SqlitePool, session_id: i64) -> AppResult<()> {
+    let result = sqlx::query("DELETE FROM sessions WHERE id = ?1").bind(session_id).execute(pool).await?;
+    if result.rows_affected() == 0 {
+        return Err(session_not_found(session_id));
+    }
+    Ok(())
+}
+
+/// Moves every finished session starting in `[from, to)` to the trash in one statement, returning
+/// how many went. The running session is left alone so the timer keeps its row.
+pub async fn delete_sessions_in_range(pool: &SqlitePool, from: DateTime<Utc>, to: DateTime<Utc>) -> AppResult<u64> {
+    if from >= to {
+        return Err(AppError::Custom("The start of the range must be before its end".into()));
+    }
+    let result = sqlx::query(
+        "UPDATE sessions SET deleted_at = ?3
+        WHERE start_time >= ?1 AND start_time < ?2 AND end_time IS NOT NULL AND deleted_at IS NULL",
+    )
+    .bind(from.to_rfc3339())
+    .bind(to.to_rfc3339())
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Brings a deleted session back, unless a session logged since then now covers the same time.
+pub async fn restore_session(pool: &SqlitePool, session_id: i64) -> AppResult<()> {
+    let record = sqlx::query_as::<_, SessionRecord>("SELECT * FROM sessions WHERE id = ?1 AND deleted_at IS NOT NULL")
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Session #{session_id} is not in the trash")))?;
+    let start = record.start_instant()?;
+    let end = record
+        .end_time
+        .as_deref()
+        .and_then(|end| DateTime::parse_from_rfc3339(end).ok())
+        .map_or_else(Utc::now, |end| end.with_timezone(&Utc));
+    ensure_no_overlap(pool, start, end, Some(session_id)).await?;
+
+    sqlx::query("UPDATE sessions SET deleted_at = NULL WHERE id = ?1")
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Permanently removes sessions deleted at least `older_than_days` ago, returning how many went.
+pub async fn purge_deleted(pool: &SqlitePool, older_than_days: i64) -> AppResult<u64> {
+    let cutoff = Utc::now() - Duration::days(older_than_days.max(0));
+    let result = sqlx::query("DELETE FROM sessions WHERE deleted_at IS NOT NULL AND deleted_at <= ?1")
+        .bind(cutoff.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Deletes every session (trash included), skill, daily note and screenshot link, and puts the
+/// settings back to their defaults, which recreates the default skill. The app PIN is kept so the
+/// reset cannot unlock the app.
+pub async fn factory_reset(pool: &SqlitePool) -> AppResult<FactoryResetReport> {
+    let mut tx = pool.begin().await?;
+    for table in ["screenshots", "session_events", "daily_notes"] {
+        sqlx::query(&format!("DELETE FROM {table}")).execute(&mut *tx).await?;
+    }
+    let sessions_removed = sqlx::query("DELETE FROM sessions").execute(&mut *tx).await?.rows_affected();
+    let skills_removed = sqlx::query("DELETE FROM skills").execute(&mut *tx).await?.rows_affected();
+    // Bookkeeping columns outside `AppSettings` that would otherwise survive the reset
+    sqlx::query("UPDATE settings SET streak_milestone_reached = 0, weekly_summary_last_sent = NULL WHERE id = 1")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    save_settings(pool, &AppSettings::default()).await?;
+    Ok(FactoryResetReport { sessions_removed, skills_removed, screenshots_removed: 0 })
+}
+
+fn session_not_found(session_id: i64) -> AppError {
+    AppError::NotFound(format!("Session #{session_id} no longer exists"))
+}
+
+/// Session columns written by `export_csv`, in order.
+const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "start_time",
+    "end_time",
+    "duration_minutes",
+    "notes",
+    "what_practiced",
+    "what_learned",
+    "next_focus",
+    "tags",
+];
+/// Exports written before session tags existed end at `next_focus`.
+const CSV_COLUMNS_BEFORE_TAGS: usize = 8;
+/// Marks the trailing CSV columns holding answers to custom reflection prompts.
+const CSV_PROMPT_PREFIX: &str = "prompt:";
+
+pub async fn export_sessions(
+    pool: &SqlitePool,
+    format: &str,
+    output: &Path,
+    include_settings: bool,
+) -> AppResult<PathBuf> {
+    match format {
+        "csv" => export_csv(pool, output, include_settings).await?,
+        "jsonl" => export_json_lines(pool, output).await?,
+        _ => {
+            let sessions = all_sessions(pool, &SessionFilter::default()).await?;
+            let bytes = render_export(pool, format, &sessions, include_settings).await?;
+            tokio::fs::write(output, bytes).await?;
+        }
+    }
+    Ok(output.to_path_buf())
+}
+
+/// Streams sessions, newest first, straight from the query to the file as one JSON object per
+/// line, so the whole history is never held in memory. There is no room for a settings block.
+async fn export_json_lines(pool: &SqlitePool, output: &Path) -> AppResult<()> {
+    let mut out = tokio::io::BufWriter::new(tokio::fs::File::create(output).await?);
+    let mut records = sqlx::query_as::<_, SessionRecord>(
+        "SELECT * FROM sessions WHERE deleted_at IS NULL ORDER BY start_time DESC",
+    )
+    .fetch(pool);
+    while let Some(record) = records.try_next().await? {
+        if let Some(row) = to_history_row(record) {
+            let mut line = serde_json::to_vec(&row)?;
+            line.push(b'\n');
+            out.write_all(&line).await?;
+        }
+    }
+    out.flush().await?;
+    Ok(())
+}
+
+/// Sessions read to estimate the size of an export without rendering all of them.
+const EXPORT_PREVIEW_SAMPLE: i64 = 50;
+
+/// Counts the sessions an export would contain and estimates its size from a rendered sample,
+/// without writing anything.
+pub async fn export_preview(pool: &SqlitePool, format: &str, include_settings: bool) -> AppResult<ExportPreview> {
+    let filter = SessionFilter::default();
+    let session_count = count_sessions(pool, &filter).await?;
+    let sample = query_sessions(pool, &filter, EXPORT_PREVIEW_SAMPLE).await?;
+    // Headers and metadata are paid once; only the per-session part grows with the count
+    let overhead = render_export(pool, format, &[], include_settings).await?.len() as u64;
+    let estimated_bytes = if sample.is_empty() {
+        overhead
+    } else {
+        let sampled = render_export(pool, format, &sample, include_settings).await?.len() as u64;
+        let per_session = sampled.saturating_sub(overhead) as f64 / sample.len() as f64;
+        overhead + (per_session * session_count as f64).round() as u64
+    };
+    Ok(ExportPreview { session_count, estimated_bytes })
+}
+
+async fn render_export(pool: &SqlitePool, format: &str, data: &[SessionHistoryRow], include_settings: bool) -> AppResult<Vec<u8>> {
+    match format {
+        "csv" => render_csv(data, include_settings, pool).await,
+        "json" => render_json(data, include_settings, pool).await,
+        "md" => Ok(render_markdown(data).into_bytes()),
+        "ics" => Ok(render_ics(data, &session_skill_names(pool).await?, Utc::now()).into_bytes()),
+        "jsonl" => {
+            let mut bytes = Vec::new();
+            for row in data {
+                serde_json::to_writer(&mut bytes, row)?;
+                bytes.push(b'\n');
+            }
+            Ok(bytes)
+        }
+        _ => Err(AppError::UnsupportedExportFormat),
+    }
+}
+
+async fn render_csv(data: &[SessionHistoryRow], include_settings: bool, pool: &SqlitePool) -> AppResult<Vec<u8>> {
+    let mut wtr = csv_writer(Vec::new());
+    if include_settings {
+        write_csv_settings(&mut wtr, &ensure_settings(pool).await?)?;
+    }
+
+    // One extra column per custom prompt answered anywhere in the export
+    let prompts: BTreeSet<String> = data.iter().flat_map(|row| row.reflection_extra.keys().cloned()).collect();
+    wtr.write_record(csv_header(&prompts))?;
+    for row in data {
+        wtr.write_record(csv_record(row, &prompts))?;
+    }
+
+    Ok(wtr.into_inner()?)
+}
+
+/// Writes the same file as `render_csv`, but streams sessions from the query to disk one record
+/// at a time instead of loading the whole history first.
+async fn export_csv(pool: &SqlitePool, output: &Path, include_settings: bool) -> AppResult<()> {
+    let mut out = tokio::io::BufWriter::new(tokio::fs::File::create(output).await?);
+    let mut head = csv_writer(Vec::new());
+    if include_settings {
+        write_csv_settings(&mut head, &ensure_settings(pool).await?)?;
+    }
+    // The header names every custom prompt, so those are gathered in a first, lighter pass
+    let prompts = exported_prompts(pool).await?;
+    head.write_record(csv_header(&prompts))?;
+    out.write_all(&head.into_inner()?).await?;
+
+    let mut records = sqlx::query_as::<_, SessionRecord>(
+        "SELECT * FROM sessions WHERE deleted_at IS NULL ORDER BY start_time DESC",
+    )
+    .fetch(pool);
+    while let Some(record) = records.try_next().await? {
+        if let Some(row) = to_history_row(record) {
+            let mut line = csv::Writer::from_writer(Vec::new());
+            line.write_record(csv_record(&row, &prompts))?;
+            out.write_all(&line.into_inner()?).await?;
+        }
+    }
+    out.flush().await?;
+    Ok(())
+}
+
+/// Every custom prompt answered by a live session, in column order.
+async fn exported_prompts(pool: &SqlitePool) -> AppResult<BTreeSet<String>> {
+    let mut prompts = BTreeSet::new();
+    let mut extras = sqlx::query_scalar::<_, String>(
+        "SELECT reflection_extra FROM sessions WHERE deleted_at IS NULL AND reflection_extra IS NOT NULL",
+    )
+    .fetch(pool);
+    while let Some(extra) = extras.try_next().await? {
+        prompts.extend(decode_extra(Some(&extra)).into_keys());
+    }
+    Ok(prompts)
+}
+
+fn csv_writer<W: std::io::Write>(inner: W) -> csv::Writer<W> {
+    // The metadata rows are shorter than the session rows
+    csv::WriterBuilder::new().flexible(true).from_writer(inner)
+}
+
+fn write_csv_settings<W: std::io::Write>(wtr: &mut csv::Writer<W>, settings: &AppSettings) -> AppResult<()> {
+    wtr.write_record(["# MasteryTrack Export"])?;
+    wtr.write_record(["# Format: CSV with Settings"])?;
+    wtr.write_record(["# Version: 1.0"])?;
+    wtr.write_record(["# Settings JSON:", &serde_json::to_string(settings)?])?;
+    wtr.write_record(["", ""])?; // Empty row separator
+    Ok(())
+}
+
+fn csv_header(prompts: &BTreeSet<String>) -> Vec<String> {
+    let mut header: Vec<String> = CSV_COLUMNS.iter().map(|c| c.to_string()).collect();
+    header.extend(prompts.iter().map(|p| format!("{CSV_PROMPT_PREFIX}{p}")));
+    header
+}
+
+fn csv_record(row: &SessionHistoryRow, prompts: &BTreeSet<String>) -> Vec<String> {
+    let mut record = vec![
+        row.id.to_string(),
+        row.start.to_rfc3339(),
+        row.end.map(|dt| dt.to_rfc3339()).unwrap_or_default(),
+        format!("{:.2}", row.duration_minutes),
+        row.notes.clone().unwrap_or_default(),
+        row.what_practiced.clone().unwrap_or_default(),
+        row.what_learned.clone().unwrap_or_default(),
+        row.next_focus.clone().unwrap_or_default(),
+        row.tags.join(";"),
+    ];
+    record.extend(prompts.iter().map(|p| row.reflection_extra.get(p).cloned().unwrap_or_default()));
+    record
+}
+
+async fn render_json(data: &[SessionHistoryRow], include_settings: bool, pool: &SqlitePool) -> AppResult<Vec<u8>> {
+    use serde_json::json;
+    
+    let mut export_data = json!({
+        "version": "1.0",
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "sessions": data,
+    });
+    
+    if include_settings {
+        let settings = ensure_settings(pool).await?;
+        export_data["settings"] = json!(settings);
+    }
+    
+    Ok(serde_json::to_vec_pretty(&export_data)?)
+}
+
+/// Writes the dashboard numbers plus minutes per day over the trailing month, for sharing progress.
+pub async fn export_dashboard(
+    pool: &SqlitePool,
+    settings: &AppSettings,
+    active_seconds: i64,
+    format: &ExportFormat,
+    output: &Path,
+) -> AppResult<PathBuf> {
+    let stats = fetch_dashboard_stats(pool, settings, active_seconds).await?;
+    let zone = DayZone::from_setting(settings.timezone.as_deref());
+    let days = practice_heatmap(pool, DASHBOARD_EXPORT_DAYS, zone).await?;
+    let content = match format {
+        ExportFormat::Csv => dashboard_csv(&stats, &days)?,
+        ExportFormat::Json => serde_json::to_vec_pretty(&serde_json::json!({
+            "version": "1.0",
+            "exported_at": chrono::Utc::now().to_rfc3339(),
+            "stats": stats,
+            "days": days,
+        }))?,
+        _ => return Err(AppError::UnsupportedExportFormat),
+    };
+    tokio::fs::write(output, content).await?;
+    Ok(output.to_path_buf())
+}
+
+/// One row per day, then a `total` row with the period's minutes, all-time hours and the streak.
+fn dashboard_csv(stats: &DashboardStats, days: &[DailyTotal]) -> AppResult<Vec<u8>> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(["date", "minutes", "total_hours", "streak_days"])?;
+    for day in days {
+        wtr.write_record([day.date.to_string(), format!("{:.2}", day.minutes), String::new(), String::new()])?;
+    }
+    let minutes: f64 = days.iter().map(|d| d.minutes).sum();
+    wtr.write_record([
+        "total".to_string(),
+        format!("{minutes:.2}"),
+        format!("{:.2}", stats.total_hours),
+        stats.streak_days.to_string(),
+    ])?;
+    Ok(wtr.into_inner()?)
+}
+
+/// Skill name of every session, keyed by session id, for the calendar export.
+async fn session_skill_names(pool: &SqlitePool) -> AppResult<HashMap<i64, String>> {
+    let names = sqlx::query_as::<_, (i64, String)>(
+        "SELECT sessions.id, skills.skill_name FROM sessions JOIN skills ON skills.id = sessions.skill_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(names.into_iter().collect())
+}
+
+/// Renders finished sessions as an iCalendar file with one `VEVENT` each; open sessions are skipped.
+fn render_ics(data: &[SessionHistoryRow], skills: &HashMap<i64, String>, stamp: DateTime<Utc>) -> String {
+    const ICS_TIME: &str = "%Y%m%dT%H%M%SZ";
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//MasteryTrack//Session Export//EN".to_string(),
+    ];
+    for row in data {
+        let Some(end) = row.end else {
+            continue;
+        };
+        let skill = skills.get(&row.id).map(String::as_str).unwrap_or("Practice");
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:session-{}@masterytrack", row.id));
+        lines.push(format!("DTSTAMP:{}", stamp.format(ICS_TIME)));
+        lines.push(format!("DTSTART:{}", row.start.format(ICS_TIME)));
+        lines.push(format!("DTEND:{}", end.format(ICS_TIME)));
+        lines.push(format!("SUMMARY:{}", escape_ics_text(skill)));
+        let description: Vec<String> = [
+            ("What I practiced", &row.what_practiced),
+            ("What I learned", &row.what_learned),
+            ("Next focus", &row.next_focus),
+            ("Notes", &row.notes),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| {
+            let text = value.as_deref().map(str::trim).filter(|t| !t.is_empty())?;
+            Some(format!("{label}: {text}"))
+        })
+        .collect();
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_ics_text(&description.join("\n"))));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_ics_line(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Escapes TEXT values per RFC 5545 section 3.3.11.
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits lines longer than 75 octets, continuing with a leading space, without breaking a UTF-8 character.
+fn fold_ics_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out
+}
+
+/// Renders sessions as a journal: one `##` heading per day, one `###` per session.
+fn render_markdown(data: &[SessionHistoryRow]) -> String {
+    let mut out = String::from("# MasteryTrack Journal\n");
+    let mut days: Vec<(NaiveDate, Vec<&SessionHistoryRow>)> = Vec::new();
+    for row in data {
+        let date = row.start.date_naive();
+        match days.last_mut() {
+            Some((day, rows)) if *day == date => rows.push(row),
+            _ => days.push((date, vec![row])),
+        }
+    }
+
+    for (date, rows) in days {
+        let total: f64 = rows.iter().map(|r| r.duration_minutes).sum();
+        out.push_str(&format!("\n## {} ({:.0} min)\n", date.format("%Y-%m-%d"), total));
+        for row in rows {
+            out.push_str(&format!(
+                "\n### {} ({:.0} min)\n\n",
+                row.start.format("%H:%M"),
+                row.duration_minutes
+            ));
+            let fields = [
+                ("What I practiced", &row.what_practiced),
+                ("What I learned", &row.what_learned),
+                ("Next focus", &row.next_focus),
+                ("Notes", &row.notes),
+            ];
+            if !row.tags.is_empty() {
+                out.push_str(&format!("- **Tags:** {}\n", row.tags.join(", ")));
+            }
+            for (label, value) in fields {
+                if let Some(text) = value.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+                    out.push_str(&format!("- **{label}:** {}\n", text.replace('\n', " ")));
+                }
+            }
+        }
+    }
+    out
+}
+
+pub async fn backup_database<P: AsRef<Path>>(
+    pool: &SqlitePool,
+    db_path: P,
+    target_dir: &Path,
+    compress: bool,
+) -> AppResult<PathBuf> {
+    // Recent writes live in the `-wal` file until checkpointed; fold them in so the copy has them
+    let (busy, _, _): (i64, i64, i64) = sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE)").fetch_one(pool).await?;
+    if busy != 0 {
+        return Err(AppError::Custom("The database is busy; try the backup again in a moment".into()));
+    }
+    tokio::fs::create_dir_all(target_dir).await?;
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    if !compress {
+        let backup_path = target_dir.join(format!("masterytrack-{timestamp}.db"));
+        tokio::fs::copy(db_path, &backup_path).await?;
+        return Ok(backup_path);
+    }
+
+    let backup_path = target_dir.join(format!("masterytrack-{timestamp}.db.gz"));
+    let source = db_path.as_ref().to_path_buf();
+    let target = backup_path.clone();
+    tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        use flate2::{write::GzEncoder, Compression};
+        let mut input = std::fs::File::open(source)?;
+        let mut encoder = GzEncoder::new(std::fs::File::create(target)?, Compression::default());
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Backup task failed: {e}")))??;
+    Ok(backup_path)
+}
+
+/// Writes a backup to `dest` as a plain database file, decompressing `.gz` backups.
+async fn materialize_backup(backup: &Path, dest: &Path) -> AppResult<()> {
+    let is_gzip = backup.extension().and_then(|e| e.to_str()) == Some("gz");
+    if !is_gzip {
+        tokio::fs::copy(backup, dest).await?;
+        return Ok(());
+    }
+
+    let (source, target) = (backup.to_path_buf(), dest.to_path_buf());
+    tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        let mut decoder = flate2::read::GzDecoder::new(std::fs::File::open(source)?);
+        let mut output = std::fs::File::create(target)?;
+        std::io::copy(&mut decoder, &mut output)?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("Restore task failed: {e}")))??;
+    Ok(())
+}
+
+/// Replaces the live database with `backup` after checking it is an intact MasteryTrack database.
+pub async fn restore_database(pool: &SharedPool, db_path: &Path, backup: &Path) -> AppResult<()> {
+    let staged = db_path.with_extension("db.restore");
+    materialize_backup(backup, &staged).await?;
+    if let Err(e) = verify_database_file(&staged, pool.passphrase().as_deref()).await {
+        tokio::fs::remove_file(&staged).await.ok();
+        return Err(e);
+    }
+    swap_database_file(pool, db_path, &staged, pool.passphrase()).await
+}
+
+/// Closes the pool, moves `staged` over the live database and reopens it with `passphrase`. If
+/// the new file will not open, the original is moved back and reopened so the app stays usable.
+async fn swap_database_file(pool: &SharedPool, db_path: &Path, staged: &Path, passphrase: Option<String>) -> AppResult<()> {
+    let previous_passphrase = pool.passphrase();
+    pool.get().close().await;
+    remove_sidecars(db_path).await;
+    let previous = db_path.with_extension("db.previous");
+    tokio::fs::remove_file(&previous).await.ok();
+    if let Err(e) = tokio::fs::rename(db_path, &previous).await {
+        pool.reopen(db_path, previous_passphrase).await?;
+        return Err(e.into());
+    }
+    if let Err(e) = tokio::fs::rename(staged, db_path).await {
+        tokio::fs::rename(&previous, db_path).await?;
+        pool.reopen(db_path, previous_passphrase).await?;
+        return Err(e.into());
+    }
+
+    if let Err(e) = pool.reopen(db_path, passphrase).await {
+        log::warn!("Swapped-in database would not open, putting the original back: {e}");
+        remove_sidecars(db_path).await;
+        tokio::fs::rename(&previous, db_path).await?;
+        pool.reopen(db_path, previous_passphrase).await?;
+        return Err(e);
+    }
+    tokio::fs::remove_file(&previous).await.ok();
+    Ok(())
+}
+
+async fn remove_sidecars(db_path: &Path) {
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        tokio::fs::remove_file(PathBuf::from(sidecar)).await.ok();
+    }
+}
+
+/// Encrypts the database with `new_passphrase`, or re-keys it if it is already encrypted.
+pub async fn change_passphrase(pool: &SharedPool, db_path: &Path, new_passphrase: &str) -> AppResult<()> {
+    if new_passphrase.is_empty() {
+        return Err(AppError::Custom("The new passphrase cannot be empty".into()));
+    }
+    let live = pool.get();
+    ensure_sqlcipher(&live).await?;
+
+    match pool.passphrase() {
+        None => {
+            let staged = db_path.with_extension("db.encrypted");
+            if let Err(e) = export_encrypted(&live, &staged, new_passphrase).await {
+                tokio::fs::remove_file(&staged).await.ok();
+                return Err(e);
+            }
+            swap_database_file(pool, db_path, &staged, Some(new_passphrase.to_string())).await
+        }
+        Some(current) => {
+            live.close().await;
+            let rekeyed = rekey(db_path, &current, new_passphrase).await;
+            let passphrase = if rekeyed.is_ok() { new_passphrase.to_string() } else { current };
+            pool.reopen(db_path, Some(passphrase)).await?;
+            rekeyed
+        }
+    }
+}
+
+/// `PRAGMA rekey` cannot encrypt a plaintext file, so copy everything into an encrypted one instead.
+async fn export_encrypted(live: &SqlitePool, staged: &Path, passphrase: &str) -> AppResult<()> {
+    tokio::fs::remove_file(staged).await.ok();
+    let mut conn = live.acquire().await?;
+    sqlx::query(&format!(
+        "ATTACH DATABASE {} AS encrypted KEY {}",
+        sql_string(&staged.to_string_lossy()),
+        sql_string(passphrase)
+    ))
+    .execute(&mut *conn)
+    .await?;
+    let exported = sqlx::query("SELECT sqlcipher_export('encrypted')").execute(&mut *conn).await;
+    sqlx::query("DETACH DATABASE encrypted").execute(&mut *conn).await?;
+    exported?;
+    Ok(())
+}
+
+/// Re-keys on a lone connection, with the WAL folded back into the main file first.
+async fn rekey(db_path: &Path, current: &str, new_passphrase: &str) -> AppResult<()> {
+    let opts = keyed(SqliteConnectOptions::new().filename(db_path), Some(current));
+    let single = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .map_err(key_error)?;
+    let result = async {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&single).await?;
+        sqlx::query("PRAGMA journal_mode = DELETE").execute(&single).await?;
+        sqlx::query(&format!("PRAGMA rekey = {}", sql_string(new_passphrase)))
+            .execute(&single)
+            .await?;
+        Ok(())
+    }
+    .await;
+    single.close().await;
+    result
+}
+
+/// Checks a staged copy before it replaces the live database. The copy is opened writable because
+/// the integrity check of the full-text index needs to write, which is harmless on a throwaway copy.
+async fn verify_database_file(path: &Path, passphrase: Option<&str>) -> AppResult<()> {
+    let opts = keyed(SqliteConnectOptions::new().filename(path), passphrase);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .map_err(|e| AppError::Custom(format!("Backup is not a readable SQLite database: {e}")))?;
+
+    let result = async {
+        let integrity: String = sqlx::query_scalar("PRAGMA integrity_check").fetch_one(&pool).await?;
+        if integrity != "ok" {
+            return Err(AppError::Custom(format!("Backup failed integrity check: {integrity}")));
+        }
+        let has_sessions: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'sessions'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_sessions == 0 {
+            return Err(AppError::Custom("Backup is not a MasteryTrack database".into()));
+        }
+        Ok(())
+    }
+    .await;
+
+    pool.close().await;
+    result
+}
+
+/// Checks integrity, folds the WAL back into the database and reclaims free pages.
+pub async fn run_maintenance(pool: &SqlitePool, db_path: &Path) -> AppResult<MaintenanceReport> {
+    let size_before_bytes = database_size(db_path).await;
+
+    // Each step locks the database only while it runs; other connections may write in between,
+    // which none of them depends on
+    let mut conn = pool.acquire().await?;
+    let problems: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check").fetch_all(&mut *conn).await?;
+    let integrity = problems.join("; ");
+    if integrity != "ok" {
+        return Err(AppError::Custom(format!("Integrity check failed: {integrity}")));
+    }
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *conn).await?;
+    sqlx::query("VACUUM").execute(&mut *conn).await?;
+    drop(conn);
+
+    Ok(MaintenanceReport {
+        integrity,
+        size_before_bytes,
+        size_after_bytes: database_size(db_path).await,
+    })
+}
+
+/// Checks the database can take writes: a scratch file beside it for the directory, and a
+/// rolled-back update for the database itself, so nothing is left behind.
+pub async fn probe_writable(pool: &SqlitePool, db_path: &Path) -> AppResult<()> {
+    let mut scratch = db_path.as_os_str().to_owned();
+    scratch.push(".write-test");
+    let scratch = PathBuf::from(scratch);
+    tokio::fs::write(&scratch, b"ok").await?;
+    tokio::fs::remove_file(&scratch).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE settings SET id = id WHERE id = 1").execute(&mut *tx).await?;
+    tx.rollback().await?;
+    Ok(())
+}
+
+async fn database_size(db_path: &Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    let mut total = 0;
+    for path in [db_path.to_path_buf(), PathBuf::from(wal)] {
+        total += tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+    }
+    total
+}
+
+/// Merges an export file into the existing history inside one transaction. Sessions are matched
+/// on `(start_time, skill_id)`: unknown ones are inserted, known ones are only updated when the
+/// imported copy ran longer (a later end time), and reflection text is never replaced by blanks.
+pub async fn import_data(
+    pool: &SqlitePool,
+    file_path: &Path,
+    import_settings: bool,
+) -> AppResult<ImportSummary> {
+    let content = tokio::fs::read_to_string(file_path).await?;
+    let format = import_format(file_path, &content);
+    // Parse everything up front so a malformed file is rejected before anything is written
+    let sessions = parse_exported_sessions(&content, &format)?;
+    let settings = if import_settings {
+        exported_settings(&content, &format)?
+    } else {
+        None
+    };
+
+    let summary = merge_sessions(pool, sessions).await?;
+    if let Some(settings) = settings {
+        save_settings(pool, &settings).await?;
+    }
+    Ok(summary)
+}
+
+/// Merges parsed export rows into the history inside one transaction, matching on
+/// `(start_time, skill_id)` as described on `import_data`. Rows without a skill belong to the
+/// skill currently being tracked.
+async fn merge_sessions(pool: &SqlitePool, sessions: Vec<ExportedSession>) -> AppResult<ImportSummary> {
+    let default_skill = ensure_settings(pool).await?.skill_name;
+    let mut skill_ids: HashMap<String, i64> = HashMap::new();
+    let mut summary = ImportSummary::default();
+    let mut tx = pool.begin().await?;
+    for session in sessions {
+        let skill = session.skill.unwrap_or_else(|| default_skill.clone());
+        let skill_id = match skill_ids.get(&skill) {
+            Some(id) => *id,
+            None => {
+                let id = ensure_skill_on(&mut tx, &skill).await?;
+                skill_ids.insert(skill, id);
+                id
+            }
+        };
+
+        let row = session.row;
+        let existing = sqlx::query(
+            "SELECT id, end_time FROM sessions
+            WHERE skill_id = ?1 AND start_time = ?2 AND deleted_at IS NULL LIMIT 1",
+        )
+        .bind(skill_id)
+        .bind(row.start.to_rfc3339())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(existing) = existing else {
+            sqlx::query(
+                r#"
+                INSERT INTO sessions (
+                    skill_id, start_time, end_time, duration_minutes, notes, what_practiced, what_learned, next_focus, tags,
+                    reflection_extra
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                "#,
+            )
+            .bind(skill_id)
+            .bind(row.start.to_rfc3339())
+            .bind(row.end.map(|dt| dt.to_rfc3339()))
+            .bind(row.duration_minutes)
+            .bind(row.notes.as_ref())
+            .bind(row.what_practiced.as_ref())
+            .bind(row.what_learned.as_ref())
+            .bind(row.next_focus.as_ref())
+            .bind(encode_tags(&row.tags))
+            .bind(encode_extra(&row.reflection_extra))
+            .execute(&mut *tx)
+            .await?;
+            summary.inserted += 1;
+            continue;
+        };
+
+        // A stored session that is still open counts as older than any finished copy
+        let stored_end = existing
+            .get::<Option<String>, _>("end_time")
+            .and_then(|end| DateTime::parse_from_rfc3339(&end).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        if row.end <= stored_end {
+            summary.skipped += 1;
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE sessions
+            SET end_time = ?2,
+                duration_minutes = ?3,
+                notes = COALESCE(NULLIF(TRIM(?4), ''), notes),
+                what_practiced = COALESCE(NULLIF(TRIM(?5), ''), what_practiced),
+                what_learned = COALESCE(NULLIF(TRIM(?6), ''), what_learned),
+                next_focus = COALESCE(NULLIF(TRIM(?7), ''), next_focus),
+                tags = COALESCE(?8, tags),
+                reflection_extra = COALESCE(?9, reflection_extra)
+            WHERE id = ?1
+            "#,
+        )
+        .bind(existing.get::<i64, _>("id"))
+        .bind(row.end.map(|dt| dt.to_rfc3339()))
+        .bind(row.duration_minutes)
+        .bind(row.notes.as_ref())
+        .bind(row.what_practiced.as_ref())
+        .bind(row.what_learned.as_ref())
+        .bind(row.next_focus.as_ref())
+        .bind(encode_tags(&row.tags))
+        .bind(encode_extra(&row.reflection_extra))
+        .execute(&mut *tx)
+        .await?;
+        summary.updated += 1;
+    }
+    tx.commit().await?;
+    Ok(summary)
+}
+
+/// The format of the export at `path`: the one its extension names, otherwise a guess from the
+/// content.
+fn import_format(path: &Path, content: &str) -> ExportFormat {
+    if let Some(format) = path.extension().and_then(|e| e.to_str()).and_then(|e| e.parse().ok()) {
+        return format;
+    }
+    let trimmed = content.trim_start();
+    if !trimmed.starts_with(['{', '[']) {
+        return ExportFormat::Csv;
+    }
+    // A JSON Lines export opens with `{` too, but never parses as one export document
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(serde_json::Value::Object(export)) if !export.contains_key("sessions") => ExportFormat::JsonLines,
+        Err(_) if trimmed.starts_with('{') => ExportFormat::JsonLines,
+        _ => ExportFormat::Json,
+    }
+}
+
+/// The settings block embedded in an export, if it was written with one.
+fn exported_settings(content: &str, format: &ExportFormat) -> AppResult<Option<AppSettings>> {
+    let raw = match format {
+        ExportFormat::Json => match serde_json::from_str::<serde_json::Value>(content)? {
+            serde_json::Value::Object(mut export) => export.remove("settings"),
+            _ => None,
+        },
+        ExportFormat::JsonLines => None,
+        _ => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(content.as_bytes());
+            let mut found = None;
+            for record in reader.records() {
+                let record = record?;
+                if record.get(0).is_some_and(|f| f.starts_with('#') && f.contains("Settings JSON:")) {
+                    found = record.get(1).map(serde_json::from_str).transpose()?;
+                    break;
+                }
+            }
+            found
+        }
+    };
+    raw.map(|settings| {
+        serde_json::from_value(settings).map_err(|e| AppError::Custom(format!("Settings in the export are invalid: {e}")))
+    })
+    .transpose()
+}
+
+/// A session read back from an export file, with the skill it was logged under when the file says so.
+#[derive(Debug, serde::Deserialize)]
+struct ExportedSession {
+    #[serde(flatten)]
+    row: SessionHistoryRow,
+    #[serde(default)]
+    skill: Option<String>,
+}
+
+/// Re-imports a CSV, JSON or JSON Lines file written by `export_sessions`, merging it the same way
+/// as `import_data`, so importing the same file twice is harmless.
+pub async fn import_sessions(pool: &SqlitePool, path: &Path, format: &ExportFormat) -> AppResult<ImportSummary> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let sessions = parse_exported_sessions(&content, format)?;
+    merge_sessions(pool, sessions).await
+}
+
+fn parse_exported_sessions(content: &str, format: &ExportFormat) -> AppResult<Vec<ExportedSession>> {
+    match format {
+        ExportFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(content)
+                .map_err(|e| AppError::Custom(format!("Not a valid JSON export: {e}")))?;
+            let sessions = match value {
+                serde_json::Value::Object(mut export) => export
+                    .remove("sessions")
+                    .ok_or_else(|| AppError::Custom("JSON export has no \"sessions\" list".into()))?,
+                list @ serde_json::Value::Array(_) => list,
+                _ => return Err(AppError::Custom("JSON export must be an object or a list of sessions".into())),
+            };
+            serde_json::from_value(sessions).map_err(|e| AppError::Custom(format!("Invalid session in JSON export: {e}")))
+        }
+        ExportFormat::JsonLines => content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| AppError::Custom(format!("Invalid session on line {} of the JSON Lines export: {e}", index + 1)))
+            })
+            .collect(),
+        ExportFormat::Csv => parse_csv_sessions(content),
+        ExportFormat::Markdown => Err(AppError::Custom("Markdown exports cannot be imported".into())),
+        ExportFormat::Ics => Err(AppError::Custom("Calendar exports cannot be imported".into())),
+    }
+}
+
+/// Reads the session table of a CSV export, skipping the `#` metadata block written with settings.
+fn parse_csv_sessions(content: &str) -> AppResult<Vec<ExportedSession>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut header: Option<Vec<String>> = None;
+    let mut sessions = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        if record.get(0).is_some_and(|f| f.starts_with('#')) || record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let Some(columns) = &header else {
+            let columns: Vec<String> = record.iter().map(|h| h.trim().to_string()).collect();
+            validate_csv_header(&columns, line)?;
+            header = Some(columns);
+            continue;
+        };
+        if record.len() != columns.len() {
+            return Err(AppError::Custom(format!(
+                "Line {line}: expected {} fields, found {}",
+                columns.len(),
+                record.len()
+            )));
+        }
+
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c == name)
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        let invalid = |name: &str| {
+            AppError::Custom(format!("Line {line}, field {name}: invalid value '{}'", field(name).unwrap_or("")))
+        };
+        let timestamp = |name: &str| -> AppResult<Option<DateTime<Utc>>> {
+            field(name)
+                .map(|v| DateTime::parse_from_rfc3339(v).map(|dt| dt.with_timezone(&Utc)).map_err(|_| invalid(name)))
+                .transpose()
+        };
+
+        let id = field("id").and_then(|v| v.parse::<i64>().ok()).ok_or_else(|| invalid("id"))?;
+        let start = timestamp("start_time")?.ok_or_else(|| invalid("start_time"))?;
+        let end = timestamp("end_time")?;
+        let duration_minutes = match field("duration_minutes") {
+            Some(v) => v.parse::<f64>().map_err(|_| invalid("duration_minutes"))?,
+            None => end.map(|end| (end - start).num_seconds() as f64 / 60.0).unwrap_or(0.0),
+        };
+        let text = |name: &str| field(name).map(String::from);
+
+        sessions.push(ExportedSession {
+            row: SessionHistoryRow {
+                id,
+                start,
+                end,
+                duration_minutes,
+                notes: text("notes"),
+                what_practiced: text("what_practiced"),
+                what_learned: text("what_learned"),
+                next_focus: text("next_focus"),
+                tags: decode_csv_tags(field("tags").unwrap_or("")),
+                reflection_extra: columns
+                    .iter()
+                    .zip(record.iter())
+                    .filter_map(|(column, value)| {
+                        let prompt = column.strip_prefix(CSV_PROMPT_PREFIX)?;
+                        Some((prompt.to_string(), value.trim().to_string())).filter(|(_, v)| !v.is_empty())
+                    })
+                    .collect(),
+            },
+            skill: text("skill"),
+        });
+    }
+
+    if header.is_none() {
+        return Err(AppError::Custom("CSV file has no header row".into()));
+    }
+    Ok(sessions)
+}
+
+/// Accepts the column layout `export_csv` writes, or the one from before tags existed, optionally
+/// followed by a `skill` column; custom prompt columns may appear anywhere after the fixed ones.
+fn validate_csv_header(columns: &[String], line: u64) -> AppResult<()> {
+    let mut known: Vec<&str> = columns
+        .iter()
+        .map(String::as_str)
+        .filter(|c| !c.starts_with(CSV_PROMPT_PREFIX))
+        .collect();
+    if known.last() == Some(&"skill") {
+        known.pop();
+    }
+    if known.len() == CSV_COLUMNS.len() || known.len() == CSV_COLUMNS_BEFORE_TAGS {
+        if let Some((i, (found, expected))) = known.iter().zip(CSV_COLUMNS).enumerate().find(|(_, (f, e))| *f != e) {
+            return Err(AppError::Custom(format!(
+                "Line {line}: column {} is '{found}', expected '{expected}'",
+                i + 1
+            )));
+        }
+        return Ok(());
+    }
+    let missing: Vec<&str> = CSV_COLUMNS
+        .iter()
+        .copied()
+        .filter(|expected| !known.contains(expected))
+        .collect();
+    if missing.is_empty() {
+        Err(AppError::Custom(format!("Line {line}: unexpected columns in CSV header")))
+    } else {
+        Err(AppError::Custom(format!("Line {line}: CSV header is missing {}", missing.join(", "))))
+    }
+}
+
+/// A fresh in-memory database with every migration applied.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WeekStartDay;
+    use chrono::TimeZone;
+
+    fn history_row(id: i64, start: &str, minutes: f64, practiced: Option<&str>) -> SessionHistoryRow {
+        SessionHistoryRow {
+            id,
+            start: DateTime::parse_from_rfc3339(start).unwrap().with_timezone(&Utc),
+            end: None,
+            duration_minutes: minutes,
+            notes: Some("  ".into()),
+            what_practiced: practiced.map(String::from),
+            what_learned: None,
+            next_focus: None,
+            tags: Vec::new(),
+            reflection_extra: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn practice_days_count_local_days_with_time() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        for start in ["2024-03-01T09:00:00Z", "2024-03-01T18:00:00Z", "2024-03-04T09:00:00Z"] {
+            insert_session(&pool, skill, utc(start)).await.unwrap();
+        }
+        sqlx::query("UPDATE sessions SET duration_minutes = 30 WHERE start_time < '2024-03-02'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut settings = ensure_settings(&pool).await.unwrap();
+        settings.timezone = Some("UTC".into());
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.total_practice_days, 1);
+    }
+
+    #[tokio::test]
+    async fn mastery_progress_follows_the_target_setting() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let start = Utc::now() - Duration::hours(10);
+        let id = insert_session(&pool, skill, start).await.unwrap();
+        finalize_session(&pool, id, Utc::now(), 600.0, &ReflectionInput::default()).await.unwrap();
+
+        let mut settings = ensure_settings(&pool).await.unwrap();
+        settings.mastery_target_hours = 20;
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.total_hours_target, 20.0);
+        assert_eq!(stats.mastery_progress, 0.5);
+
+        settings.mastery_target_hours = 0;
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.total_hours_target, MIN_MASTERY_TARGET_HOURS as f64);
+
+        let legacy: serde_json::Value = serde_json::to_value(&stats).unwrap();
+        let mut legacy = legacy.as_object().unwrap().clone();
+        let progress = legacy.remove("mastery_progress").unwrap();
+        legacy.insert("goal_progress".into(), progress);
+        let parsed: DashboardStats = serde_json::from_value(legacy.into()).unwrap();
+        assert_eq!(parsed.mastery_progress, 1.0);
+    }
+
+    #[tokio::test]
+    async fn merging_skills_moves_sessions_and_follows_the_setting() {
+        let pool = test_pool().await;
+        let settings = AppSettings { skill_name: "piano".into(), ..ensure_settings(&pool).await.unwrap() };
+        save_settings(&pool, &settings).await.unwrap();
+        let source = ensure_skill(&pool, "piano").await.unwrap();
+        let target = ensure_skill(&pool, "Piano").await.unwrap();
+        for _ in 0..2 {
+            insert_session(&pool, source, Utc::now()).await.unwrap();
+        }
+        insert_session(&pool, target, Utc::now()).await.unwrap();
+
+        assert!(merge_skills(&pool, target, target).await.is_err());
+        let report = merge_skills(&pool, source, target).await.unwrap();
+        assert_eq!(report.moved_sessions, 2);
+        assert_eq!(report.target.skill_name, "Piano");
+        assert!(fetch_skill(&pool, source).await.unwrap().is_none());
+        let summaries = list_skill_summaries(&pool).await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        let moved: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE skill_id = ?1")
+            .bind(target)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(moved, 3);
+        assert_eq!(ensure_settings(&pool).await.unwrap().skill_name, "Piano");
+        assert!(matches!(merge_skills(&pool, source, target).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn data_dir_prefers_the_env_then_the_saved_choice() {
+        let root = std::env::temp_dir().join(format!("masterytrack-datadir-test-{}", uuid::Uuid::new_v4()));
+        let default_dir = root.join("default");
+        let saved = root.join("usb");
+        let from_env = root.join("synced");
+        tokio::fs::create_dir_all(&default_dir).await.unwrap();
+        // A path below a regular file can never be created
+        let file = root.join("file");
+        tokio::fs::write(&file, b"").await.unwrap();
+        let unusable = file.join("data").to_string_lossy().to_string();
+
+        assert_eq!(resolve_data_dir(&default_dir, None).await, default_dir);
+        assert!(save_data_dir(&default_dir, Some(&unusable)).await.is_err());
+        save_data_dir(&default_dir, Some(&saved.to_string_lossy())).await.unwrap();
+        assert_eq!(resolve_data_dir(&default_dir, None).await, saved);
+        let env = Some(from_env.to_string_lossy().to_string());
+        assert_eq!(resolve_data_dir(&default_dir, env).await, from_env);
+        assert_eq!(resolve_data_dir(&default_dir, Some(unusable)).await, saved);
+
+        save_data_dir(&default_dir, None).await.unwrap();
+        assert_eq!(resolve_data_dir(&default_dir, None).await, default_dir);
+        save_data_dir(&default_dir, None).await.unwrap();
+        tokio::fs::remove_dir_all(&root).await.ok();
+    }
+
+    #[tokio::test]
+    async fn factory_reset_clears_data_but_keeps_the_pin() {
+        let pool = test_pool().await;
+        let settings = AppSettings { skill_name: "Piano".into(), daily_goal_minutes: 15, ..ensure_settings(&pool).await.unwrap() };
+        save_settings(&pool, &settings).await.unwrap();
+        save_pin_hash(&pool, "hash").await.unwrap();
+        save_streak_milestone(&pool, 30).await.unwrap();
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let kept = insert_session(&pool, skill, Utc::now() - Duration::hours(2)).await.unwrap();
+        let trashed = insert_session(&pool, skill, Utc::now() - Duration::hours(1)).await.unwrap();
+        finalize_session(&pool, kept, Utc::now(), 30.0, &ReflectionInput::default()).await.unwrap();
+        finalize_session(&pool, trashed, Utc::now(), 30.0, &ReflectionInput::default()).await.unwrap();
+        delete_session(&pool, trashed).await.unwrap();
+
+        let report = factory_reset(&pool).await.unwrap();
+        assert_eq!((report.sessions_removed, report.skills_removed), (2, 1));
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions").fetch_one(&pool).await.unwrap();
+        assert_eq!(left, 0);
+        let reset = ensure_settings(&pool).await.unwrap();
+        assert_eq!(reset.skill_name, AppSettings::default().skill_name);
+        assert_eq!(reset.daily_goal_minutes, AppSettings::default().daily_goal_minutes);
+        let skills: Vec<String> = list_skills(&pool).await.unwrap().into_iter().map(|s| s.skill_name).collect();
+        assert_eq!(skills, vec![reset.skill_name]);
+        assert_eq!(load_streak_milestone(&pool).await.unwrap(), 0);
+        assert_eq!(load_pin_hash(&pool).await.unwrap().as_deref(), Some("hash"));
+    }
+
+    #[tokio::test]
+    async fn week_total_starts_on_the_configured_day() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let zone = DayZone::from_setting(Some("UTC"));
+        let today = zone.today();
+        // The Sunday opening a Sunday-first week is the tail of the previous Monday-first week,
+        // unless today is that Sunday
+        let sunday = WeekStartDay::Sunday.week_containing(today);
+        let id = insert_session(&pool, skill, zone.start_of_day(sunday) + Duration::hours(12)).await.unwrap();
+        finalize_session(&pool, id, Utc::now(), 60.0, &ReflectionInput::default()).await.unwrap();
+
+        let mut settings = ensure_settings(&pool).await.unwrap();
+        settings.timezone = Some("UTC".into());
+        settings.weekly_goal_minutes = 120;
+        settings.week_start_day = WeekStartDay::Sunday;
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.week_hours, 1.0);
+        assert_eq!(stats.weekly_goal.progress, 0.5);
+
+        settings.week_start_day = WeekStartDay::Monday;
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        let expected = if today == sunday { 1.0 } else { 0.0 };
+        assert_eq!(stats.week_hours, expected);
+        assert_eq!(stats.weekly_goal.progress, expected / 2.0);
+    }
+
+    #[tokio::test]
+    async fn zero_goals_keep_the_dashboard_finite() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let id = insert_session(&pool, skill, Utc::now()).await.unwrap();
+        checkpoint_session(&pool, id, 600.0).await.unwrap();
+
+        let mut settings = ensure_settings(&pool).await.unwrap();
+        settings.daily_goal_minutes = 0;
+        settings.weekly_goal_minutes = 0;
+        let stats = fetch_dashboard_stats(&pool, &settings, 90).await.unwrap();
+        assert_eq!(stats.daily_goal_hours, 0.0);
+        assert_eq!(stats.todays_goal_hours, 0.0);
+        assert_eq!(stats.weekly_goal.progress, 0.0);
+        assert!(!stats.weekly_goal.met);
+        assert!(stats.mastery_progress.is_finite() && (0.0..=1.0).contains(&stats.mastery_progress));
+    }
+
+    #[tokio::test]
+    async fn dashboard_csv_has_a_row_per_day_and_a_total() {
+        let pool = test_pool().await;
+        let settings = ensure_settings(&pool).await.unwrap();
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        let days = vec![
+            DailyTotal { date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(), minutes: 30.0 },
+            DailyTotal { date: NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(), minutes: 45.5 },
+        ];
+        let csv = String::from_utf8(dashboard_csv(&stats, &days).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "date,minutes,total_hours,streak_days");
+        assert_eq!(lines[1], "2024-03-01,30.00,,");
+        assert_eq!(lines[3], "total,75.50,0.00,0");
+    }
+
+    #[test]
+    fn ics_skips_open_sessions_and_escapes_reflections() {
+        let mut done = history_row(7, "2024-03-01T08:00:00Z", 30.0, Some("Scales, arpeggios; slowly\nthen fast"));
+        done.end = Some(DateTime::parse_from_rfc3339("2024-03-01T08:30:00Z").unwrap().with_timezone(&Utc));
+        done.notes = Some("x".repeat(100));
+        let open = history_row(8, "2024-03-02T08:00:00Z", 0.0, None);
+        let skills = HashMap::from([(7, "Piano".to_string())]);
+        let stamp = Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap();
+        let ics = render_ics(&[done, open], &skills, stamp);
+
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("UID:session-7@masterytrack\r\n"));
+        assert!(ics.contains("DTSTART:20240301T080000Z\r\nDTEND:20240301T083000Z\r\nSUMMARY:Piano\r\n"));
+        assert!(ics.contains("DESCRIPTION:What I practiced: Scales\\, arpeggios\\; slowly\\nthen fast\\nNot"));
+        assert!(ics.lines().all(|line| line.trim_end_matches('\r').len() <= 75));
+    }
+
+    #[test]
+    fn markdown_groups_by_day_and_skips_empty_fields() {
+        let rows = vec![
+            history_row(3, "2024-03-02T09:00:00Z", 45.0, Some("Scales")),
+            history_row(2, "2024-03-01T18:00:00Z", 30.0, None),
+            history_row(1, "2024-03-01T08:00:00Z", 20.0, Some("Etudes")),
+        ];
+        let md = render_markdown(&rows);
+
+        assert!(md.contains("## 2024-03-02 (45 min)"));
+        assert!(md.contains("## 2024-03-01 (50 min)"));
+        assert!(md.contains("- **What I practiced:** Scales"));
+        assert!(!md.contains("Notes"));
+        assert!(!md.contains("What I learned"));
+        assert_eq!(md.matches("## 2024-03-01").count(), 1);
+    }
+
+    fn day(date: &str, minutes: f64) -> DailyTotal {
+        DailyTotal {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            minutes,
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn late_session_in_negative_offset_keeps_streak() {
+        let zone = DayZone::from_setting(Some("America/Chicago"));
+        // 23:15 local on each day, which UTC files under the following date
+        let sessions = [
+            (utc("2024-03-02T05:15:00Z"), 60.0),
+            (utc("2024-03-03T05:15:00Z"), 60.0),
+            (utc("2024-03-04T05:15:00Z"), 60.0),
+        ];
+        let days = bucket_by_local_day(sessions.into_iter(), zone);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(days[2].date, NaiveDate::from_ymd_opt(2024, 3, 3).unwrap());
+
+        let today = NaiveDate::from_ymd_opt(2024, 3, 3).unwrap();
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 0), 3);
+
+        // Bucketed by UTC the same sessions would start a day late and miss "today"
+        let utc_days = bucket_by_local_day(sessions.into_iter(), DayZone::from_setting(Some("UTC")));
+        assert_eq!(utc_days[0].date, NaiveDate::from_ymd_opt(2024, 3, 2).unwrap());
+    }
+
+    fn streak_days(dates: &[&str]) -> Vec<DailyTotal> {
+        dates.iter().map(|d| day(d, 60.0)).collect()
+    }
+
+    #[test]
+    fn grace_day_bridges_a_single_gap() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let days = streak_days(&["2024-03-06", "2024-03-07", "2024-03-09", "2024-03-10"]);
+
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 0), 2);
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 1), 4);
+    }
+
+    #[test]
+    fn weekday_goals_judge_each_day_and_skip_rest_days() {
+        // Weekdays need 30 minutes, Saturday 90 and Sunday is a rest day
+        let settings = AppSettings { daily_goal_by_weekday: Some([30, 30, 30, 30, 30, 90, 0]), ..AppSettings::default() };
+        let goal = |day| settings.streak_goal_for(day);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+        let week = |saturday: f64| {
+            vec![
+                day("2024-03-04", 30.0),
+                day("2024-03-05", 45.0),
+                day("2024-03-06", 30.0),
+                day("2024-03-07", 30.0),
+                day("2024-03-08", 30.0),
+                day("2024-03-09", saturday),
+                day("2024-03-11", 30.0),
+            ]
+        };
+
+        assert_eq!(streak_from_days(&week(90.0), goal, today, 0), 7);
+        // An hour on Saturday falls short of that day's goal; Sunday's absence never counts
+        assert_eq!(streak_from_days(&week(60.0), goal, today, 0), 1);
+        assert_eq!(streak_from_days(&week(60.0), goal, today, 1), 6);
+        // With one goal for every day the missing Sunday breaks the streak
+        let flat = AppSettings { daily_goal_minutes: 30, ..AppSettings::default() };
+        assert_eq!(streak_from_days(&week(90.0), |day| flat.streak_goal_for(day), today, 0), 1);
+    }
+
+    #[tokio::test]
+    async fn dashboard_uses_todays_weekday_goal() {
+        use chrono::Datelike;
+
+        let pool = test_pool().await;
+        let mut settings = ensure_settings(&pool).await.unwrap();
+        settings.timezone = Some("UTC".into());
+        let today = DayZone::from_setting(Some("UTC")).today();
+        let mut goals = [60; 7];
+        goals[today.weekday().num_days_from_monday() as usize] = 150;
+        settings.daily_goal_by_weekday = Some(goals);
+        save_settings(&pool, &settings).await.unwrap();
+
+        let settings = ensure_settings(&pool).await.unwrap();
+        assert_eq!(settings.daily_goal_by_weekday, Some(goals));
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.daily_goal_hours, 2.5);
+    }
+
+    #[test]
+    fn grace_day_does_not_bridge_two_missed_days() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let days = streak_days(&["2024-03-05", "2024-03-06", "2024-03-09", "2024-03-10"]);
+
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 1), 2);
+    }
+
+    #[test]
+    fn below_goal_days_count_as_missed() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let days = vec![day("2024-03-08", 60.0), day("2024-03-09", 5.0)];
+
+        // Today is still open, so only the short day on the 9th is a miss
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 0), 0);
+        assert_eq!(streak_from_days(&days, |_| Some(30), today, 1), 1);
+    }
+
+    #[tokio::test]
+    async fn compressed_backup_round_trips() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-backup-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("source.db");
+        tokio::fs::write(&db_file, b"SQLite format 3\0 pretend pages").await.unwrap();
+
+        let backup = backup_database(&test_pool().await, &db_file, &dir.join("backups"), true).await.unwrap();
+        assert!(backup.to_string_lossy().ends_with(".db.gz"));
+
+        let mut restored = Vec::new();
+        let file = std::fs::File::open(&backup).unwrap();
+        std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(file), &mut restored).unwrap();
+        assert_eq!(restored, tokio::fs::read(&db_file).await.unwrap());
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn backup_includes_writes_still_in_the_wal() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-wal-backup-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("masterytrack.db");
+        let pool = open_pool(&db_file, None).await.unwrap();
+        ensure_skill(&pool, "Piano").await.unwrap();
+
+        let backup = backup_database(&pool, &db_file, &dir.join("backups"), false).await.unwrap();
+        let copy = open_pool(&backup, None).await.unwrap();
+        let names: Vec<String> = list_skills(&copy).await.unwrap().into_iter().map(|s| s.skill_name).collect();
+        assert!(names.contains(&"Piano".to_string()), "{names:?}");
+
+        copy.close().await;
+        pool.close().await;
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn restore_replaces_database_from_compressed_backup() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-restore-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("masterytrack.db");
+        let shared = SharedPool::new(open_pool(&db_file, None).await.unwrap(), None);
+
+        ensure_skill(&shared.get(), "Piano").await.unwrap();
+        let backup = backup_database(&shared.get(), &db_file, &dir.join("backups"), true).await.unwrap();
+        ensure_skill(&shared.get(), "Chess").await.unwrap();
+
+        restore_database(&shared, &db_file, &backup).await.unwrap();
+        let names: Vec<String> = list_skills(&shared.get()).await.unwrap().into_iter().map(|s| s.skill_name).collect();
+        assert_eq!(names, vec!["Piano".to_string()]);
+
+        let bogus = dir.join("notes.txt");
+        tokio::fs::write(&bogus, b"not a database").await.unwrap();
+        assert!(restore_database(&shared, &db_file, &bogus).await.is_err());
+        assert_eq!(list_skills(&shared.get()).await.unwrap().len(), 1);
+
+        shared.get().close().await;
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn passphrase_needs_a_sqlcipher_build() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-plain-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let result = open_pool(&dir.join("masterytrack.db"), Some("secret")).await;
+        assert!(matches!(result, Err(AppError::Custom(msg)) if msg.contains("SQLCipher")));
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn encrypted_database_opens_only_with_its_passphrase() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-cipher-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("masterytrack.db");
+        let shared = SharedPool::new(open_pool(&db_file, None).await.unwrap(), None);
+        ensure_skill(&shared.get(), "Piano").await.unwrap();
+
+        change_passphrase(&shared, &db_file, "first").await.unwrap();
+        ensure_skill(&shared.get(), "Chess").await.unwrap();
+        change_passphrase(&shared, &db_file, "it's second").await.unwrap();
+        shared.get().close().await;
+
+        assert!(matches!(open_pool(&db_file, None).await, Err(AppError::DatabaseEncrypted)));
+        assert!(matches!(open_pool(&db_file, Some("first")).await, Err(AppError::DatabaseEncrypted)));
+        let reopened = open_pool(&db_file, Some("it's second")).await.unwrap();
+        assert_eq!(list_skills(&reopened).await.unwrap().len(), 2);
+
+        reopened.close().await;
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn maintenance_reclaims_deleted_pages() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-maintenance-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("masterytrack.db");
+        let pool = open_pool(&db_file, None).await.unwrap();
+
+        let filler = "x".repeat(4096);
+        for i in 0..200 {
+            sqlx::query("INSERT INTO daily_notes (day, notes) VALUES (?1, ?2)")
+                .bind(format!("2024-01-{i:03}"))
+                .bind(&filler)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM daily_notes").execute(&pool).await.unwrap();
+
+        let report = run_maintenance(&pool, &db_file).await.unwrap();
+        assert_eq!(report.integrity, "ok");
+        assert!(report.size_after_bytes < report.size_before_bytes);
+
+        pool.close().await;
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn write_probe_leaves_nothing_behind() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-probe-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let db_file = dir.join("masterytrack.db");
+        let pool = open_pool(&db_file, None).await.unwrap();
+        ensure_settings(&pool).await.unwrap();
+
+        probe_writable(&pool, &db_file).await.unwrap();
+        assert!(!dir.join("masterytrack.db.write-test").exists());
+        assert!(probe_writable(&pool, &dir.join("missing").join("masterytrack.db")).await.is_err());
+
+        pool.close().await;
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn manual_session_computes_duration_and_rejects_bad_ranges() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let reflections = ReflectionInput {
+            notes: Some("Scales at the studio".into()),
+            tags: vec!["offline".into()],
+            ..ReflectionInput::default()
+        };
+
+        let row = create_manual_session(&pool, skill, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T09:45:00Z"), &reflections)
+            .await
+            .unwrap();
+        assert_eq!(row.duration_minutes, 45.0);
+        assert_eq!(row.tags, vec!["offline".to_string()]);
+
+        let backwards = create_manual_session(&pool, skill, utc("2024-03-01T12:00:00Z"), utc("2024-03-01T11:00:00Z"), &reflections);
+        assert!(backwards.await.is_err());
+        let overlapping = create_manual_session(&pool, skill, utc("2024-03-01T09:30:00Z"), utc("2024-03-01T10:00:00Z"), &reflections);
+        assert!(overlapping.await.is_err());
+        let adjacent = create_manual_session(&pool, skill, utc("2024-03-01T09:45:00Z"), utc("2024-03-01T10:00:00Z"), &reflections);
+        assert!(adjacent.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn weekly_summary_covers_the_seven_days_ending_on_the_given_day() {
+        let pool = test_pool().await;
+        let settings = AppSettings { timezone: Some("UTC".into()), ..AppSettings::default() };
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let reflections = ReflectionInput::default();
+        for (start, end) in [
+            ("2024-03-03T09:00:00Z", "2024-03-03T10:00:00Z"),
+            ("2024-03-05T09:00:00Z", "2024-03-05T09:30:00Z"),
+            ("2024-03-08T09:00:00Z", "2024-03-08T10:30:00Z"),
+            ("2024-03-10T09:00:00Z", "2024-03-10T09:20:00Z"),
+        ] {
+            create_manual_session(&pool, skill, utc(start), utc(end), &reflections).await.unwrap();
+        }
+
+        let to = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let summary = weekly_summary(&pool, &settings, to).await.unwrap();
+        assert_eq!(summary.from, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(summary.days.len(), 7);
+        assert_eq!(summary.total_minutes, 140.0);
+        assert_eq!(summary.days_practiced, 3);
+        assert_eq!(summary.best_day.unwrap().date, NaiveDate::from_ymd_opt(2024, 3, 8).unwrap());
+    }
+
+    #[tokio::test]
+    async fn hourly_distribution_uses_local_start_hour() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let reflections = ReflectionInput::default();
+        create_manual_session(&pool, skill, utc("2024-03-01T14:30:00Z"), utc("2024-03-01T15:30:00Z"), &reflections)
+            .await
+            .unwrap();
+        create_manual_session(&pool, skill, utc("2024-03-02T14:05:00Z"), utc("2024-03-02T14:25:00Z"), &reflections)
+            .await
+            .unwrap();
+        insert_session(&pool, skill, utc("2024-03-03T14:00:00Z")).await.unwrap();
+
+        let hours = hourly_distribution(&pool, DayZone::from_setting(Some("Europe/Berlin"))).await.unwrap();
+        assert_eq!(hours.len(), 24);
+        // 14:xx UTC is 15:xx in Berlin in winter; the session running into 16:00 stays at 15
+        assert_eq!(hours[15], HourlyTotal { hour: 15, minutes: 80.0 });
+        assert_eq!(hours.iter().map(|h| h.minutes).sum::<f64>(), 80.0);
+    }
+
+    #[tokio::test]
+    async fn skill_summaries_total_each_skill() {
+        let pool = test_pool().await;
+        let piano = ensure_skill(&pool, "Piano").await.unwrap();
+        ensure_skill(&pool, "Chess").await.unwrap();
+        let reflections = ReflectionInput::default();
+        create_manual_session(&pool, piano, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T09:45:00Z"), &reflections)
+            .await
+            .unwrap();
+        let trashed = create_manual_session(&pool, piano, utc("2024-03-02T09:00:00Z"), utc("2024-03-02T10:00:00Z"), &reflections)
+            .await
+            .unwrap();
+        delete_session(&pool, trashed.id).await.unwrap();
+
+        let summaries = list_skill_summaries(&pool).await.unwrap();
+        let totals: Vec<(&str, f64)> = summaries.iter().map(|s| (s.skill_name.as_str(), s.total_minutes)).collect();
+        assert_eq!(totals, [("Chess", 0.0), ("Piano", 45.0)]);
+    }
+
+    #[tokio::test]
+    async fn export_preview_estimates_the_written_size() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-preview-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let pool = test_pool().await;
+        let empty = export_preview(&pool, "csv", false).await.unwrap();
+        assert_eq!(empty.session_count, 0);
+        assert!(empty.estimated_bytes > 0, "the header is still written");
+
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let start = Utc::now() - Duration::days(200);
+        for day in 0..120 {
+            let from = start + Duration::days(day);
+            let reflections = ReflectionInput { notes: Some(format!("Day {day} scales")), ..ReflectionInput::default() };
+            create_manual_session(&pool, skill, from, from + Duration::minutes(30), &reflections).await.unwrap();
+        }
+
+        for format in ["csv", "json", "md", "ics", "jsonl"] {
+            let preview = export_preview(&pool, format, false).await.unwrap();
+            assert_eq!(preview.session_count, 120);
+            let file = dir.join(format!("export.{format}"));
+            export_sessions(&pool, format, &file, false).await.unwrap();
+            let actual = tokio::fs::metadata(&file).await.unwrap().len() as f64;
+            let error = (preview.estimated_bytes as f64 - actual).abs() / actual;
+            assert!(error < 0.05, "{format}: estimated {} for {actual} bytes", preview.estimated_bytes);
+        }
+        assert!(export_preview(&pool, "xml", false).await.is_err());
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn streamed_csv_matches_the_rendered_export() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-csv-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let start = Utc::now() - Duration::days(5000);
+        let mut tx = pool.begin().await.unwrap();
+        for i in 0..4000 {
+            let from = start + Duration::hours(i);
+            let extra = (i % 7 == 0).then(|| format!(r#"{{"Prompt {}":"answer, \"{i}\""}}"#, i % 3));
+            sqlx::query(
+                "INSERT INTO sessions (skill_id, start_time, end_time, duration_minutes, notes, tags, reflection_extra)
+                 VALUES (?1, ?2, ?3, 30, ?4, '[\"scales\"]', ?5)",
+            )
+            .bind(skill)
+            .bind(from.to_rfc3339())
+            .bind((from + Duration::minutes(30)).to_rfc3339())
+            .bind(format!("Session {i}\nwith a second line"))
+            .bind(extra)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        tx.commit().await.unwrap();
+
+        let sessions = all_sessions(&pool, &SessionFilter::default()).await.unwrap();
+        for include_settings in [false, true] {
+            let file = dir.join(format!("export-{include_settings}.csv"));
+            export_sessions(&pool, "csv", &file, include_settings).await.unwrap();
+            let rendered = render_csv(&sessions, include_settings, &pool).await.unwrap();
+            assert_eq!(tokio::fs::read(&file).await.unwrap(), rendered);
+        }
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn reimporting_an_export_skips_existing_sessions() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-import-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let source = test_pool().await;
+        let skill = ensure_skill(&source, "Piano").await.unwrap();
+        let reflections = ReflectionInput {
+            notes: Some("Hands, then \"together\"".into()),
+            what_practiced: Some("Scales".into()),
+            tags: vec!["warmup".into()],
+            extra: HashMap::from([("Hardest passage?".to_string(), "Bar 12, left hand".to_string())]),
+            ..ReflectionInput::default()
+        };
+        create_manual_session(&source, skill, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T09:45:00Z"), &reflections)
+            .await
+            .unwrap();
+        create_manual_session(&source, skill, utc("2024-03-02T09:00:00Z"), utc("2024-03-02T09:30:00Z"), &reflections)
+            .await
+            .unwrap();
+
+        for format in [ExportFormat::Csv, ExportFormat::Json, ExportFormat::JsonLines] {
+            let file = dir.join(format!("export.{format}"));
+            export_sessions(&source, &format.to_string(), &file, true).await.unwrap();
+
+            // The target's own session takes id 1, so ids collide with the export's rows
+            let target = test_pool().await;
+            let local = ensure_skill(&target, "Piano").await.unwrap();
+            create_manual_session(&target, local, utc("2024-02-01T09:00:00Z"), utc("2024-02-01T09:10:00Z"), &reflections)
+                .await
+                .unwrap();
+            let first = import_sessions(&target, &file, &format).await.unwrap();
+            assert_eq!((first.inserted, first.updated, first.skipped), (2, 0, 0));
+            let again = import_sessions(&target, &file, &format).await.unwrap();
+            assert_eq!((again.inserted, again.updated, again.skipped), (0, 0, 2));
+
+            let rows = list_sessions(&target, &SessionFilter::default()).await.unwrap();
+            assert_eq!(rows.len(), 3);
+            assert_eq!(rows[1].duration_minutes, 45.0);
+            assert_eq!(rows[1].notes, reflections.notes);
+            assert_eq!(rows[1].tags, vec!["warmup".to_string()]);
+            assert_eq!(rows[1].reflection_extra, reflections.extra);
+        }
+
+        let markdown = dir.join("export.md");
+        export_sessions(&source, "md", &markdown, false).await.unwrap();
+        assert!(import_sessions(&test_pool().await, &markdown, &ExportFormat::Markdown).await.is_err());
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[test]
+    fn csv_import_reports_bad_headers_and_rows_by_line() {
+        let header = "id,start_time,end_time,duration_minutes,notes,what_practiced,what_learned,next_focus,tags";
+        let good = "1,2024-03-01T09:00:00+00:00,2024-03-01T09:30:00+00:00,30.00,,Scales,,,warmup";
+        let error = |csv: String| match parse_csv_sessions(&csv) {
+            Err(AppError::Custom(msg)) => msg,
+            other => panic!("expected validation error, got {other:?}"),
+        };
+
+        let rows = parse_csv_sessions(&format!("# MasteryTrack Export\n{header}\n{good}\n")).unwrap();
+        assert_eq!(rows[0].row.what_practiced.as_deref(), Some("Scales"));
+        let before_tags = header.trim_end_matches(",tags");
+        assert!(parse_csv_sessions(&format!("{before_tags},skill\n1,2024-03-01T09:00:00Z,,5,,,,,Piano\n")).is_ok());
+
+        let swapped = header.replacen("start_time,end_time", "end_time,start_time", 1);
+        assert_eq!(error(format!("{swapped}\n{good}\n")), "Line 1: column 2 is 'end_time', expected 'start_time'");
+        assert_eq!(error("id,start_time,notes\n".into()), "Line 1: CSV header is missing end_time, duration_minutes, what_practiced, what_learned, next_focus, tags");
+
+        let bad_time = good.replacen("2024-03-01T09:30:00+00:00", "2024-03-01 09:30", 1);
+        assert_eq!(
+            error(format!("{header}\n{good}\n{bad_time}\n")),
+            "Line 3, field end_time: invalid value '2024-03-01 09:30'"
+        );
+        assert_eq!(error(format!("{header}\n1,2024-03-01T09:00:00Z\n")), "Line 2: expected 9 fields, found 2");
+    }
+
+    #[tokio::test]
+    async fn import_merges_by_start_time_and_keeps_reflections() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-merge-test-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, &ensure_settings(&pool).await.unwrap().skill_name).await.unwrap();
+        let noted = ReflectionInput { notes: Some("Local notes".into()), ..ReflectionInput::default() };
+        let short = create_manual_session(&pool, skill, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T09:30:00Z"), &noted)
+            .await
+            .unwrap();
+        create_manual_session(&pool, skill, utc("2024-03-01T10:00:00Z"), utc("2024-03-01T11:00:00Z"), &noted)
+            .await
+            .unwrap();
+
+        let session = |id: i64, start: &str, end: &str, minutes: f64, learned: &str| {
+            serde_json::json!({
+                "id": id, "start": start, "end": end, "duration_minutes": minutes,
+                "notes": "", "what_practiced": null, "what_learned": learned, "next_focus": null, "tags": [],
+            })
+        };
+        let file = dir.join("merge.json");
+        let export = serde_json::json!({ "sessions": [
+            session(1, "2024-03-01T09:00:00Z", "2024-03-01T09:45:00Z", 45.0, "Longer copy"),
+            session(2, "2024-03-01T10:00:00Z", "2024-03-01T10:30:00Z", 30.0, "Stale copy"),
+            session(2, "2024-03-02T08:00:00Z", "2024-03-02T08:20:00Z", 20.0, "New"),
+        ]});
+        tokio::fs::write(&file, export.to_string()).await.unwrap();
+
+        let summary = import_data(&pool, &file, false).await.unwrap();
+        assert_eq!((summary.inserted, summary.updated, summary.skipped), (1, 1, 1));
+        let rows = list_sessions(&pool, &SessionFilter::default()).await.unwrap();
+        assert_eq!(rows.len(), 3);
+        let merged = rows.iter().find(|r| r.id == short.id).unwrap();
+        assert_eq!(merged.duration_minutes, 45.0);
+        assert_eq!(merged.notes.as_deref(), Some("Local notes"));
+        assert_eq!(merged.what_learned.as_deref(), Some("Longer copy"));
+
+        let again = import_data(&pool, &file, false).await.unwrap();
+        assert_eq!((again.inserted, again.updated, again.skipped), (0, 0, 3));
+
+        // A trashed copy does not count as a match, so re-importing brings the session back
+        delete_session(&pool, short.id).await.unwrap();
+        let restored = import_data(&pool, &file, false).await.unwrap();
+        assert_eq!((restored.inserted, restored.updated, restored.skipped), (1, 0, 2));
+
+        let broken = dir.join("broken.json");
+        let export = serde_json::json!({ "sessions": [
+            session(9, "2024-03-05T08:00:00Z", "2024-03-05T08:20:00Z", 20.0, "Fine"),
+            session(10, "yesterday", "2024-03-05T09:20:00Z", 20.0, "Broken"),
+        ]});
+        tokio::fs::write(&broken, export.to_string()).await.unwrap();
+        assert!(import_data(&pool, &broken, false).await.is_err());
+        assert_eq!(list_sessions(&pool, &SessionFilter::default()).await.unwrap().len(), 3);
+
+        // JSON Lines opens with `{` like a JSON export, but has one session per line
+        let lines = [
+            session(11, "2024-03-06T08:00:00Z", "2024-03-06T08:30:00Z", 30.0, "First line"),
+            session(12, "2024-03-07T08:00:00Z", "2024-03-07T08:40:00Z", 40.0, "Second line"),
+        ]
+        .map(|line| line.to_string())
+        .join("\n");
+        // Without the extension the format is told from the content instead
+        for (name, expected) in [("lines.jsonl", (2, 0)), ("lines", (0, 2))] {
+            let file = dir.join(name);
+            tokio::fs::write(&file, &lines).await.unwrap();
+            let summary = import_data(&pool, &file, false).await.unwrap();
+            assert_eq!((summary.inserted, summary.skipped), expected, "{name}");
+        }
+        assert_eq!(list_sessions(&pool, &SessionFilter::default()).await.unwrap().len(), 5);
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn editing_into_another_session_is_rejected() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let morning = create_manual_session(&pool, skill, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T10:00:00Z"), &ReflectionInput::default())
+            .await
+            .unwrap();
+        let noon = create_manual_session(&pool, skill, utc("2024-03-01T12:00:00Z"), utc("2024-03-01T13:00:00Z"), &ReflectionInput::default())
+            .await
+            .unwrap();
+
+        let mut edit = SessionEditPayload {
+            id: noon.id,
+            start_time: utc("2024-03-01T09:30:00Z"),
+            end_time: Some(utc("2024-03-01T13:00:00Z")),
+            duration_minutes: 210.0,
+            notes: None,
+            what_practiced: None,
+            what_learned: None,
+            next_focus: None,
+            tags: Vec::new(),
+        };
+        match update_session(&pool, &edit).await {
+            Err(AppError::Custom(msg)) => assert!(msg.contains(&format!("#{}", morning.id)), "{msg}"),
+            other => panic!("expected overlap error, got {other:?}"),
+        }
+
+        // Moving a session within its own old range is not a conflict with itself
+        edit.start_time = utc("2024-03-01T12:15:00Z");
+        edit.duration_minutes = 45.0;
+        update_session(&pool, &edit).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn session_lengths_skip_open_sessions_and_empty_tables() {
+        let pool = test_pool().await;
+        let empty = session_lengths_since(&pool, None, None).await.unwrap();
+        assert_eq!((empty.count, empty.average, empty.longest), (0, 0.0, 0.0));
+
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        create_manual_session(&pool, skill, utc("2024-03-01T09:00:00Z"), utc("2024-03-01T09:20:00Z"), &ReflectionInput::default()).await.unwrap();
+        create_manual_session(&pool, skill, utc("2024-03-02T09:00:00Z"), utc("2024-03-02T10:00:00Z"), &ReflectionInput::default()).await.unwrap();
+        insert_session(&pool, skill, utc("2024-03-03T09:00:00Z")).await.unwrap();
+
+        let lengths = session_lengths_since(&pool, None, None).await.unwrap();
+        assert_eq!(lengths.count, 2);
+        assert_eq!(lengths.average, 40.0);
+        assert_eq!(lengths.longest, 60.0);
+    }
+
+    #[test]
+    fn completion_projection_uses_trailing_pace() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        // 60 minutes a day on average over 30 days, 300 hours left
+        let (date, days) = project_completion(300.0 * 60.0, 30.0 * 60.0, 30, today).unwrap();
+        assert_eq!(days, 300.0);
+        assert_eq!(date, today + Duration::days(300));
+
+        assert!(project_completion(600_000.0, 0.0, 30, today).is_none());
+        assert_eq!(project_completion(-5.0, 60.0, 30, today).unwrap().0, today);
+    }
+
+    #[test]
+    fn heatmap_fills_missing_days_with_zero() {
+        let first = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        let last = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        let filled = fill_daily_gaps(first, last, &[day("2024-02-29", 45.0), day("2024-03-02", 20.0)]);
+
+        let minutes: Vec<f64> = filled.iter().map(|d| d.minutes).collect();
+        assert_eq!(minutes, [0.0, 45.0, 0.0, 20.0]);
+        assert_eq!(filled[0].date, first);
+        assert_eq!(filled[3].date, last);
+    }
+
+    #[test]
+    fn records_pick_best_day_and_rolling_week() {
+        let days = vec![
+            day("2024-03-01", 60.0),
+            day("2024-03-03", 90.0),
+            day("2024-03-07", 30.0),
+            day("2024-03-20", 100.0),
+        ];
+        let records = compute_records(&days);
+
+        let best_day = records.best_day.unwrap();
+        assert_eq!(best_day.date, day("2024-03-20", 0.0).date);
+        let best_week = records.best_week.unwrap();
+        assert_eq!(best_week.start_date, day("2024-03-01", 0.0).date);
+        assert_eq!(best_week.minutes, 180.0);
+    }
+
+    #[test]
+    fn records_ties_prefer_most_recent() {
+        let days = vec![day("2024-01-01", 45.0), day("2024-02-01", 45.0)];
+        let records = compute_records(&days);
+
+        assert_eq!(records.best_day.unwrap().date, day("2024-02-01", 0.0).date);
+        assert_eq!(records.best_week.unwrap().start_date, day("2024-02-01", 0.0).date);
+    }
+
+    #[test]
+    fn records_empty_history() {
+        let records = compute_records(&[]);
+        assert!(records.best_day.is_none());
+        assert!(records.best_week.is_none());
+    }
+
+    #[tokio::test]
+    async fn todays_sessions_skips_earlier_days() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let today = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+        insert_session(&pool, skill_id, Utc::now() - Duration::days(2)).await.unwrap();
+
+        let rows = todays_sessions(&pool, DayZone::from_setting(Some("UTC"))).await.unwrap();
+        assert_eq!(rows.iter().map(|r| r.id).collect::<Vec<_>>(), vec![today]);
+    }
+
+    #[tokio::test]
+    async fn renaming_a_skill_keeps_its_sessions() {
+        let pool = test_pool().await;
+        let settings = ensure_settings(&pool).await.unwrap();
+        save_settings(&pool, &settings).await.unwrap();
+        let skill_id = ensure_skill(&pool, &settings.skill_name).await.unwrap();
+        insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+        ensure_skill(&pool, "Guitar").await.unwrap();
+
+        assert!(rename_skill(&pool, &settings.skill_name, "Guitar").await.is_err());
+        rename_skill(&pool, &settings.skill_name, "Piano").await.unwrap();
+
+        assert_eq!(ensure_settings(&pool).await.unwrap().skill_name, "Piano");
+        assert_eq!(ensure_skill(&pool, "Piano").await.unwrap(), skill_id);
+        let on_skill: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE skill_id = ?1")
+            .bind(skill_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(on_skill, 1);
+        assert!(matches!(rename_skill(&pool, "Cello", "Viola").await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn concurrent_note_appends_both_persist() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let session_id = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+
+        let (first, second) = tokio::join!(
+            append_session_note(&pool, session_id, "[10:00] scales felt smooth"),
+            append_session_note(&pool, session_id, "[10:05] left hand lagging"),
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let notes: String = sqlx::query_scalar("SELECT notes FROM sessions WHERE id = ?1")
+            .bind(session_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(notes.contains("scales felt smooth"));
+        assert!(notes.contains("left hand lagging"));
+        assert_eq!(notes.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn unfinished_session_recovers_at_checkpoint() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let start = DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        let session_id = insert_session(&pool, skill_id, start).await.unwrap();
+        checkpoint_session(&pool, session_id, 42.5).await.unwrap();
+
+        let pending = fetch_unfinished_session(&pool).await.unwrap().unwrap();
+        assert_eq!(pending.id, session_id);
+
+        let recovered = close_unfinished_session(&pool, session_id).await.unwrap().unwrap();
+        assert_eq!(recovered.duration_minutes, 42.5);
+        assert_eq!(recovered.end, Some(start + Duration::seconds(2550)));
+        assert!(fetch_unfinished_session(&pool).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn search_matches_reflections_literally() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let start = Utc::now() - Duration::hours(3);
+        for (offset, notes, learned) in [
+            (0, "Worked on the Chopin nocturne", None),
+            (1, "Scales at 100% tempo", Some("nocturne voicing")),
+            (2, "Sight reading", None),
+        ] {
+            let id = insert_session(&pool, skill_id, start + Duration::hours(offset)).await.unwrap();
+            let reflections = ReflectionInput {
+                notes: Some(notes.into()),
+                what_learned: learned.map(String::from),
+                ..ReflectionInput::default()
+            };
+            finalize_session(&pool, id, Utc::now(), 20.0, &reflections).await.unwrap();
+        }
+
+        let found = search_sessions(&pool, " Noct ").await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|hit| hit.snippet.as_deref().is_some_and(|s| s.contains("[nocturne]"))));
+        assert_eq!(search_sessions(&pool, "chopin OR sight").await.unwrap().len(), 0);
+        assert_eq!(search_sessions(&pool, "\"reading").await.unwrap().len(), 1);
+
+        // Punctuation-only queries skip the index and are matched literally
+        assert_eq!(search_sessions(&pool, "%").await.unwrap().len(), 1);
+        assert!(search_sessions(&pool, "_").await.unwrap().is_empty());
+        assert!(search_sessions(&pool, "  ").await.unwrap().is_empty());
+        let literal = substring_search(&pool, "100%").await.unwrap();
+        assert_eq!(literal.len(), 1);
+        assert!(literal[0].snippet.is_none());
+    }
+
+    #[tokio::test]
+    async fn search_index_follows_edits_and_backfills() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let id = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+        let reflections = ReflectionInput { notes: Some("arpeggios".into()), ..ReflectionInput::default() };
+        finalize_session(&pool, id, Utc::now(), 20.0, &reflections).await.unwrap();
+        sqlx::query("UPDATE sessions SET notes = 'etudes' WHERE id = ?1").bind(id).execute(&pool).await.unwrap();
+        assert!(search_sessions(&pool, "arpeggios").await.unwrap().is_empty());
+        assert_eq!(search_sessions(&pool, "etudes").await.unwrap().len(), 1);
+
+        // Dropping the index and migrating again rebuilds it from the existing sessions
+        for object in ["TRIGGER sessions_fts_insert", "TRIGGER sessions_fts_delete", "TRIGGER sessions_fts_update", "TABLE sessions_fts"] {
+            sqlx::query(&format!("DROP {object}")).execute(&pool).await.unwrap();
+        }
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(search_sessions(&pool, "etudes").await.unwrap().len(), 1);
+
+        sqlx::query("DELETE FROM sessions WHERE id = ?1").bind(id).execute(&pool).await.unwrap();
+        assert!(search_sessions(&pool, "etudes").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sessions_filter_and_break_down_by_tag() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let today = DayZone::System.start_of_day(DayZone::System.today());
+        for (tags, minutes) in [(vec!["Scales", "scales "], 30.0), (vec!["Repertoire", "Scales"], 60.0)] {
+            let id = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+            let reflections = ReflectionInput { tags: tags.into_iter().map(String::from).collect(), ..ReflectionInput::default() };
+            finalize_session(&pool, id, Utc::now(), minutes, &reflections).await.unwrap();
+        }
+
+        let scales = list_sessions_by_tag(&pool, "scales").await.unwrap();
+        assert_eq!(scales.len(), 2);
+        assert_eq!(scales.iter().find(|s| s.duration_minutes == 30.0).unwrap().tags, ["Scales"]);
+        assert_eq!(list_sessions_by_tag(&pool, "Repertoire").await.unwrap().len(), 1);
+
+        let breakdown = tag_minutes_since(&pool, today, None).await.unwrap();
+        assert_eq!(breakdown[0].tag, "Scales");
+        assert_eq!(breakdown[0].hours, 1.5);
+        assert_eq!(breakdown[1].tag, "Repertoire");
+    }
+
+    #[tokio::test]
+    async fn migrations_bring_an_old_database_current() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // The shape of the first releases: no tags, and settings without any of the later columns
+        for sql in [
+            "CREATE TABLE skills (id INTEGER PRIMARY KEY AUTOINCREMENT, skill_name TEXT NOT NULL UNIQUE, created_at TEXT)",
+            "CREATE TABLE sessions (id INTEGER PRIMARY KEY AUTOINCREMENT, skill_id INTEGER NOT NULL, start_time TEXT NOT NULL,
+                end_time TEXT, duration_minutes REAL, notes TEXT, what_practiced TEXT, what_learned TEXT, next_focus TEXT)",
+            "CREATE TABLE settings (id INTEGER PRIMARY KEY CHECK (id = 1), skill_name TEXT NOT NULL DEFAULT 'Primary Skill',
+                daily_goal_minutes INTEGER NOT NULL DEFAULT 120, idle_timeout_minutes INTEGER NOT NULL DEFAULT 5,
+                productivity_mode_enabled INTEGER NOT NULL DEFAULT 0, allowed_apps TEXT NOT NULL DEFAULT '[]',
+                blocked_apps TEXT NOT NULL DEFAULT '[]', auto_backup_path TEXT)",
+            "INSERT INTO skills (id, skill_name) VALUES (1, 'Cello')",
+            "INSERT INTO settings (id, skill_name, daily_goal_minutes) VALUES (1, 'Cello', 60)",
+            "INSERT INTO sessions (skill_id, start_time, end_time, duration_minutes, notes)
+                VALUES (1, '2023-05-01T09:00:00+00:00', '2023-05-01T10:00:00+00:00', 60, 'Bach suite')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+
+        run_migrations(&pool).await.unwrap();
+
+        let settings = ensure_settings(&pool).await.unwrap();
+        assert_eq!(settings.skill_name, "Cello");
+        assert_eq!(settings.daily_goal_minutes, 60);
+        assert_eq!(settings.weekly_goal_minutes, 420);
+        let rows = list_sessions(&pool, &SessionFilter::default()).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].notes.as_deref(), Some("Bach suite"));
+        assert!(rows[0].tags.is_empty());
+
+        let version: i64 = sqlx::query_scalar("SELECT MAX(version) FROM schema_version").fetch_one(&pool).await.unwrap();
+        assert_eq!(version, MIGRATIONS.last().unwrap().0);
+        // A second start finds nothing left to apply
+        run_migrations(&pool).await.unwrap();
+        let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM schema_version").fetch_one(&pool).await.unwrap();
+        assert_eq!(recorded, MIGRATIONS.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn sessions_page_newest_first_with_total() {
+        let pool = test_pool().await;
+        let skill = ensure_skill(&pool, "Piano").await.unwrap();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 3, d, 9, 0, 0).unwrap();
+        for d in 1..=5 {
+            insert_session(&pool, skill, day(d)).await.unwrap();
+        }
+
+        let filter = SessionFilter {
+            limit: Some(2),
+            offset: Some(2),
+            ..Default::default()
+        };
+        let page = list_sessions(&pool, &filter).await.unwrap();
+        assert_eq!(page.iter().map(|r| r.start).collect::<Vec<_>>(), vec![day(3), day(2)]);
+        assert_eq!(count_sessions(&pool, &filter).await.unwrap(), 5);
+
+        let recent = SessionFilter {
+            from: Some(day(4)),
+            ..Default::default()
+        };
+        assert_eq!(list_sessions(&pool, &recent).await.unwrap().len(), 2);
+        assert_eq!(count_sessions(&pool, &recent).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn daily_sum_searches_the_start_time_index() {
+        let pool = test_pool().await;
+        let plan = sqlx::query(&format!("EXPLAIN QUERY PLAN {SUM_MINUTES_SINCE_SQL}"))
+            .bind(Utc::now().to_rfc3339())
+            .bind(None::<i64>)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let details: Vec<String> = plan.iter().map(|row| row.get("detail")).collect();
+        assert!(
+            details.iter().any(|d| d.contains("USING INDEX idx_sessions_start_time")),
+            "{details:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn streak_milestone_survives_settings_saves_and_never_drops() {
+        let pool = test_pool().await;
+        let settings = ensure_settings(&pool).await.unwrap();
+        assert_eq!(load_streak_milestone(&pool).await.unwrap(), 0);
+
+        save_streak_milestone(&pool, 30).await.unwrap();
+        save_streak_milestone(&pool, 7).await.unwrap();
+        save_settings(&pool, &settings).await.unwrap();
+        assert_eq!(load_streak_milestone(&pool).await.unwrap(), 30);
+    }
+
+    #[tokio::test]
+    async fn screenshots_are_listed_per_session() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let first = insert_session(&pool, skill_id, Utc::now() - Duration::hours(2)).await.unwrap();
+        let second = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+        record_screenshot(&pool, first, Path::new("/shots/a.jpg")).await.unwrap();
+        record_screenshot(&pool, first, Path::new("/shots/b.jpg")).await.unwrap();
+        record_screenshot(&pool, second, Path::new("/shots/c.jpg")).await.unwrap();
+
+        let paths = |rows: Vec<SessionScreenshot>| rows.into_iter().map(|r| r.path).collect::<Vec<_>>();
+        assert_eq!(paths(fetch_session_screenshots(&pool, first).await.unwrap()), ["/shots/a.jpg", "/shots/b.jpg"]);
+
+        forget_screenshot(&pool, Path::new("/shots/a.jpg")).await.unwrap();
+        assert_eq!(paths(fetch_session_screenshots(&pool, first).await.unwrap()), ["/shots/b.jpg"]);
+
+        delete_session(&pool, second).await.unwrap();
+        purge_deleted(&pool, 0).await.unwrap();
+        assert!(fetch_session_screenshots(&pool, second).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn deleting_session_cascades_events() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let session_id = insert_session(&pool, skill_id, Utc::now()).await.unwrap();
+        record_session_event(&pool, session_id, SessionEventKind::Started, None).await.unwrap();
+        record_session_event(&pool, session_id, SessionEventKind::Paused, Some("idle")).await.unwrap();
+
+        let timeline = fetch_session_events(&pool, session_id).await.unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[1].detail.as_deref(), Some("idle"));
+
+        delete_session(&pool, session_id).await.unwrap();
+        assert!(matches!(delete_session(&pool, session_id).await, Err(AppError::NotFound(_))));
+        assert!(matches!(append_session_note(&pool, session_id, "late").await, Err(AppError::NotFound(_))));
+
+        assert_eq!(purge_deleted(&pool, 0).await.unwrap(), 1);
+        assert!(fetch_session_events(&pool, session_id).await.unwrap().is_empty());
+        assert!(matches!(restore_session(&pool, session_id).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn deleted_sessions_leave_totals_until_restored() {
+        let pool = test_pool().await;
+        let settings = ensure_settings(&pool).await.unwrap();
+        save_settings(&pool, &settings).await.unwrap();
+        let skill_id = ensure_skill(&pool, &settings.skill_name).await.unwrap();
+        let session_id = insert_session(&pool, skill_id, Utc::now() - Duration::minutes(45)).await.unwrap();
+        let reflections = ReflectionInput { what_practiced: Some("kept".into()), ..ReflectionInput::default() };
+        finalize_session(&pool, session_id, Utc::now(), 45.0, &reflections).await.unwrap();
+
+        delete_session(&pool, session_id).await.unwrap();
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert_eq!(stats.total_hours, 0.0);
+        assert_eq!(count_sessions(&pool, &SessionFilter::default()).await.unwrap(), 0);
+        // Too recent for a week-old purge
+        assert_eq!(purge_deleted(&pool, 7).await.unwrap(), 0);
+
+        restore_session(&pool, session_id).await.unwrap();
+        let stats = fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert!((stats.total_hours - 0.75).abs() < 1e-9);
+        let sessions = list_sessions(&pool, &SessionFilter::default()).await.unwrap();
+        assert_eq!(sessions[0].what_practiced.as_deref(), Some("kept"));
+    }
+
+    #[tokio::test]
+    async fn range_delete_trashes_finished_sessions_in_the_range() {
+        let pool = test_pool().await;
+        let skill_id = ensure_skill(&pool, "Piano").await.unwrap();
+        let day = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        let reflections = ReflectionInput::default();
+        let mut finished = Vec::new();
+        for hours in [-1, 2, 5, 24] {
+            let start = day + Duration::hours(hours);
+            let id = insert_session(&pool, skill_id, start).await.unwrap();
+            finalize_session(&pool, id, start + Duration::minutes(30), 30.0, &reflections).await.unwrap();
+            finished.push(id);
+        }
+        let running = insert_session(&pool, skill_id, day + Duration::hours(8)).await.unwrap();
+
+        assert!(matches!(
+            delete_sessions_in_range(&pool, day + Duration::days(1), day).await,
+            Err(AppError::Custom(_))
+        ));
+        assert_eq!(delete_sessions_in_range(&pool, day, day + Duration::days(1)).await.unwrap(), 2);
+        // Already trashed sessions are not counted again
+        assert_eq!(delete_sessions_in_range(&pool, day, day + Duration::days(1)).await.unwrap(), 0);
+
+        let mut left: Vec<i64> = list_sessions(&pool, &SessionFilter::default()).await.unwrap().iter().map(|s| s.id).collect();
+        left.sort_unstable();
+        assert_eq!(left, [finished[0], finished[3], running]);
+        restore_session(&pool, finished[1]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn checkpointed_running_session_is_counted_once() {
+        let pool = test_pool().await;
+        let settings = ensure_settings(&pool).await.unwrap();
+        let skill_id = ensure_skill(&pool, &settings.skill_name).await.unwrap();
+        let running = insert_session(&pool, skill_id, Utc::now() - Duration::minutes(20)).await.unwrap();
+        sqlx::query("UPDATE sessions SET tags = '[\"scales\"]' WHERE id = ?1")
+            .bind(running)
+            .execute(&pool)
+            .await
+            .unwrap();
+        checkpoint_session(&pool, running, 20.0).await.unwrap();
+
+        let stats = fetch_dashboard_stats(&pool, &settings, 20 * 60).await.unwrap();
+        assert!((stats.today_hours - 20.0 / 60.0).abs() < 1e-9, "{}", stats.today_hours);
+        assert!((stats.total_hours - 20.0 / 60.0).abs() < 1e-9, "{}", stats.total_hours);
+        assert!((stats.week_hours - 20.0 / 60.0).abs() < 1e-9);
+        assert!((stats.weekly_goal.completed_minutes - 20.0).abs() < 1e-9);
+        assert!(stats.today_by_tag.is_empty());
+    }
 }
@@ -26,6 +26,14 @@ pub enum AppError {
     CsvInner(String),
     #[error("Tauri error: {0}")]
     Tauri(String),
+    #[error("Screen recording permission has not been granted")]
+    ScreenPermissionDenied,
+    /// The database file is SQLCipher-encrypted and the passphrase is missing or wrong.
+    #[error("The database is encrypted; start MasteryTrack with the correct passphrase in MASTERYTRACK_DB_PASSPHRASE")]
+    DatabaseEncrypted,
+    /// The row a command referred to does not exist (any more).
+    #[error("{0}")]
+    NotFound(String),
     #[error("{0}")]
     Custom(String),
 }
@@ -1,21 +1,32 @@
+mod api;
+mod backup;
 mod db;
 mod errors;
+mod lock;
 mod models;
+mod notify;
+mod report;
 mod screenshot;
+mod summary;
 mod timer;
+mod timezone;
+mod webhook;
 
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use db::{
-    backup_database, ensure_settings, export_sessions, import_data as db_import_data, fetch_dashboard_stats, init_pool, list_sessions,
-    save_settings, update_session as db_update_session, delete_session as db_delete_session,
+    append_daily_note, append_session_note, fetch_daily_note, latest_session_id,
+    ensure_settings, export_sessions, fetch_practice_records, import_data as db_import_data, fetch_dashboard_stats, init_pool, list_sessions,
+    load_pin_hash, save_pin_hash, save_settings, SharedPool, update_session as db_update_session, delete_session as db_delete_session,
+    restore_session as db_restore_session,
 };
 use errors::{AppError, AppResult};
+use lock::AppLock;
 use models::{
-    AppSettings, DashboardStats, ExportRequest, ImportRequest, GoalNotification, ReflectionInput, SessionEditPayload,
-    SessionHistoryRow, StartTimerResponse, TimerStatus,
+    ActiveSessionInfo, AppSettings, CreateSkillPayload, DailyTotal, HourlyTotal, DashboardStats, ExportRequest, Skill, SkillMergeReport, SkillSummary, PracticeRecords, QuickNoteResult, QuickNoteTarget, SessionEvent, SessionEventKind, ImportRequest, ImportSummary, GoalApproachNotification, GoalNotification, MilestoneNotification, GoalWebhookPayload, MaintenanceReport, PomodoroPhase, SelfTestReport, SessionScreenshot, SessionSearchHit, ProductivitySnapshot, ReflectionInput, SessionEditPayload,
+    ExportFormat, ExportPreview, FactoryResetReport, IssueSeverity, ScreenshotFormat, SettingsIssue, SessionFilter, SessionHistoryRow, SessionPage, StartTimerResponse, StopOutcome, TimerStatus,
 };
 use tauri::{
     async_runtime,
@@ -26,35 +37,43 @@ use tauri::{
 };
 use tokio::sync::RwLock;
 
-use crate::timer::{idle_monitor, productivity_monitor, TimerService};
+use crate::timer::{checkpoint_worker, idle_monitor, productivity_monitor, TimerService};
+use crate::timezone::DayZone;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: sqlx::SqlitePool,
+    pub pool: SharedPool,
     pub timer: TimerService,
     pub settings: Arc<RwLock<AppSettings>>,
     pub db_path: PathBuf,
+    pub lock: AppLock,
 }
 
 impl AppState {
     pub fn new(
-        pool: sqlx::SqlitePool,
+        pool: SharedPool,
         timer: TimerService,
         settings: Arc<RwLock<AppSettings>>,
         db_path: PathBuf,
+        lock: AppLock,
     ) -> Self {
         Self {
             pool,
             timer,
             settings,
             db_path,
+            lock,
         }
     }
 }
 
 #[tauri::command]
-async fn start_timer(app: AppHandle, state: State<'_, AppState>) -> Result<StartTimerResponse, AppError> {
-    let response = state.timer.start().await?;
+async fn start_timer(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    skill_id: Option<i64>,
+) -> Result<StartTimerResponse, AppError> {
+    let response = state.timer.start_for_skill(skill_id).await?;
     app.emit("timer:started", &response).ok();
     Ok(response)
 }
@@ -64,27 +83,127 @@ async fn stop_timer(
     app: AppHandle,
     state: State<'_, AppState>,
     reflections: ReflectionInput,
-) -> Result<f64, AppError> {
-    let minutes = state.timer.stop(reflections).await?;
+) -> Result<StopOutcome, AppError> {
+    let session_id = state.timer.active_session_id().await;
+    let skill_id = state.timer.active_skill_id().await;
+    let outcome = state.timer.stop(reflections).await?;
+    if let StopOutcome::Discarded { .. } = outcome {
+        app.emit("timer:discarded", &outcome).ok();
+        return Ok(outcome);
+    }
     let settings = state.settings.read().await.clone();
-    let stats = fetch_dashboard_stats(&state.pool, &settings, 0).await?;
-    if stats.todays_goal_hours >= stats.daily_goal_hours && stats.daily_goal_hours > 0.0 {
+    let stats = fetch_dashboard_stats(&state.pool.get(), &settings, 0).await?;
+    let goal_met = stats.todays_goal_hours >= stats.daily_goal_hours && stats.daily_goal_hours > 0.0;
+    let today = DayZone::from_setting(settings.timezone.as_deref()).today();
+    if goal_met && state.timer.claim_goal_notification(today).await {
         let payload = GoalNotification {
             achieved_at: chrono::Utc::now(),
             total_minutes: stats.today_hours * 60.0,
+            goal_minutes: settings.daily_goal_for(today),
         };
         app.emit("goal:reached", &payload).ok();
+        if let Some(url) = settings.goal_webhook_url.as_deref() {
+            let skill = match skill_id {
+                Some(id) => db::fetch_skill(&state.pool.get(), id).await.ok().flatten(),
+                None => None,
+            };
+            webhook::send_goal_webhook(
+                url,
+                GoalWebhookPayload {
+                    date: today,
+                    total_minutes: payload.total_minutes,
+                    skill_name: skill.map(|s| s.skill_name).unwrap_or_else(|| settings.skill_name.clone()),
+                },
+            );
+        }
+        if let Some(id) = session_id {
+            db::record_session_event(&state.pool.get(), id, SessionEventKind::GoalReached, None).await.ok();
+        }
     }
+    check_streak_milestone(&app, &state, &settings, stats.streak_days).await;
 
     // Optional auto-backup
     if let Some(dir) = settings.auto_backup_path {
         let path = PathBuf::from(dir);
-        if tokio::fs::create_dir_all(&path).await.is_ok() {
-            let _ = backup_database(&state.db_path, path.as_path()).await;
+        if let Ok(backup) = backup::create_rotated_backup(
+            &state.pool,
+            &state.db_path,
+            &path,
+            settings.auto_backup_keep,
+            settings.backup_compress,
+        )
+        .await {
+            app.emit("backup:created", &backup.to_string_lossy()).ok();
         }
     }
 
-    Ok(minutes)
+    Ok(outcome)
+}
+
+/// Celebrates the first time the streak reaches a configured milestone; the highest one reached is
+/// stored so a restart does not announce it again.
+async fn check_streak_milestone(app: &AppHandle, state: &AppState, settings: &AppSettings, streak_days: u32) {
+    let pool = state.pool.get();
+    let highest_reached = match db::load_streak_milestone(&pool).await {
+        Ok(reached) => reached,
+        Err(e) => {
+            log::warn!("Unable to check streak milestones: {e}");
+            return;
+        }
+    };
+    let Some(milestone_days) = settings.streak_milestone_due(streak_days, highest_reached) else {
+        return;
+    };
+    if let Err(e) = db::save_streak_milestone(&pool, milestone_days).await {
+        log::warn!("Unable to record the {milestone_days}-day streak milestone: {e}");
+        return;
+    }
+    app.emit("milestone:reached", &MilestoneNotification { milestone_days, streak_days }).ok();
+    notify::desktop(
+        app,
+        &format!("{milestone_days}-day streak!"),
+        &format!("You've practiced {streak_days} days in a row."),
+    );
+}
+
+#[tauri::command]
+async fn pause_timer(app: AppHandle, state: State<'_, AppState>) -> Result<TimerStatus, AppError> {
+    let status = state.timer.pause().await?;
+    app.emit("timer:paused", &status).ok();
+    Ok(status)
+}
+
+#[tauri::command]
+async fn resume_timer(app: AppHandle, state: State<'_, AppState>) -> Result<TimerStatus, AppError> {
+    let status = state.timer.resume().await?;
+    app.emit("timer:resumed", &status).ok();
+    Ok(status)
+}
+
+/// Answers the idle prompt by carrying on with the same session.
+#[tauri::command]
+async fn resume_from_idle(app: AppHandle, state: State<'_, AppState>) -> Result<TimerStatus, AppError> {
+    if !state.timer.resume_from_idle().await? {
+        return Err(AppError::Custom("The timer is not waiting on an idle prompt".into()));
+    }
+    let status = state.timer.status().await;
+    app.emit("timer:resumed", &status).ok();
+    Ok(status)
+}
+
+/// Answers the idle prompt by ending the session, keeping only the time before going idle.
+#[tauri::command]
+async fn discard_idle_session(app: AppHandle, state: State<'_, AppState>) -> Result<StopOutcome, AppError> {
+    let outcome = state
+        .timer
+        .stop_from_idle()
+        .await?
+        .ok_or_else(|| AppError::Custom("The timer is not waiting on an idle prompt".into()))?;
+    match outcome {
+        StopOutcome::Discarded { .. } => app.emit("timer:discarded", &outcome).ok(),
+        StopOutcome::Saved { .. } => app.emit("timer:stopped", &()).ok(),
+    };
+    Ok(outcome)
 }
 
 #[tauri::command]
@@ -92,18 +211,233 @@ async fn timer_status(state: State<'_, AppState>) -> Result<TimerStatus, AppErro
     Ok(state.timer.status().await)
 }
 
+#[tauri::command]
+async fn active_session(state: State<'_, AppState>) -> Result<Option<ActiveSessionInfo>, AppError> {
+    state.lock.ensure_unlocked()?;
+    let Some(active) = state.timer.active_session().await else {
+        return Ok(None);
+    };
+    let skill_name = match db::fetch_skill(&state.pool.get(), active.skill_id).await? {
+        Some(skill) => skill.skill_name,
+        None => state.settings.read().await.skill_name.clone(),
+    };
+    Ok(Some(active.as_info(skill_name)))
+}
+
+/// Overwrites the running session's notes; blank text clears them.
+#[tauri::command]
+async fn annotate_active_session(state: State<'_, AppState>, notes: String) -> Result<(), AppError> {
+    state.lock.ensure_unlocked()?;
+    let notes = notes.trim();
+    state.timer.annotate(Some(notes).filter(|n| !n.is_empty())).await
+}
+
+#[tauri::command]
+async fn change_active_skill(state: State<'_, AppState>, skill_name: String) -> Result<ActiveSessionInfo, AppError> {
+    state.lock.ensure_unlocked()?;
+    let name = skill_name.trim();
+    if name.is_empty() {
+        return Err(AppError::Custom("Skill name cannot be empty".into()));
+    }
+    let active = state.timer.change_skill(name).await?;
+    Ok(active.as_info(name.to_string()))
+}
+
+#[tauri::command]
+async fn reconcile_timer(state: State<'_, AppState>) -> Result<i64, AppError> {
+    state.lock.ensure_unlocked()?;
+    Ok(state.timer.reconcile().await)
+}
+
 #[tauri::command]
 async fn dashboard(
     state: State<'_, AppState>,
 ) -> Result<DashboardStats, AppError> {
+    state.lock.ensure_unlocked()?;
     let settings = state.settings.read().await.clone();
     let active = state.timer.active_seconds().await;
-    fetch_dashboard_stats(&state.pool, &settings, active).await
+    fetch_dashboard_stats(&state.pool.get(), &settings, active).await
+}
+
+#[tauri::command]
+async fn dashboard_by_skill(
+    state: State<'_, AppState>,
+    skill_id: i64,
+) -> Result<DashboardStats, AppError> {
+    state.lock.ensure_unlocked()?;
+    let settings = state.settings.read().await.clone();
+    let active = if state.timer.active_skill_id().await == Some(skill_id) {
+        state.timer.active_seconds().await
+    } else {
+        0
+    };
+    db::fetch_dashboard_stats_for_skill(&state.pool.get(), &settings, active, skill_id).await
+}
+
+#[tauri::command]
+async fn get_skills(state: State<'_, AppState>) -> Result<Vec<Skill>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::list_skills(&state.pool.get()).await
+}
+
+#[tauri::command]
+async fn skills(state: State<'_, AppState>) -> Result<Vec<SkillSummary>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::list_skill_summaries(&state.pool.get()).await
+}
+
+#[tauri::command]
+async fn create_skill(state: State<'_, AppState>, payload: CreateSkillPayload) -> Result<Skill, AppError> {
+    state.lock.ensure_unlocked()?;
+    let name = payload.skill_name.trim();
+    if name.is_empty() {
+        return Err(AppError::Custom("Skill name cannot be empty".into()));
+    }
+    let id = db::ensure_skill(&state.pool.get(), name).await?;
+    Ok(Skill {
+        id,
+        skill_name: name.to_string(),
+    })
+}
+
+#[tauri::command]
+async fn rename_skill(
+    state: State<'_, AppState>,
+    old_name: String,
+    new_name: String,
+) -> Result<AppSettings, AppError> {
+    state.lock.ensure_unlocked()?;
+    let pool = state.pool.get();
+    db::rename_skill(&pool, &old_name, &new_name).await?;
+    let settings = ensure_settings(&pool).await?;
+    state.timer.update_settings(settings.clone()).await;
+    *state.settings.write().await = settings.clone();
+    Ok(settings)
+}
+
+/// Folds a duplicate skill into another, moving its sessions across.
+#[tauri::command]
+async fn merge_skills(state: State<'_, AppState>, source_id: i64, target_id: i64) -> Result<SkillMergeReport, AppError> {
+    state.lock.ensure_unlocked()?;
+    if state.timer.active_skill_id().await == Some(source_id) {
+        return Err(AppError::Custom("Stop the timer before merging the skill it is running on".into()));
+    }
+    let pool = state.pool.get();
+    let report = db::merge_skills(&pool, source_id, target_id).await?;
+    let settings = ensure_settings(&pool).await?;
+    state.timer.update_settings(settings.clone()).await;
+    *state.settings.write().await = settings;
+    log::info!("Merged skill {source_id} into '{}', moving {} sessions", report.target.skill_name, report.moved_sessions);
+    Ok(report)
+}
+
+#[tauri::command]
+async fn records(state: State<'_, AppState>) -> Result<PracticeRecords, AppError> {
+    state.lock.ensure_unlocked()?;
+    let zone = DayZone::from_setting(state.settings.read().await.timezone.as_deref());
+    fetch_practice_records(&state.pool.get(), zone).await
+}
+
+#[tauri::command]
+async fn heatmap(state: State<'_, AppState>, days: i64) -> Result<Vec<DailyTotal>, AppError> {
+    state.lock.ensure_unlocked()?;
+    let zone = DayZone::from_setting(state.settings.read().await.timezone.as_deref());
+    db::practice_heatmap(&state.pool.get(), days, zone).await
+}
+
+#[tauri::command]
+async fn hourly_distribution(state: State<'_, AppState>) -> Result<Vec<HourlyTotal>, AppError> {
+    state.lock.ensure_unlocked()?;
+    let zone = DayZone::from_setting(state.settings.read().await.timezone.as_deref());
+    db::hourly_distribution(&state.pool.get(), zone).await
+}
+
+#[tauri::command]
+async fn sessions(
+    state: State<'_, AppState>,
+    filter: Option<SessionFilter>,
+) -> Result<SessionPage, AppError> {
+    state.lock.ensure_unlocked()?;
+    let filter = filter.unwrap_or_default();
+    let pool = state.pool.get();
+    Ok(SessionPage {
+        sessions: list_sessions(&pool, &filter).await?,
+        total: db::count_sessions(&pool, &filter).await?,
+    })
+}
+
+#[tauri::command]
+async fn todays_sessions(state: State<'_, AppState>) -> Result<Vec<SessionHistoryRow>, AppError> {
+    state.lock.ensure_unlocked()?;
+    let zone = DayZone::from_setting(state.settings.read().await.timezone.as_deref());
+    db::todays_sessions(&state.pool.get(), zone).await
+}
+
+/// An unfinished session left behind by a crash, excluding the one currently running.
+async fn orphaned_session(state: &AppState) -> AppResult<Option<SessionHistoryRow>> {
+    let pending = db::fetch_unfinished_session(&state.pool.get()).await?;
+    let active = state.timer.active_session_id().await;
+    Ok(pending.filter(|row| Some(row.id) != active))
+}
+
+#[tauri::command]
+async fn unfinished_session(state: State<'_, AppState>) -> Result<Option<SessionHistoryRow>, AppError> {
+    state.lock.ensure_unlocked()?;
+    orphaned_session(&state).await
 }
 
 #[tauri::command]
-async fn sessions(state: State<'_, AppState>) -> Result<Vec<SessionHistoryRow>, AppError> {
-    list_sessions(&state.pool).await
+async fn recover_session(state: State<'_, AppState>) -> Result<Option<SessionHistoryRow>, AppError> {
+    state.lock.ensure_unlocked()?;
+    match orphaned_session(&state).await? {
+        Some(row) => db::close_unfinished_session(&state.pool.get(), row.id).await,
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+async fn search_sessions(state: State<'_, AppState>, query: String) -> Result<Vec<SessionSearchHit>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::search_sessions(&state.pool.get(), &query).await
+}
+
+#[tauri::command]
+async fn sessions_by_tag(state: State<'_, AppState>, tag: String) -> Result<Vec<SessionHistoryRow>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::list_sessions_by_tag(&state.pool.get(), &tag).await
+}
+
+#[tauri::command]
+async fn session_timeline(state: State<'_, AppState>, id: i64) -> Result<Vec<SessionEvent>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::fetch_session_events(&state.pool.get(), id).await
+}
+
+/// Timer transitions across every session, newest first, for the diagnostics view.
+#[tauri::command]
+async fn recent_events(state: State<'_, AppState>, limit: Option<i64>) -> Result<Vec<SessionEvent>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::recent_session_events(&state.pool.get(), limit.unwrap_or(db::DEFAULT_RECENT_EVENTS)).await
+}
+
+#[tauri::command]
+async fn session_screenshots(state: State<'_, AppState>, session_id: i64) -> Result<Vec<SessionScreenshot>, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::fetch_session_screenshots(&state.pool.get(), session_id).await
+}
+
+#[tauri::command]
+async fn create_manual_session(
+    state: State<'_, AppState>,
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    reflections: ReflectionInput,
+) -> Result<SessionHistoryRow, AppError> {
+    state.lock.ensure_unlocked()?;
+    let pool = state.pool.get();
+    let skill_name = state.settings.read().await.skill_name.clone();
+    let skill_id = db::ensure_skill(&pool, &skill_name).await?;
+    db::create_manual_session(&pool, skill_id, start, end, &reflections).await
 }
 
 #[tauri::command]
@@ -111,16 +445,124 @@ async fn update_session(
     state: State<'_, AppState>,
     payload: SessionEditPayload,
 ) -> Result<(), AppError> {
-    db_update_session(&state.pool, &payload).await
+    state.lock.ensure_unlocked()?;
+    db_update_session(&state.pool.get(), &payload).await
 }
 
 #[tauri::command]
 async fn delete_session(state: State<'_, AppState>, session_id: i64) -> Result<(), AppError> {
-    db_delete_session(&state.pool, session_id).await
+    state.lock.ensure_unlocked()?;
+    db_delete_session(&state.pool.get(), session_id).await
+}
+
+#[tauri::command]
+async fn restore_session(state: State<'_, AppState>, session_id: i64) -> Result<(), AppError> {
+    state.lock.ensure_unlocked()?;
+    db_restore_session(&state.pool.get(), session_id).await
+}
+
+#[tauri::command]
+async fn purge_deleted(state: State<'_, AppState>, older_than_days: i64) -> Result<u64, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::purge_deleted(&state.pool.get(), older_than_days).await
+}
+
+/// Moves the finished sessions starting in `[from, to)` to the trash, returning how many went.
+#[tauri::command]
+async fn delete_sessions_in_range(
+    state: State<'_, AppState>,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> Result<u64, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::delete_sessions_in_range(&state.pool.get(), from, to).await
+}
+
+#[tauri::command]
+async fn set_app_pin(state: State<'_, AppState>, pin: String) -> Result<(), AppError> {
+    // Changing an existing PIN requires the current one to have been entered.
+    state.lock.ensure_unlocked()?;
+    let hash = lock::hash_pin(&pin)?;
+    save_pin_hash(&state.pool.get(), &hash).await
+}
+
+#[tauri::command]
+async fn unlock(state: State<'_, AppState>, pin: String) -> Result<(), AppError> {
+    let stored = load_pin_hash(&state.pool.get()).await?;
+    state.lock.unlock(&pin, stored.as_deref())
+}
+
+#[tauri::command]
+async fn is_locked(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.lock.is_locked())
+}
+
+#[tauri::command]
+async fn append_note(state: State<'_, AppState>, text: String) -> Result<QuickNoteResult, AppError> {
+    state.lock.ensure_unlocked()?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::Custom("Note is empty".into()));
+    }
+    let now = chrono::Local::now();
+    let line = format!("[{}] {}", now.format("%H:%M"), text);
+
+    let target = state.settings.read().await.quick_note_target;
+    let session_id = match state.timer.active_session_id().await {
+        Some(id) => Some(id),
+        None if target == QuickNoteTarget::RecentSession => latest_session_id(&state.pool.get()).await?,
+        None => None,
+    };
+
+    if let Some(id) = session_id {
+        append_session_note(&state.pool.get(), id, &line).await?;
+        return Ok(QuickNoteResult {
+            session_id: Some(id),
+            daily_note_date: None,
+            line,
+        });
+    }
+
+    // No session to attach to (or daily notes preferred): keep it on today's note
+    let today = now.date_naive();
+    append_daily_note(&state.pool.get(), today, &line).await?;
+    Ok(QuickNoteResult {
+        session_id: None,
+        daily_note_date: Some(today),
+        line,
+    })
+}
+
+#[tauri::command]
+async fn daily_note(
+    state: State<'_, AppState>,
+    date: Option<chrono::NaiveDate>,
+) -> Result<Option<String>, AppError> {
+    state.lock.ensure_unlocked()?;
+    let day = date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    fetch_daily_note(&state.pool.get(), day).await
+}
+
+#[tauri::command]
+async fn skip_break(app: AppHandle, state: State<'_, AppState>) -> Result<TimerStatus, AppError> {
+    if state.timer.skip_break().await? {
+        app.emit("pomodoro:break-end", &()).ok();
+    }
+    Ok(state.timer.status().await)
+}
+
+#[tauri::command]
+async fn productivity_status(state: State<'_, AppState>) -> Result<ProductivitySnapshot, AppError> {
+    let settings = state.settings.read().await.clone();
+    // Listing windows and processes blocks, so keep it off the async workers
+    async_runtime::spawn_blocking(move || timer::scan_productivity(&settings))
+        .await
+        .map_err(|e| AppError::Io(format!("Productivity scan failed: {e}")))
 }
 
 #[tauri::command]
 async fn load_settings(state: State<'_, AppState>) -> Result<AppSettings, AppError> {
+    state.lock.ensure_unlocked()?;
     Ok(state.settings.read().await.clone())
 }
 
@@ -137,12 +579,27 @@ async fn write_temp_file(path: String, content: String) -> Result<(), AppError>
     Ok(())
 }
 
+/// Reports what `persist_settings` would adjust or reject, without saving anything.
+#[tauri::command]
+async fn validate_settings(mut new_settings: AppSettings) -> Result<Vec<SettingsIssue>, AppError> {
+    Ok(new_settings.review())
+}
+
+/// Saves `new_settings`, clamping out-of-range values unless `strict` is set, in which case any
+/// adjustment is rejected like an invalid value.
 #[tauri::command]
 async fn persist_settings(
     state: State<'_, AppState>,
-    new_settings: AppSettings,
+    mut new_settings: AppSettings,
+    strict: Option<bool>,
 ) -> Result<AppSettings, AppError> {
-    save_settings(&state.pool, &new_settings).await?;
+    state.lock.ensure_unlocked()?;
+    let issues = new_settings.review();
+    let strict = strict.unwrap_or(false);
+    if let Some(issue) = issues.iter().find(|i| strict || i.severity == IssueSeverity::Error) {
+        return Err(AppError::Custom(issue.message.clone()));
+    }
+    save_settings(&state.pool.get(), &new_settings).await?;
     state.timer.update_settings(new_settings.clone()).await;
     {
         let mut guard = state.settings.write().await;
@@ -151,12 +608,9 @@ async fn persist_settings(
     Ok(new_settings)
 }
 
-#[tauri::command]
-async fn export_data(
-    state: State<'_, AppState>,
-    request: ExportRequest,
-) -> Result<String, AppError> {
-    let dir = if let Some(dir) = request.target_dir {
+/// `target_dir` when given, otherwise `exports` next to the database; created if missing.
+async fn exports_dir(state: &AppState, target_dir: Option<String>) -> AppResult<PathBuf> {
+    let dir = if let Some(dir) = target_dir {
         PathBuf::from(dir)
     } else {
         state
@@ -166,13 +620,85 @@ async fn export_data(
             .ok_or_else(|| AppError::Custom("Invalid data directory".into()))?
     };
     tokio::fs::create_dir_all(&dir).await?;
+    Ok(dir)
+}
+
+#[tauri::command]
+async fn export_data(
+    state: State<'_, AppState>,
+    request: ExportRequest,
+) -> Result<String, AppError> {
+    state.lock.ensure_unlocked()?;
+    let dir = exports_dir(&state, request.target_dir).await?;
     let filename = format!(
         "masterytrack-export-{}.{}",
         chrono::Utc::now().format("%Y%m%d-%H%M%S"),
         request.format
     );
     let output = dir.join(filename);
-    let path = export_sessions(&state.pool, &request.format.to_string(), &output, request.include_settings).await?;
+    let path = export_sessions(&state.pool.get(), &request.format.to_string(), &output, request.include_settings).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Reports how many sessions an export would hold and roughly how big it would be, writing nothing.
+#[tauri::command]
+async fn export_preview(
+    state: State<'_, AppState>,
+    format: ExportFormat,
+    include_settings: Option<bool>,
+) -> Result<ExportPreview, AppError> {
+    state.lock.ensure_unlocked()?;
+    db::export_preview(&state.pool.get(), &format.to_string(), include_settings.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn export_pdf(
+    state: State<'_, AppState>,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<String, AppError> {
+    state.lock.ensure_unlocked()?;
+    if to < from {
+        return Err(AppError::Custom("The report must end on or after its start date".into()));
+    }
+    let settings = state.settings.read().await.clone();
+    let zone = DayZone::from_setting(settings.timezone.as_deref());
+    let filter = SessionFilter {
+        from: Some(zone.start_of_day(from)),
+        to: Some(zone.start_of_day(to + chrono::Duration::days(1))),
+        ..SessionFilter::default()
+    };
+    let pool = state.pool.get();
+    let report = report::PracticeReport {
+        skill_name: settings.skill_name.clone(),
+        from,
+        to,
+        sessions: db::all_sessions(&pool, &filter).await?,
+        streak_days: fetch_dashboard_stats(&pool, &settings, 0).await?.streak_days,
+    };
+    let bytes = report::render_pdf(&report, zone)?;
+    let output = exports_dir(&state, None)
+        .await?
+        .join(format!("masterytrack-report-{from}-to-{to}.pdf"));
+    tokio::fs::write(&output, bytes).await?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn export_dashboard(
+    state: State<'_, AppState>,
+    format: ExportFormat,
+) -> Result<String, AppError> {
+    state.lock.ensure_unlocked()?;
+    let dir = exports_dir(&state, None).await?;
+    let output = dir.join(format!(
+        "masterytrack-dashboard-{}.{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        format
+    ));
+    let settings = state.settings.read().await.clone();
+    let active = state.timer.active_seconds().await;
+    let path = db::export_dashboard(&state.pool.get(), &settings, active, &format, &output).await?;
     Ok(path.to_string_lossy().to_string())
 }
 
@@ -180,13 +706,14 @@ async fn export_data(
 async fn import_data(
     state: State<'_, AppState>,
     request: ImportRequest,
-) -> Result<(), AppError> {
+) -> Result<ImportSummary, AppError> {
+    state.lock.ensure_unlocked()?;
     let file_path = PathBuf::from(&request.file_path);
-    db_import_data(&state.pool, &file_path, request.import_settings).await?;
+    let summary = db_import_data(&state.pool.get(), &file_path, request.import_settings).await?;
     
     // Refresh settings if imported
     if request.import_settings {
-        let updated = ensure_settings(&state.pool).await?;
+        let updated = ensure_settings(&state.pool.get()).await?;
         {
             let mut guard = state.settings.write().await;
             *guard = updated.clone();
@@ -194,6 +721,161 @@ async fn import_data(
         state.timer.update_settings(updated).await;
     }
     
+    Ok(summary)
+}
+
+/// Re-imports an export file; `format` defaults to the file extension.
+#[tauri::command]
+async fn import_sessions(
+    state: State<'_, AppState>,
+    path: String,
+    format: Option<ExportFormat>,
+) -> Result<ImportSummary, AppError> {
+    state.lock.ensure_unlocked()?;
+    let path = PathBuf::from(path);
+    let format = match format {
+        Some(format) => format,
+        None => match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => ExportFormat::Csv,
+            Some("json") => ExportFormat::Json,
+            Some("jsonl" | "ndjson") => ExportFormat::JsonLines,
+            _ => return Err(AppError::Custom("Import files must be .csv, .json or .jsonl exports".into())),
+        },
+    };
+    let summary = db::import_sessions(&state.pool.get(), &path, &format).await?;
+    log::info!(
+        "Imported {} sessions from {} ({} updated, {} skipped)",
+        summary.inserted,
+        path.display(),
+        summary.updated,
+        summary.skipped
+    );
+    Ok(summary)
+}
+
+#[tauri::command]
+async fn run_maintenance(state: State<'_, AppState>) -> Result<MaintenanceReport, AppError> {
+    state.lock.ensure_unlocked()?;
+    let report = db::run_maintenance(&state.pool.get(), &state.db_path).await?;
+    log::info!(
+        "Maintenance finished: {} -> {} bytes",
+        report.size_before_bytes,
+        report.size_after_bytes
+    );
+    Ok(report)
+}
+
+/// Tries each OS capability once so the settings screen can show what works. The test
+/// screenshot is captured and dropped, never written to the screenshot folder.
+#[tauri::command]
+async fn self_test(app: AppHandle, state: State<'_, AppState>) -> Result<SelfTestReport, AppError> {
+    use tauri::plugin::PermissionState;
+    use tauri_plugin_notification::NotificationExt;
+
+    let mut report = SelfTestReport::default();
+    let capture = async_runtime::spawn_blocking(screenshot::probe_capture)
+        .await
+        .map_err(|e| AppError::Io(format!("Screenshot probe failed: {e}")))
+        .and_then(|result| result);
+    match capture {
+        Ok(()) => report.screenshots_ok = true,
+        Err(e) => report.screenshots_error = Some(e.to_string()),
+    }
+    match user_idle_time::get_idle_time() {
+        Ok(_) => report.idle_ok = true,
+        Err(e) => report.idle_error = Some(e.to_string()),
+    }
+    match db::probe_writable(&state.pool.get(), &state.db_path).await {
+        Ok(()) => report.db_writable = true,
+        Err(e) => report.db_error = Some(e.to_string()),
+    }
+    match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => report.notifications_ok = true,
+        Ok(permission) => report.notifications_error = Some(format!("Notification permission is {permission:?}")),
+        Err(e) => report.notifications_error = Some(e.to_string()),
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+async fn restore_backup(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<(), AppError> {
+    state.lock.ensure_unlocked()?;
+    if state.timer.active_session_id().await.is_some() {
+        return Err(AppError::Custom("Stop the running timer before restoring a backup".into()));
+    }
+
+    db::restore_database(&state.pool, &state.db_path, &PathBuf::from(&path)).await?;
+
+    let restored = ensure_settings(&state.pool.get()).await?;
+    {
+        let mut guard = state.settings.write().await;
+        *guard = restored.clone();
+    }
+    state.timer.update_settings(restored).await;
+
+    log::info!("Database restored from {}", path);
+    app.emit("db:restored", &path).ok();
+    Ok(())
+}
+
+/// Text the UI must send back before `factory_reset` wipes anything.
+const FACTORY_RESET_CONFIRMATION: &str = "DELETE";
+
+/// Deletes all practice data and restores default settings, optionally emptying the screenshot
+/// folder as well. Meant for testing or before handing the device on.
+#[tauri::command]
+async fn factory_reset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    confirm: String,
+    clear_screenshots: Option<bool>,
+) -> Result<FactoryResetReport, AppError> {
+    state.lock.ensure_unlocked()?;
+    if confirm != FACTORY_RESET_CONFIRMATION {
+        return Err(AppError::Custom(format!("Type {FACTORY_RESET_CONFIRMATION} to confirm the reset")));
+    }
+    // A paused session still holds its row, so `running` alone is not enough
+    if state.timer.active_session_id().await.is_some() {
+        return Err(AppError::Custom("Stop the running timer before resetting".into()));
+    }
+
+    // Resolved before the reset, which forgets a custom screenshot folder
+    let screenshot_dir = screenshot::storage_dir(&*state.settings.read().await, &state.db_path);
+    let pool = state.pool.get();
+    let mut report = db::factory_reset(&pool).await?;
+    if clear_screenshots.unwrap_or(false) {
+        report.screenshots_removed = screenshot::delete_all(&screenshot_dir).await?.deleted_count;
+    }
+
+    let defaults = ensure_settings(&pool).await?;
+    {
+        let mut guard = state.settings.write().await;
+        *guard = defaults.clone();
+    }
+    state.timer.update_settings(defaults).await;
+
+    log::info!(
+        "Factory reset removed {} sessions, {} skills and {} screenshots",
+        report.sessions_removed, report.skills_removed, report.screenshots_removed
+    );
+    app.emit("data:reset", &report).ok();
+    Ok(report)
+}
+
+/// Encrypts the database or changes its passphrase. Later launches need the new passphrase in
+/// `MASTERYTRACK_DB_PASSPHRASE`; there is no way to recover data without it.
+#[tauri::command]
+async fn change_passphrase(state: State<'_, AppState>, passphrase: String) -> Result<(), AppError> {
+    state.lock.ensure_unlocked()?;
+    if state.timer.active_session_id().await.is_some() {
+        return Err(AppError::Custom("Stop the running timer before changing the passphrase".into()));
+    }
+    db::change_passphrase(&state.pool, &state.db_path, &passphrase).await?;
+    log::info!("Database passphrase changed");
     Ok(())
 }
 
@@ -203,22 +885,19 @@ struct ScreenshotInfo {
     path: String,
     timestamp: String,
     size_kb: u64,
+    // Privacy-mode fingerprints are a few hundred bytes, which rounds to 0 KB
+    size_bytes: u64,
+    /// Small JPEG for the gallery grid; `None` for captures taken before thumbnails existed.
+    thumbnail_path: Option<String>,
 }
 
 #[tauri::command]
 async fn list_screenshots(state: State<'_, AppState>) -> Result<Vec<ScreenshotInfo>, AppError> {
     use tokio::fs;
-    
-    let storage_path = {
-        let settings = state.settings.read().await;
-        if let Some(ref path) = settings.screenshot_storage_path {
-            PathBuf::from(path)
-        } else {
-            state.db_path.parent()
-                .unwrap_or(&state.db_path)
-                .join("screenshots")
-        }
-    };
+
+    state.lock.ensure_unlocked()?;
+
+    let storage_path = screenshot::storage_dir(&*state.settings.read().await, &state.db_path);
     
     let mut screenshots = Vec::new();
     
@@ -226,17 +905,17 @@ async fn list_screenshots(state: State<'_, AppState>) -> Result<Vec<ScreenshotIn
         let mut entries = fs::read_dir(&storage_path).await?;
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jpg") {
+            if path.is_file() && screenshot::is_screenshot_file(&path) {
                 if let Ok(metadata) = entry.metadata().await {
                     let filename = path.file_name()
                         .and_then(|n| n.to_str())
                         .unwrap_or("")
                         .to_string();
                     
-                    // Extract timestamp from filename: screenshot_YYYYMMDD_HHMMSS_mmm.jpg
+                    // Extract timestamp from filename: screenshot_YYYYMMDD_HHMMSS_mmm.{jpg,png}
                     let timestamp = filename
                         .strip_prefix("screenshot_")
-                        .and_then(|s| s.strip_suffix(".jpg"))
+                        .and_then(|s| s.rsplit_once('.').map(|(stem, _)| stem))
                         .map(|s| {
                             // Parse YYYYMMDD_HHMMSS_mmm into readable format
                             if s.len() >= 15 {
@@ -253,11 +932,18 @@ async fn list_screenshots(state: State<'_, AppState>) -> Result<Vec<ScreenshotIn
                         })
                         .unwrap_or_else(|| "Unknown".to_string());
                     
+                    let thumbnail = screenshot::thumbnail_path(&path);
+                    let thumbnail_path = fs::try_exists(&thumbnail)
+                        .await
+                        .unwrap_or(false)
+                        .then(|| thumbnail.to_string_lossy().to_string());
                     screenshots.push(ScreenshotInfo {
                         filename,
                         path: path.to_string_lossy().to_string(),
                         timestamp,
                         size_kb: metadata.len() / 1024,
+                        size_bytes: metadata.len(),
+                        thumbnail_path,
                     });
                 }
             }
@@ -271,59 +957,176 @@ async fn list_screenshots(state: State<'_, AppState>) -> Result<Vec<ScreenshotIn
 }
 
 #[tauri::command]
-async fn delete_screenshot(path: String) -> Result<(), AppError> {
+async fn delete_screenshot(state: State<'_, AppState>, path: String) -> Result<(), AppError> {
     use tokio::fs;
-    fs::remove_file(&path).await?;
-    Ok(())
+    state.lock.ensure_unlocked()?;
+    let result = fs::remove_file(&path).await;
+    fs::remove_file(screenshot::thumbnail_path(std::path::Path::new(&path))).await.ok();
+    db::forget_screenshot(&state.pool.get(), std::path::Path::new(&path)).await?;
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(AppError::NotFound(format!("Screenshot {path} no longer exists")))
+        }
+        other => Ok(other?),
+    }
 }
 
 #[tauri::command]
-async fn read_screenshot_base64(path: String) -> Result<String, AppError> {
+async fn read_screenshot_base64(state: State<'_, AppState>, path: String) -> Result<String, AppError> {
     use tokio::fs;
     use base64::{Engine as _, engine::general_purpose};
-    
+
+    state.lock.ensure_unlocked()?;
+    if state.settings.read().await.screenshot_privacy_mode {
+        return Err(AppError::Custom("Screenshot previews are disabled in privacy mode".into()));
+    }
+
+    let format = std::path::Path::new(&path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(ScreenshotFormat::from_extension)
+        .unwrap_or_default();
     let data = fs::read(&path).await?;
     let base64_data = general_purpose::STANDARD.encode(&data);
-    Ok(format!("data:image/jpeg;base64,{}", base64_data))
+    Ok(format!("data:{};base64,{}", format.mime_type(), base64_data))
+}
+
+/// The folder holding the database this session opened.
+#[tauri::command]
+async fn get_data_dir(state: State<'_, AppState>) -> Result<String, AppError> {
+    let dir = state.db_path.parent().unwrap_or(&state.db_path);
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Chooses the data directory for the next launch, or goes back to the default with `None`.
+#[tauri::command]
+async fn set_data_dir(app: AppHandle, state: State<'_, AppState>, path: Option<String>) -> Result<(), AppError> {
+    state.lock.ensure_unlocked()?;
+    db::save_data_dir(&db::default_data_dir(&app)?, path.as_deref()).await?;
+    log::info!("Data directory set to {} from the next launch", path.as_deref().unwrap_or("the default"));
+    Ok(())
 }
 
 #[tauri::command]
 async fn get_screenshot_path(state: State<'_, AppState>) -> Result<String, AppError> {
-    let storage_path = {
-        let settings = state.settings.read().await;
-        if let Some(ref path) = settings.screenshot_storage_path {
-            PathBuf::from(path)
-        } else {
-            state.db_path.parent()
-                .unwrap_or(&state.db_path)
-                .join("screenshots")
-        }
-    };
+    let storage_path = screenshot::storage_dir(&*state.settings.read().await, &state.db_path);
     Ok(storage_path.to_string_lossy().to_string())
 }
 
+/// Opens the screenshot folder in the system file manager, creating it first on a fresh install.
+#[tauri::command]
+async fn open_screenshot_folder(app: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    use tauri_plugin_shell::ShellExt;
+
+    state.lock.ensure_unlocked()?;
+    let storage_path = screenshot::storage_dir(&*state.settings.read().await, &state.db_path);
+    tokio::fs::create_dir_all(&storage_path).await?;
+    let storage_path = storage_path.to_string_lossy().to_string();
+    // Opened from Rust, so the webview's shell `open` scope stays limited to URLs
+    #[allow(deprecated)]
+    app.shell()
+        .open(&storage_path, None)
+        .map_err(|e| AppError::Custom(format!("Unable to open {storage_path}: {e}")))?;
+    Ok(storage_path)
+}
+
+#[tauri::command]
+async fn request_screen_permission(app: AppHandle) -> Result<bool, AppError> {
+    if screenshot::request_screen_capture_permission() {
+        return Ok(true);
+    }
+
+    // The prompt only appears once per install; afterwards the user has to flip the switch manually
+    #[cfg(target_os = "macos")]
+    {
+        use tauri_plugin_shell::ShellExt;
+        #[allow(deprecated)]
+        app.shell()
+            .open(
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture",
+                None,
+            )
+            .map_err(|e| AppError::Custom(format!("Unable to open System Settings: {e}")))?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+
+    Ok(false)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             start_timer,
             stop_timer,
+            pause_timer,
+            resume_timer,
+            resume_from_idle,
+            discard_idle_session,
             timer_status,
+            active_session,
+            annotate_active_session,
+            change_active_skill,
+            reconcile_timer,
             dashboard,
+            dashboard_by_skill,
+            get_skills,
+            skills,
+            create_skill,
+            rename_skill,
+            merge_skills,
+            records,
+            heatmap,
+            hourly_distribution,
             sessions,
+            todays_sessions,
+            session_timeline,
+            recent_events,
+            session_screenshots,
+            create_manual_session,
             update_session,
             delete_session,
+            restore_session,
+            purge_deleted,
+            delete_sessions_in_range,
+            sessions_by_tag,
+            search_sessions,
+            unfinished_session,
+            recover_session,
+            append_note,
+            daily_note,
             load_settings,
+            productivity_status,
+            skip_break,
             persist_settings,
+            validate_settings,
             export_data,
+            export_preview,
+            export_dashboard,
+            export_pdf,
             import_data,
+            import_sessions,
+            restore_backup,
+            factory_reset,
+            change_passphrase,
+            run_maintenance,
+            self_test,
             get_temp_dir,
             write_temp_file,
             list_screenshots,
             delete_screenshot,
             get_screenshot_path,
-            read_screenshot_base64
+            get_data_dir,
+            set_data_dir,
+            open_screenshot_folder,
+            read_screenshot_base64,
+            request_screen_permission,
+            set_app_pin,
+            unlock,
+            is_locked
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -336,16 +1139,25 @@ pub fn run() {
 
             async_runtime::block_on(async {
                 let (pool, db_path) = init_pool(app.handle()).await?;
-                let settings = ensure_settings(&pool).await?;
+                let settings = ensure_settings(&pool.get()).await?;
+                if let Some(orphan) = db::fetch_unfinished_session(&pool.get()).await? {
+                    log::warn!(
+                        "Session {} was left open by a previous run; awaiting recover_session",
+                        orphan.id
+                    );
+                }
                 let shared_settings = Arc::new(RwLock::new(settings.clone()));
+                let app_lock = AppLock::new(load_pin_hash(&pool.get()).await?.is_some());
                 let timer = TimerService::new(pool.clone(), shared_settings.clone(), db_path.clone());
 
                 app.manage(AppState::new(
-                    pool.clone(),
+                    pool,
                     timer.clone(),
                     shared_settings.clone(),
                     db_path.clone(),
+                    app_lock,
                 ));
+                app.manage(api::ApiServer::default());
 
                 spawn_background_workers(app.handle().clone(), timer.clone());
                 Ok::<(), AppError>(())
@@ -355,15 +1167,85 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Finish the running session before the process goes away, whichever way the app quit
+                if let Some(state) = app.try_state::<AppState>() {
+                    match async_runtime::block_on(state.timer.stop_on_exit()) {
+                        Ok(Some(outcome)) => log::info!("Stopped the running session on quit: {outcome:?}"),
+                        Ok(None) => {}
+                        Err(e) => log::error!("Unable to stop the running session on quit: {e}"),
+                    }
+                }
+                if let Some(api) = app.try_state::<api::ApiServer>() {
+                    api.stop();
+                }
+            }
+        });
 }
 
+/// Emits `goal:approaching` and a desktop notification the first tick today's practice crosses the
+/// goal reminder percentage.
+async fn check_goal_approach(app: &AppHandle, timer: &TimerService, active_seconds: i64) {
+    let settings = timer.settings().await;
+    if settings.goal_reminder_percent <= 0 {
+        return;
+    }
+    let zone = DayZone::from_setting(settings.timezone.as_deref());
+    let today = zone.today();
+    if !timer.goal_approach_pending(today).await {
+        return;
+    }
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let logged = match db::todays_minutes(&state.pool.get(), zone).await {
+        Ok(minutes) => minutes,
+        Err(e) => {
+            log::warn!("Unable to check progress toward the daily goal: {e}");
+            return;
+        }
+    };
+    let total_minutes = logged + active_seconds as f64 / 60.0;
+    let goal_minutes = settings.daily_goal_for(today);
+    if goal_minutes > 0 && total_minutes >= goal_minutes as f64 {
+        // Past the goal the reminder can no longer fire today; claim the day so later ticks skip the query
+        timer.claim_goal_approach_notification(today).await;
+        return;
+    }
+    if !settings.goal_reminder_due(today, total_minutes) || !timer.claim_goal_approach_notification(today).await {
+        return;
+    }
+    let payload = GoalApproachNotification {
+        percent: settings.goal_reminder_percent,
+        total_minutes,
+        goal_minutes,
+    };
+    app.emit("goal:approaching", &payload).ok();
+    let left = (goal_minutes as f64 - total_minutes).ceil().max(1.0);
+    notify::desktop(
+        app,
+        "Almost there",
+        &format!("{}% of today's practice goal done, {left:.0} min to go.", settings.goal_reminder_percent),
+    );
+}
+
+const IDLE_MONITOR_RESTART_DELAY: Duration = Duration::from_secs(5);
+
 fn spawn_background_workers(handle: AppHandle, timer: TimerService) {
     let idle_app = handle.clone();
     let idle_timer = timer.clone();
     async_runtime::spawn(async move {
-        idle_monitor(idle_timer, idle_app).await;
+        // Idle detection must not silently stop for the rest of the run, so a panicking monitor is restarted
+        loop {
+            let monitor = async_runtime::spawn(idle_monitor(idle_timer.clone(), idle_app.clone()));
+            if let Err(e) = monitor.await {
+                log::error!("Idle monitor stopped unexpectedly, restarting it: {e}");
+                tokio::time::sleep(IDLE_MONITOR_RESTART_DELAY).await;
+            }
+        }
     });
 
     let prod_app = handle.clone();
@@ -372,43 +1254,154 @@ fn spawn_background_workers(handle: AppHandle, timer: TimerService) {
         productivity_monitor(prod_timer, prod_app).await;
     });
 
+    let checkpoint_timer = timer.clone();
+    async_runtime::spawn(async move {
+        checkpoint_worker(checkpoint_timer).await;
+    });
+
     let tick_app = handle.clone();
     let tick_timer = timer.clone();
     async_runtime::spawn(async move {
         loop {
+            let skipped = tick_timer.reconcile().await;
+            if skipped > 0 {
+                tick_app.emit("timer:reconciled", &skipped).ok();
+            }
+            match tick_timer.pomodoro_tick().await {
+                Some(PomodoroPhase::Break) => {
+                    tick_app.emit("pomodoro:break-start", &tick_timer.settings().await.pomodoro_break_minutes).ok();
+                }
+                Some(PomodoroPhase::Work) => {
+                    tick_app.emit("pomodoro:break-end", &()).ok();
+                }
+                None => {}
+            }
+            match tick_timer.stop_if_over_max().await {
+                Ok(Some(outcome)) => {
+                    tick_app.emit("timer:auto-stopped", &outcome).ok();
+                    notify::desktop(&tick_app, "Practice stopped", "The session reached its maximum length.");
+                }
+                Ok(None) => {}
+                Err(e) => log::error!("Unable to stop the session at its maximum length: {e}"),
+            }
             let status = tick_timer.status().await;
             tick_app.emit("timer:tick", &status).ok();
-            update_tray_tooltip(&tick_app, &status);
+            let active_seconds = tick_timer.active_seconds().await;
+            update_tray_tooltip(&tick_app, status.running, active_seconds);
+            if let Some(tray) = tick_app.try_state::<TrayController>() {
+                tray.sync(status.running);
+                if tray.claim_today_refresh() {
+                    refresh_tray_today(&tick_app, &tray, active_seconds).await;
+                }
+            }
+            if status.running {
+                check_goal_approach(&tick_app, &tick_timer, status.elapsed_seconds).await;
+            }
             tokio::time::sleep(Duration::from_secs(1)).await;
         }
     });
 
+    if let Some(state) = handle.try_state::<AppState>() {
+        let backup_app = handle.clone();
+        let backup_settings = state.settings.clone();
+        let backup_pool = state.pool.clone();
+        let backup_db_path = state.db_path.clone();
+        async_runtime::spawn(async move {
+            backup::backup_worker(backup_app, backup_settings, backup_pool, backup_db_path).await;
+        });
+    }
+
+    if let Some(state) = handle.try_state::<AppState>() {
+        let api_app = handle.clone();
+        let api_state = state.inner().clone();
+        async_runtime::spawn(async move {
+            api::api_worker(api_app, api_state).await;
+        });
+    }
+
+    if let Some(state) = handle.try_state::<AppState>() {
+        let reminder_app = handle.clone();
+        let reminder_state = state.inner().clone();
+        async_runtime::spawn(async move {
+            notify::reminder_worker(reminder_app, reminder_state).await;
+        });
+    }
+
+    if let Some(state) = handle.try_state::<AppState>() {
+        let summary_app = handle.clone();
+        let summary_state = state.inner().clone();
+        async_runtime::spawn(async move {
+            summary::weekly_summary_worker(summary_app, summary_state).await;
+        });
+    }
+
     // Screenshot worker
     if let Some(state) = handle.try_state::<AppState>() {
         let screenshot_timer = timer.clone();
         let screenshot_app = handle.clone();
         let screenshot_settings = state.settings.clone();
         let screenshot_db_path = state.db_path.clone();
+        let screenshot_pool = state.pool.clone();
         async_runtime::spawn(async move {
-            // Initialize screenshot service with default storage path if not set
-            let storage_path = {
-                let settings = screenshot_settings.read().await;
-                if let Some(ref path) = settings.screenshot_storage_path {
-                    PathBuf::from(path)
-                } else {
-                    // Default to screenshots folder in app data directory
-                    screenshot_db_path.parent()
-                        .unwrap_or(&screenshot_db_path)
-                        .join("screenshots")
-                }
-            };
-            let service = screenshot::ScreenshotService::new(screenshot_settings.clone(), storage_path);
+            let storage_path = screenshot::storage_dir(&*screenshot_settings.read().await, &screenshot_db_path);
+            let service = screenshot::ScreenshotService::new(screenshot_settings.clone(), storage_path, screenshot_pool);
             screenshot::screenshot_worker(service, screenshot_app, screenshot_timer).await;
         });
     }
 }
 
 const TRAY_ID: &str = "masterytrack-tray";
+/// The tray's "Today" line runs a full stats query, so it is refreshed at most this often.
+const TRAY_STATS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tray menu items whose state follows the timer, kept so the tick loop can update them.
+struct TrayController {
+    start: tauri::menu::MenuItem<tauri::Wry>,
+    stop: tauri::menu::MenuItem<tauri::Wry>,
+    today: tauri::menu::MenuItem<tauri::Wry>,
+    running: std::sync::Mutex<Option<bool>>,
+    today_refreshed: std::sync::Mutex<Option<std::time::Instant>>,
+}
+
+impl TrayController {
+    /// Enables Start while idle and Stop while running, touching the menu only when that flips.
+    fn sync(&self, running: bool) {
+        let mut last = self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *last == Some(running) {
+            return;
+        }
+        self.start.set_enabled(!running).ok();
+        self.stop.set_enabled(running).ok();
+        *last = Some(running);
+    }
+
+    /// True when the "Today" line is due for a refresh, which it then counts as done.
+    fn claim_today_refresh(&self) -> bool {
+        let mut last = self.today_refreshed.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if last.is_some_and(|at| at.elapsed() < TRAY_STATS_INTERVAL) {
+            return false;
+        }
+        *last = Some(std::time::Instant::now());
+        true
+    }
+
+    fn show_today(&self, today_hours: f64, goal_hours: f64) {
+        self.today
+            .set_text(format!("Today: {today_hours:.1}h / goal {goal_hours:.1}h"))
+            .ok();
+    }
+}
+
+async fn refresh_tray_today(app: &AppHandle, tray: &TrayController, active_seconds: i64) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let settings = state.settings.read().await.clone();
+    match fetch_dashboard_stats(&state.pool.get(), &settings, active_seconds).await {
+        Ok(stats) => tray.show_today(stats.today_hours, stats.daily_goal_hours),
+        Err(e) => log::warn!("Unable to refresh the tray's daily total: {e}"),
+    }
+}
 
 fn build_tray(app: AppHandle) -> AppResult<()> {
     use tauri::menu::{MenuBuilder, MenuItemBuilder};
@@ -417,10 +1410,12 @@ fn build_tray(app: AppHandle) -> AppResult<()> {
     let open = MenuItemBuilder::with_id("show", "Open Dashboard").build(&app)?;
     let start = MenuItemBuilder::with_id("start", "Start Practice").build(&app)?;
     let stop = MenuItemBuilder::with_id("stop", "Stop Practice").build(&app)?;
+    let today = MenuItemBuilder::with_id("today", "Today: —").enabled(false).build(&app)?;
     let quit = MenuItemBuilder::with_id("quit", "Quit").build(&app)?;
 
     let menu = MenuBuilder::new(&app)
         .item(&open)
+        .item(&today)
         .separator()
         .item(&start)
         .item(&stop)
@@ -456,19 +1451,25 @@ fn build_tray(app: AppHandle) -> AppResult<()> {
                     let shared = state.inner().clone();
                     let app_handle = app.clone();
                     async_runtime::spawn(async move {
-                        if let Err(err) = shared
+                        match shared
                             .timer
                             .stop(ReflectionInput {
                                 notes: Some("Stopped from tray".into()),
                                 what_practiced: None,
                                 what_learned: None,
                                 next_focus: None,
+                                tags: Vec::new(),
+                                extra: Default::default(),
                             })
                             .await
                         {
-                            log::error!("Tray stop failed: {err}");
-                        } else {
-                            app_handle.emit("timer:stopped", &()).ok();
+                            Err(err) => log::error!("Tray stop failed: {err}"),
+                            Ok(outcome @ StopOutcome::Discarded { .. }) => {
+                                app_handle.emit("timer:discarded", &outcome).ok();
+                            }
+                            Ok(StopOutcome::Saved { .. }) => {
+                                app_handle.emit("timer:stopped", &()).ok();
+                            }
                         }
                     });
                 }
@@ -480,18 +1481,35 @@ fn build_tray(app: AppHandle) -> AppResult<()> {
         })
         .build(&app)?;
 
+    app.manage(TrayController {
+        start,
+        stop,
+        today,
+        running: std::sync::Mutex::new(None),
+        today_refreshed: std::sync::Mutex::new(None),
+    });
     Ok(())
 }
 
-fn update_tray_tooltip(app: &AppHandle, status: &TimerStatus) {
+fn update_tray_tooltip(app: &AppHandle, running: bool, active_seconds: i64) {
     use tauri::tray::TrayIconId;
     if let Some(tray) = app.tray_by_id(&TrayIconId::new(TRAY_ID)) {
-        let tooltip = if status.running {
-            let hrs = status.elapsed_seconds as f64 / 3600.0;
-            format!("Practicing • {:.2}h today", hrs)
+        let tooltip = if running {
+            format!("Practicing {}", format_elapsed(active_seconds))
         } else {
             "MasteryTrack — idle".into()
         };
         tray.set_tooltip(Some(tooltip.as_str())).ok();
     }
 }
+
+/// `MM:SS`, growing an hours field once a session passes the hour.
+fn format_elapsed(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (hours, minutes, secs) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{secs:02}")
+    } else {
+        format!("{minutes:02}:{secs:02}")
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+use crate::errors::{AppError, AppResult};
+
+const MIN_PIN_LENGTH: usize = 4;
+
+/// Process-lifetime gate for commands that expose reflections, screenshots or exports.
+#[derive(Clone)]
+pub struct AppLock {
+    unlocked: Arc<AtomicBool>,
+}
+
+impl AppLock {
+    /// Starts locked only when a PIN has been configured.
+    pub fn new(pin_configured: bool) -> Self {
+        Self {
+            unlocked: Arc::new(AtomicBool::new(!pin_configured)),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        !self.unlocked.load(Ordering::SeqCst)
+    }
+
+    pub fn ensure_unlocked(&self) -> AppResult<()> {
+        if self.is_locked() {
+            return Err(AppError::Custom("locked".into()));
+        }
+        Ok(())
+    }
+
+    /// Verifies `pin` against the stored hash and unlocks on success.
+    pub fn unlock(&self, pin: &str, stored_hash: Option<&str>) -> AppResult<()> {
+        let Some(hash) = stored_hash else {
+            self.unlocked.store(true, Ordering::SeqCst);
+            return Ok(());
+        };
+
+        if verify_pin(pin, hash)? {
+            self.unlocked.store(true, Ordering::SeqCst);
+            Ok(())
+        } else {
+            Err(AppError::Custom("Incorrect PIN".into()))
+        }
+    }
+}
+
+pub fn hash_pin(pin: &str) -> AppResult<String> {
+    if pin.chars().count() < MIN_PIN_LENGTH {
+        return Err(AppError::Custom(format!(
+            "PIN must be at least {MIN_PIN_LENGTH} characters"
+        )));
+    }
+
+    let salt = SaltString::generate(&mut rand::rngs::OsRng);
+    let hash = Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map_err(|e| AppError::Custom(format!("Unable to hash PIN: {e}")))?;
+    Ok(hash.to_string())
+}
+
+/// Argon2 verification compares digests in constant time.
+pub fn verify_pin(pin: &str, stored_hash: &str) -> AppResult<bool> {
+    let parsed = PasswordHash::new(stored_hash)
+        .map_err(|_| AppError::Custom("Stored PIN hash is invalid".into()))?;
+    Ok(Argon2::default()
+        .verify_password(pin.as_bytes(), &parsed)
+        .is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correct_pin_unlocks() {
+        let hash = hash_pin("2468").unwrap();
+        let lock = AppLock::new(true);
+        assert!(lock.is_locked());
+
+        lock.unlock("2468", Some(&hash)).unwrap();
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn incorrect_pin_stays_locked() {
+        let hash = hash_pin("2468").unwrap();
+        let lock = AppLock::new(true);
+
+        assert!(lock.unlock("1357", Some(&hash)).is_err());
+        assert!(lock.is_locked());
+    }
+
+    #[test]
+    fn locked_guard_rejects_commands() {
+        let lock = AppLock::new(true);
+        match lock.ensure_unlocked() {
+            Err(AppError::Custom(msg)) => assert_eq!(msg, "locked"),
+            other => panic!("expected locked error, got {other:?}"),
+        }
+
+        assert!(AppLock::new(false).ensure_unlocked().is_ok());
+    }
+
+    #[test]
+    fn short_pins_are_rejected() {
+        assert!(hash_pin("12").is_err());
+    }
+}
@@ -1,8 +1,11 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::str::FromStr;
+use std::time::Instant;
 use crate::errors::{AppError, AppResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -16,6 +19,8 @@ pub struct SessionRecord {
     pub what_practiced: Option<String>,
     pub what_learned: Option<String>,
     pub next_focus: Option<String>,
+    pub tags: Option<String>,
+    pub reflection_extra: Option<String>,
 }
 
 impl SessionRecord {
@@ -34,14 +39,32 @@ pub struct SessionHistoryRow {
     pub what_practiced: Option<String>,
     pub what_learned: Option<String>,
     pub next_focus: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Answers to the custom `reflection_prompts`, keyed by prompt.
+    #[serde(default)]
+    pub reflection_extra: HashMap<String, String>,
 }
 
+/// A session found by `search_sessions`; `snippet` marks matched words in `[brackets]` and is only
+/// available when the full-text index answered the search.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchHit {
+    pub session: SessionHistoryRow,
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReflectionInput {
     pub notes: Option<String>,
     pub what_practiced: Option<String>,
     pub what_learned: Option<String>,
     pub next_focus: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Answers to the custom `reflection_prompts`, keyed by prompt.
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +76,16 @@ pub struct TimerStatus {
     pub last_reason: Option<String>,
 }
 
+/// The running or paused session, with the name of the skill it is logged against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveSessionInfo {
+    pub session_id: i64,
+    pub skill_id: i64,
+    pub skill_name: String,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_seconds: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartTimerResponse {
     pub session_id: i64,
@@ -65,11 +98,172 @@ pub struct DashboardStats {
     pub week_hours: f64,
     pub month_hours: f64,
     pub total_hours: f64,
-    pub goal_progress: f64,
+    /// Fraction of `total_hours_target` reached, capped at 1.
+    #[serde(alias = "goal_progress")]
+    pub mastery_progress: f64,
     pub total_hours_target: f64,
     pub daily_goal_hours: f64,
     pub todays_goal_hours: f64,
     pub streak_days: u32,
+    /// Distinct local days with any practice, ever.
+    pub total_practice_days: i64,
+    pub weekly_goal: GoalProgress,
+    pub today_by_tag: Vec<TagHours>,
+    /// Finished sessions only; 0 when there are none.
+    pub avg_session_minutes: f64,
+    pub longest_session_minutes: f64,
+    pub sessions_this_week: i64,
+    /// Projected day the mastery target is reached at the trailing 30-day pace.
+    pub eta_date: Option<NaiveDate>,
+    pub eta_days_remaining: Option<f64>,
+    pub display: DashboardDisplay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagHours {
+    pub tag: String,
+    pub hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub integrity: String,
+    /// Database plus WAL file size, in bytes.
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+}
+
+/// Result of `self_test`: whether each OS capability works, and why not when it doesn't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub screenshots_ok: bool,
+    pub screenshots_error: Option<String>,
+    pub idle_ok: bool,
+    pub idle_error: Option<String>,
+    pub db_writable: bool,
+    pub db_error: Option<String>,
+    pub notifications_ok: bool,
+    pub notifications_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub goal_minutes: i64,
+    pub completed_minutes: f64,
+    /// Fraction of the goal reached, capped at 1.0.
+    pub progress: f64,
+    pub met: bool,
+}
+
+impl GoalProgress {
+    pub fn new(goal_minutes: i64, completed_minutes: f64) -> Self {
+        let progress = if goal_minutes > 0 {
+            (completed_minutes / goal_minutes as f64).min(1.0)
+        } else {
+            0.0
+        };
+        Self {
+            goal_minutes,
+            completed_minutes,
+            progress,
+            met: goal_minutes > 0 && completed_minutes >= goal_minutes as f64,
+        }
+    }
+}
+
+/// Pre-rounded strings so every frontend renders hours the same way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DashboardDisplay {
+    pub today_hours: String,
+    pub week_hours: String,
+    pub month_hours: String,
+    pub total_hours: String,
+    pub daily_goal_hours: String,
+    pub todays_goal_hours: String,
+}
+
+impl DashboardDisplay {
+    pub fn from_stats(stats: &DashboardStats, precision: HoursPrecision) -> Self {
+        Self {
+            today_hours: precision.format_hours(stats.today_hours),
+            week_hours: precision.format_hours(stats.week_hours),
+            month_hours: precision.format_hours(stats.month_hours),
+            total_hours: precision.format_hours(stats.total_hours),
+            daily_goal_hours: precision.format_hours(stats.daily_goal_hours),
+            todays_goal_hours: precision.format_hours(stats.todays_goal_hours),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoursPrecision {
+    OneDecimal,
+    #[default]
+    TwoDecimals,
+    QuarterHour,
+}
+
+impl HoursPrecision {
+    pub fn format_hours(&self, hours: f64) -> String {
+        match self {
+            HoursPrecision::OneDecimal => format!("{hours:.1}h"),
+            HoursPrecision::TwoDecimals => format!("{hours:.2}h"),
+            HoursPrecision::QuarterHour => {
+                let quarters = (hours * 4.0).round() / 4.0;
+                let text = format!("{quarters:.2}");
+                format!("{}h", text.trim_end_matches('0').trim_end_matches('.'))
+            }
+        }
+    }
+}
+
+impl Display for HoursPrecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoursPrecision::OneDecimal => write!(f, "one_decimal"),
+            HoursPrecision::TwoDecimals => write!(f, "two_decimals"),
+            HoursPrecision::QuarterHour => write!(f, "quarter_hour"),
+        }
+    }
+}
+
+impl FromStr for HoursPrecision {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one_decimal" => Ok(Self::OneDecimal),
+            "two_decimals" => Ok(Self::TwoDecimals),
+            "quarter_hour" => Ok(Self::QuarterHour),
+            other => Err(AppError::Custom(format!("Unknown hours precision: {other}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyTotal {
+    pub date: NaiveDate,
+    pub minutes: f64,
+}
+
+/// Minutes practiced in sessions that started during `hour` (0-23, local time).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HourlyTotal {
+    pub hour: u32,
+    pub minutes: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekRecord {
+    pub start_date: NaiveDate,
+    pub minutes: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PracticeRecords {
+    pub best_day: Option<DailyTotal>,
+    pub best_week: Option<WeekRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -90,27 +284,123 @@ pub struct SettingsRow {
     pub music_volume: Option<f64>,
     pub music_auto_play: Option<i64>,
     pub music_custom_playlist_url: Option<String>,
+    pub screenshot_privacy_mode: Option<i64>,
+    pub quick_note_target: Option<String>,
+    pub display_hours_precision: Option<String>,
+    pub weekly_goal_minutes: Option<i64>,
+    pub screenshot_interval_min_minutes: Option<i64>,
+    pub screenshot_interval_max_minutes: Option<i64>,
+    pub screenshot_format: Option<String>,
+    pub screenshot_blur: Option<i64>,
+    pub timezone: Option<String>,
+    pub streak_grace_days: Option<i64>,
+    pub auto_backup_interval_hours: Option<i64>,
+    pub auto_backup_keep: Option<i64>,
+    pub backup_compress: Option<i64>,
+    pub productivity_active_from: Option<String>,
+    pub productivity_active_to: Option<String>,
+    pub productivity_grace_seconds: Option<i64>,
+    pub pomodoro_enabled: Option<i64>,
+    pub pomodoro_work_minutes: Option<i64>,
+    pub pomodoro_break_minutes: Option<i64>,
+    pub minimum_session_minutes: Option<i64>,
+    pub api_enabled: Option<i64>,
+    pub api_port: Option<i64>,
+    pub api_token: Option<String>,
+    pub goal_webhook_url: Option<String>,
+    pub reflection_prompts: String,
+    pub mastery_target_hours: Option<i64>,
+    pub goal_reminder_percent: Option<i64>,
+    pub reminder_enabled: Option<i64>,
+    pub reminder_time: Option<String>,
+    pub idle_behavior: Option<String>,
+    pub idle_prompt_timeout_minutes: Option<i64>,
+    pub active_requires_focus_app: Option<i64>,
+    pub screenshot_max_storage_mb: Option<i64>,
+    pub streak_milestones: String,
+    pub weekly_summary_enabled: Option<i64>,
+    pub weekly_summary_weekday: Option<i64>,
+    pub weekly_summary_time: Option<String>,
+    pub weekly_summary_delivery: Option<String>,
+    pub idle_poll_seconds: Option<i64>,
+    pub week_start_day: Option<String>,
+    pub max_session_minutes: Option<i64>,
+    pub daily_goal_by_weekday: Option<String>,
 }
 
+/// Streak lengths, in days, that earn a `milestone:reached` notification unless configured otherwise.
+pub const DEFAULT_STREAK_MILESTONES: [i64; 5] = [7, 30, 60, 100, 365];
+
+/// Bounds for `idle_poll_seconds`: faster polling wastes CPU, slower lets idle time slip into sessions.
+pub const MIN_IDLE_POLL_SECONDS: i64 = 2;
+pub const MAX_IDLE_POLL_SECONDS: i64 = 60;
+
+/// The lowest `mastery_target_hours` accepted, so mastery progress never divides by zero.
+pub const MIN_MASTERY_TARGET_HOURS: i64 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub skill_name: String,
     pub daily_goal_minutes: i64,
     pub idle_timeout_minutes: i64,
     pub productivity_mode_enabled: bool,
-    pub allowed_apps: Vec<String>,
-    pub blocked_apps: Vec<String>,
+    pub allowed_apps: Vec<AppRule>,
+    pub blocked_apps: Vec<AppRule>,
     pub auto_backup_path: Option<String>,
     // Screenshot settings
     pub screenshot_enabled: bool,
     pub screenshot_storage_path: Option<String>,
     pub screenshot_retention_days: i64,
+    pub screenshot_privacy_mode: bool,
     // Music settings
     pub music_enabled: bool,
     pub music_playlist_type: String,
     pub music_volume: f64,
     pub music_auto_play: bool,
     pub music_custom_playlist_url: Option<String>,
+    pub quick_note_target: QuickNoteTarget,
+    pub display_hours_precision: HoursPrecision,
+    pub weekly_goal_minutes: i64,
+    pub screenshot_interval_min_minutes: i64,
+    pub screenshot_interval_max_minutes: i64,
+    pub screenshot_format: ScreenshotFormat,
+    pub screenshot_blur: bool,
+    pub timezone: Option<String>,
+    pub streak_grace_days: i64,
+    pub auto_backup_interval_hours: i64,
+    pub auto_backup_keep: i64,
+    pub backup_compress: bool,
+    pub productivity_active_from: Option<String>,
+    pub productivity_active_to: Option<String>,
+    pub productivity_grace_seconds: i64,
+    pub pomodoro_enabled: bool,
+    pub pomodoro_work_minutes: i64,
+    pub pomodoro_break_minutes: i64,
+    pub minimum_session_minutes: i64,
+    pub api_enabled: bool,
+    pub api_port: i64,
+    pub api_token: Option<String>,
+    pub goal_webhook_url: Option<String>,
+    pub reflection_prompts: Vec<String>,
+    pub mastery_target_hours: i64,
+    pub goal_reminder_percent: i64,
+    pub reminder_enabled: bool,
+    pub reminder_time: Option<String>,
+    pub idle_behavior: IdleBehavior,
+    pub idle_prompt_timeout_minutes: i64,
+    pub active_requires_focus_app: bool,
+    pub screenshot_max_storage_mb: i64,
+    pub streak_milestones: Vec<i64>,
+    pub weekly_summary_enabled: bool,
+    pub weekly_summary_weekday: i64,
+    pub weekly_summary_time: Option<String>,
+    pub weekly_summary_delivery: WeeklySummaryDelivery,
+    pub idle_poll_seconds: i64,
+    pub week_start_day: WeekStartDay,
+    pub max_session_minutes: i64,
+    /// Goal minutes for each weekday, Monday first; `None` uses `daily_goal_minutes` every day.
+    pub daily_goal_by_weekday: Option<[i64; 7]>,
 }
 
 impl Default for AppSettings {
@@ -126,11 +416,53 @@ impl Default for AppSettings {
             screenshot_enabled: false,
             screenshot_storage_path: None,
             screenshot_retention_days: 7, // Keep screenshots for 7 days by default
+            screenshot_privacy_mode: false,
             music_enabled: false,
             music_playlist_type: "focus".into(), // Default to focus music
             music_volume: 0.5, // 50% volume
             music_auto_play: false,
             music_custom_playlist_url: None,
+            quick_note_target: QuickNoteTarget::RecentSession,
+            display_hours_precision: HoursPrecision::TwoDecimals,
+            weekly_goal_minutes: 840,
+            screenshot_interval_min_minutes: 10,
+            screenshot_interval_max_minutes: 20,
+            screenshot_format: ScreenshotFormat::default(),
+            screenshot_blur: false,
+            timezone: None,
+            streak_grace_days: 0,
+            auto_backup_interval_hours: 24,
+            auto_backup_keep: 10,
+            backup_compress: false,
+            productivity_active_from: None,
+            productivity_active_to: None,
+            productivity_grace_seconds: 15,
+            pomodoro_enabled: false,
+            pomodoro_work_minutes: 25,
+            pomodoro_break_minutes: 5,
+            minimum_session_minutes: 0,
+            api_enabled: false,
+            api_port: 4587,
+            api_token: None,
+            goal_webhook_url: None,
+            reflection_prompts: Vec::new(),
+            mastery_target_hours: 10_000,
+            goal_reminder_percent: 0,
+            reminder_enabled: false,
+            reminder_time: None,
+            idle_behavior: IdleBehavior::Pause,
+            idle_prompt_timeout_minutes: 10,
+            active_requires_focus_app: false,
+            screenshot_max_storage_mb: 0,
+            streak_milestones: DEFAULT_STREAK_MILESTONES.to_vec(),
+            weekly_summary_enabled: false,
+            weekly_summary_weekday: 6,
+            weekly_summary_time: Some("18:00".into()),
+            weekly_summary_delivery: WeeklySummaryDelivery::default(),
+            idle_poll_seconds: 30,
+            week_start_day: WeekStartDay::default(),
+            max_session_minutes: 0,
+            daily_goal_by_weekday: None,
         }
     }
 }
@@ -148,38 +480,274 @@ impl From<SettingsRow> for AppSettings {
             screenshot_enabled: value.screenshot_enabled.unwrap_or(0) == 1,
             screenshot_storage_path: value.screenshot_storage_path,
             screenshot_retention_days: value.screenshot_retention_days.unwrap_or(7),
+            screenshot_privacy_mode: value.screenshot_privacy_mode.unwrap_or(0) == 1,
             music_enabled: value.music_enabled.unwrap_or(0) == 1,
             music_playlist_type: value.music_playlist_type.unwrap_or_else(|| "focus".into()),
             music_volume: value.music_volume.unwrap_or(0.5),
             music_auto_play: value.music_auto_play.unwrap_or(0) == 1,
             music_custom_playlist_url: value.music_custom_playlist_url,
+            quick_note_target: value.quick_note_target.and_then(|v| v.parse().ok()).unwrap_or_default(),
+            display_hours_precision: value.display_hours_precision.and_then(|v| v.parse().ok()).unwrap_or_default(),
+            weekly_goal_minutes: value.weekly_goal_minutes.unwrap_or(value.daily_goal_minutes * 7),
+            screenshot_interval_min_minutes: value.screenshot_interval_min_minutes.unwrap_or(10),
+            screenshot_interval_max_minutes: value.screenshot_interval_max_minutes.unwrap_or(20),
+            screenshot_format: value
+                .screenshot_format
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            screenshot_blur: value.screenshot_blur.unwrap_or(0) == 1,
+            timezone: value.timezone,
+            streak_grace_days: value.streak_grace_days.unwrap_or(0),
+            auto_backup_interval_hours: value.auto_backup_interval_hours.unwrap_or(24),
+            auto_backup_keep: value.auto_backup_keep.unwrap_or(10),
+            backup_compress: value.backup_compress.unwrap_or(0) == 1,
+            productivity_active_from: value.productivity_active_from,
+            productivity_active_to: value.productivity_active_to,
+            productivity_grace_seconds: value.productivity_grace_seconds.unwrap_or(15),
+            pomodoro_enabled: value.pomodoro_enabled.unwrap_or(0) == 1,
+            pomodoro_work_minutes: value.pomodoro_work_minutes.unwrap_or(25),
+            pomodoro_break_minutes: value.pomodoro_break_minutes.unwrap_or(5),
+            minimum_session_minutes: value.minimum_session_minutes.unwrap_or(0),
+            api_enabled: value.api_enabled.unwrap_or(0) == 1,
+            api_port: value.api_port.unwrap_or(4587),
+            api_token: value.api_token,
+            goal_webhook_url: value.goal_webhook_url,
+            reflection_prompts: serde_json::from_str(&value.reflection_prompts).unwrap_or_default(),
+            mastery_target_hours: value.mastery_target_hours.unwrap_or(10_000),
+            goal_reminder_percent: value.goal_reminder_percent.unwrap_or(0),
+            reminder_enabled: value.reminder_enabled.unwrap_or(0) == 1,
+            reminder_time: value.reminder_time,
+            idle_behavior: value.idle_behavior.and_then(|v| v.parse().ok()).unwrap_or_default(),
+            idle_prompt_timeout_minutes: value.idle_prompt_timeout_minutes.unwrap_or(10),
+            active_requires_focus_app: value.active_requires_focus_app.unwrap_or(0) == 1,
+            screenshot_max_storage_mb: value.screenshot_max_storage_mb.unwrap_or(0),
+            streak_milestones: serde_json::from_str(&value.streak_milestones).unwrap_or_else(|_| DEFAULT_STREAK_MILESTONES.to_vec()),
+            weekly_summary_enabled: value.weekly_summary_enabled.unwrap_or(0) == 1,
+            weekly_summary_weekday: value.weekly_summary_weekday.unwrap_or(6),
+            weekly_summary_time: value.weekly_summary_time,
+            weekly_summary_delivery: value.weekly_summary_delivery.and_then(|v| v.parse().ok()).unwrap_or_default(),
+            idle_poll_seconds: value.idle_poll_seconds.unwrap_or(30),
+            week_start_day: value.week_start_day.and_then(|v| v.parse().ok()).unwrap_or_default(),
+            max_session_minutes: value.max_session_minutes.unwrap_or(0),
+            daily_goal_by_weekday: value.daily_goal_by_weekday.and_then(|json| serde_json::from_str(&json).ok()),
         }
     }
 }
 
 impl AppSettings {
-    pub fn to_row(&self) -> AppResult<(i64, &str, i64, i64, i64, String, String, Option<String>, i64, Option<String>, i64, i64, String, f64, i64, Option<String>)> {
-        Ok((
-            1,
-            &self.skill_name,
-            self.daily_goal_minutes,
-            self.idle_timeout_minutes,
-            if self.productivity_mode_enabled { 1 } else { 0 },
-            serde_json::to_string(&self.allowed_apps)?,
-            serde_json::to_string(&self.blocked_apps)?,
-            self.auto_backup_path.clone(),
-            if self.screenshot_enabled { 1 } else { 0 },
-            self.screenshot_storage_path.clone(),
-            self.screenshot_retention_days,
-            if self.music_enabled { 1 } else { 0 },
-            self.music_playlist_type.clone(),
-            self.music_volume,
-            if self.music_auto_play { 1 } else { 0 },
-            self.music_custom_playlist_url.clone(),
-        ))
+    /// The daily enforcement window; `None` means productivity mode applies all day.
+    pub fn productivity_window(&self) -> AppResult<Option<(NaiveTime, NaiveTime)>> {
+        let from = self.productivity_active_from.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        let to = self.productivity_active_to.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        match (from, to) {
+            (Some(from), Some(to)) => Ok(Some((parse_time_of_day(from)?, parse_time_of_day(to)?))),
+            (None, None) => Ok(None),
+            _ => Err(AppError::Custom(
+                "Set both the start and end of the productivity schedule, or neither".into(),
+            )),
+        }
+    }
+
+    /// The local time of the daily practice reminder; `None` while reminders are off.
+    pub fn reminder_at(&self) -> AppResult<Option<NaiveTime>> {
+        if !self.reminder_enabled {
+            return Ok(None);
+        }
+        match self.reminder_time.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(time) => parse_time_of_day(time).map(Some),
+            None => Err(AppError::Custom("Pick a time for the daily practice reminder".into())),
+        }
+    }
+
+    /// The weekday and local time the weekly summary goes out; `None` while it is off.
+    pub fn weekly_summary_schedule(&self) -> AppResult<Option<(Weekday, NaiveTime)>> {
+        if !self.weekly_summary_enabled {
+            return Ok(None);
+        }
+        let day = u8::try_from(self.weekly_summary_weekday)
+            .ok()
+            .and_then(|d| Weekday::try_from(d).ok())
+            .ok_or_else(|| AppError::Custom("Pick a day of the week for the weekly summary".into()))?;
+        let at = match self.weekly_summary_time.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(time) => parse_time_of_day(time)?,
+            None => return Err(AppError::Custom("Pick a time for the weekly summary".into())),
+        };
+        let has_webhook = self.goal_webhook_url.as_deref().is_some_and(|url| !url.trim().is_empty());
+        if self.weekly_summary_delivery == WeeklySummaryDelivery::Webhook && !has_webhook {
+            return Err(AppError::Custom("Sending the weekly summary by webhook needs a webhook URL".into()));
+        }
+        Ok(Some((day, at)))
+    }
+
+    /// Clamps out-of-range values in place and reports each adjustment as a warning; values that
+    /// cannot be saved at all come back as errors and are left as they were.
+    pub fn review(&mut self) -> Vec<SettingsIssue> {
+        let mut issues = Vec::new();
+        if self.weekly_goal_minutes < self.daily_goal_minutes {
+            self.weekly_goal_minutes = self.daily_goal_minutes;
+            issues.push(SettingsIssue::warning(
+                "weekly_goal_minutes",
+                format!("Weekly goal raised to the daily goal of {} minutes", self.daily_goal_minutes),
+            ));
+        }
+        if let Some(goals) = self.daily_goal_by_weekday.as_mut().filter(|goals| goals.iter().any(|g| *g < 0)) {
+            goals.iter_mut().for_each(|goal| *goal = (*goal).max(0));
+            issues.push(SettingsIssue::warning("daily_goal_by_weekday", "Negative weekday goals raised to 0"));
+        }
+        if self.mastery_target_hours < MIN_MASTERY_TARGET_HOURS {
+            self.mastery_target_hours = MIN_MASTERY_TARGET_HOURS;
+            issues.push(SettingsIssue::warning(
+                "mastery_target_hours",
+                format!("Mastery target raised to the {} hour minimum", MIN_MASTERY_TARGET_HOURS),
+            ));
+        }
+        if self.screenshot_max_storage_mb < 0 {
+            self.screenshot_max_storage_mb = 0;
+            issues.push(SettingsIssue::warning("screenshot_max_storage_mb", "Screenshot storage limit raised to 0 (no limit)"));
+        }
+        if self.max_session_minutes < 0 {
+            self.max_session_minutes = 0;
+            issues.push(SettingsIssue::warning("max_session_minutes", "Maximum session length raised to 0 (no limit)"));
+        }
+        let poll = self.idle_poll_seconds.clamp(MIN_IDLE_POLL_SECONDS, MAX_IDLE_POLL_SECONDS);
+        if poll != self.idle_poll_seconds {
+            self.idle_poll_seconds = poll;
+            issues.push(SettingsIssue::warning(
+                "idle_poll_seconds",
+                format!("Idle check interval kept between {MIN_IDLE_POLL_SECONDS} and {MAX_IDLE_POLL_SECONDS} seconds, now {poll}"),
+            ));
+        }
+        let mut milestones: Vec<i64> = self.streak_milestones.iter().copied().filter(|days| *days > 0).collect();
+        milestones.sort_unstable();
+        milestones.dedup();
+        if milestones.len() != self.streak_milestones.len() {
+            issues.push(SettingsIssue::warning("streak_milestones", "Duplicate and non-positive streak milestones removed"));
+        }
+        self.streak_milestones = milestones;
+        let mut prompts: Vec<String> = Vec::new();
+        for prompt in self.reflection_prompts.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            if !prompts.iter().any(|existing| existing == prompt) {
+                prompts.push(prompt.to_string());
+            }
+        }
+        if prompts.len() != self.reflection_prompts.len() {
+            issues.push(SettingsIssue::warning("reflection_prompts", "Blank and duplicate reflection prompts removed"));
+        }
+        self.reflection_prompts = prompts;
+
+        let mut check = |field: &'static str, result: AppResult<()>| {
+            if let Err(err) = result {
+                issues.push(SettingsIssue::error(field, err.to_string()));
+            }
+        };
+        check("timezone", crate::timezone::validate_timezone(self.timezone.as_deref()));
+        if self.productivity_mode_enabled {
+            check("allowed_apps", self.allowed_apps.iter().try_for_each(AppRule::validate));
+            check("blocked_apps", self.blocked_apps.iter().try_for_each(AppRule::validate));
+        }
+        check("productivity_active_from", self.productivity_window().map(drop));
+        check("reminder_time", self.reminder_at().map(drop));
+        check("weekly_summary_time", self.weekly_summary_schedule().map(drop));
+        if self.pomodoro_enabled && (self.pomodoro_work_minutes < 1 || self.pomodoro_break_minutes < 1) {
+            issues.push(SettingsIssue::error(
+                "pomodoro_work_minutes",
+                "Pomodoro work and break lengths must be at least 1 minute",
+            ));
+        }
+        if self.idle_prompt_timeout_minutes < 1 {
+            issues.push(SettingsIssue::error("idle_prompt_timeout_minutes", "The idle prompt must wait at least 1 minute"));
+        }
+        if !(0..=99).contains(&self.goal_reminder_percent) {
+            issues.push(SettingsIssue::error(
+                "goal_reminder_percent",
+                "Goal reminder must be between 1 and 99 percent, or 0 to turn it off",
+            ));
+        }
+        if self.screenshot_interval_min_minutes < 1
+            || self.screenshot_interval_min_minutes > self.screenshot_interval_max_minutes
+        {
+            issues.push(SettingsIssue::error(
+                "screenshot_interval_min_minutes",
+                "Screenshot interval minimum must be at least 1 and not exceed the maximum",
+            ));
+        }
+        if let Some(url) = self.goal_webhook_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                issues.push(SettingsIssue::error("goal_webhook_url", "Goal webhook URL must start with http:// or https://"));
+            }
+        }
+        if self.api_enabled {
+            if !(1024..=65535).contains(&self.api_port) {
+                issues.push(SettingsIssue::error("api_port", "Local API port must be between 1024 and 65535"));
+            }
+            if self.api_token.as_deref().filter(|t| !t.trim().is_empty()).is_none() {
+                issues.push(SettingsIssue::error("api_token", "Set an access token before enabling the local API"));
+            }
+        }
+        issues
+    }
+
+    /// The daily goal in minutes for `day`, from the weekday goals when they are set.
+    pub fn daily_goal_for(&self, day: NaiveDate) -> i64 {
+        match self.daily_goal_by_weekday {
+            Some(goals) => goals[day.weekday().num_days_from_monday() as usize],
+            None => self.daily_goal_minutes,
+        }
+    }
+
+    /// The goal a streak day must meet, or `None` on a rest day the weekday goals set to zero.
+    pub fn streak_goal_for(&self, day: NaiveDate) -> Option<i64> {
+        let goal = self.daily_goal_for(day);
+        (self.daily_goal_by_weekday.is_none() || goal > 0).then_some(goal)
+    }
+
+    /// Whether `today_minutes` has reached the goal reminder fraction without meeting the goal itself,
+    /// which `goal:reached` already covers.
+    pub fn goal_reminder_due(&self, today: NaiveDate, today_minutes: f64) -> bool {
+        let goal = self.daily_goal_for(today);
+        if self.goal_reminder_percent <= 0 || goal <= 0 {
+            return false;
+        }
+        let goal = goal as f64;
+        today_minutes >= goal * self.goal_reminder_percent as f64 / 100.0 && today_minutes < goal
+    }
+
+    /// The largest configured milestone that `streak_days` reaches and that is above `highest_reached`,
+    /// so a streak that skips past several milestones at once is only celebrated once.
+    pub fn streak_milestone_due(&self, streak_days: u32, highest_reached: i64) -> Option<i64> {
+        self.streak_milestones
+            .iter()
+            .copied()
+            .filter(|m| *m > highest_reached && *m <= i64::from(streak_days))
+            .max()
+    }
+
+    /// How long the idle monitor sleeps between checks, kept within the supported bounds.
+    pub fn idle_poll_interval(&self) -> std::time::Duration {
+        let secs = self.idle_poll_seconds.clamp(MIN_IDLE_POLL_SECONDS, MAX_IDLE_POLL_SECONDS);
+        std::time::Duration::from_secs(secs as u64)
     }
 }
 
+/// Optional bounds on `start_time`; `from` is inclusive and `to` exclusive. `limit` and `offset`
+/// page through the newest-first list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// One page of session history plus how many sessions match the filter in total.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPage {
+    pub sessions: Vec<SessionHistoryRow>,
+    pub total: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionEditPayload {
     pub id: i64,
@@ -190,6 +758,8 @@ pub struct SessionEditPayload {
     pub what_practiced: Option<String>,
     pub what_learned: Option<String>,
     pub next_focus: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -199,17 +769,39 @@ pub struct ExportRequest {
     pub include_settings: bool,
 }
 
+/// What an export would contain, reported before writing it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportPreview {
+    pub session_count: i64,
+    /// Extrapolated from a sample of rendered sessions, so only a rough guide for large histories.
+    pub estimated_bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportRequest {
     pub file_path: String,
     pub import_settings: bool,
 }
 
+/// What `import_data` did with each session in the file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ExportFormat {
     Csv,
     Json,
+    #[serde(alias = "md")]
+    Markdown,
+    Ics,
+    /// One compact JSON object per session per line, for jq, DuckDB and the like.
+    #[serde(alias = "jsonl", alias = "ndjson")]
+    JsonLines,
 }
 
 impl Display for ExportFormat {
@@ -217,6 +809,9 @@ impl Display for ExportFormat {
         match self {
             ExportFormat::Csv => write!(f, "csv"),
             ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Markdown => write!(f, "md"),
+            ExportFormat::Ics => write!(f, "ics"),
+            ExportFormat::JsonLines => write!(f, "jsonl"),
         }
     }
 }
@@ -228,11 +823,320 @@ impl FromStr for ExportFormat {
         match s.to_lowercase().as_str() {
             "csv" => Ok(Self::Csv),
             "json" => Ok(Self::Json),
+            "md" | "markdown" => Ok(Self::Markdown),
+            "ics" => Ok(Self::Ics),
+            "jsonl" | "ndjson" => Ok(Self::JsonLines),
             _ => Err(AppError::UnsupportedExportFormat),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppMatchKind {
+    /// Substring of a running process name.
+    #[default]
+    Process,
+    /// Substring of the foreground window's title, e.g. a browser tab.
+    WindowTitle,
+}
+
+/// An allowlist/blocklist entry for productivity mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "AppRuleRepr")]
+pub struct AppRule {
+    pub pattern: String,
+    pub kind: AppMatchKind,
+    /// Treat `pattern` as a regular expression rather than a substring.
+    pub regex: bool,
+    /// Compiled once on load; `None` falls back to substring matching.
+    #[serde(skip)]
+    compiled: Option<Regex>,
+}
+
+/// Lists saved before match kinds existed are plain strings of process names.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AppRuleRepr {
+    Name(String),
+    Rule {
+        pattern: String,
+        #[serde(default)]
+        kind: AppMatchKind,
+        #[serde(default)]
+        regex: bool,
+    },
+}
+
+impl From<AppRuleRepr> for AppRule {
+    fn from(repr: AppRuleRepr) -> Self {
+        match repr {
+            AppRuleRepr::Name(pattern) => AppRule::new(pattern, AppMatchKind::Process, false),
+            AppRuleRepr::Rule { pattern, kind, regex } => AppRule::new(pattern, kind, regex),
+        }
+    }
+}
+
+impl AppRule {
+    pub fn new(pattern: String, kind: AppMatchKind, regex: bool) -> Self {
+        let compiled = if regex { build_rule_regex(&pattern).ok() } else { None };
+        Self { pattern, kind, regex, compiled }
+    }
+
+    /// Reports a regex rule whose pattern does not compile.
+    pub fn validate(&self) -> AppResult<()> {
+        if !self.regex {
+            return Ok(());
+        }
+        build_rule_regex(&self.pattern)
+            .map(|_| ())
+            .map_err(|e| AppError::Custom(format!("Invalid pattern '{}': {e}", self.pattern)))
+    }
+
+    /// Case-insensitive match; `process_names` are expected to be lowercase already.
+    pub fn matches(&self, process_names: &[String], window_title: Option<&str>) -> bool {
+        match self.kind {
+            AppMatchKind::Process => process_names.iter().any(|name| self.matches_text(name)),
+            AppMatchKind::WindowTitle => window_title.is_some_and(|title| self.matches_text(title)),
+        }
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.compiled {
+            Some(re) => re.is_match(text),
+            None => text.to_lowercase().contains(&self.pattern.to_lowercase()),
+        }
+    }
+}
+
+fn parse_time_of_day(value: &str) -> AppResult<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map_err(|_| AppError::Custom(format!("Invalid time of day: {value}")))
+}
+
+fn build_rule_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotFormat {
+    #[default]
+    Jpeg,
+    Png,
+}
+
+impl ScreenshotFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::Png => "png",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Jpeg => "image/jpeg",
+            ScreenshotFormat::Png => "image/png",
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScreenshotFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScreenshotFormat::Jpeg => write!(f, "jpeg"),
+            ScreenshotFormat::Png => write!(f, "png"),
+        }
+    }
+}
+
+impl FromStr for ScreenshotFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            "png" => Ok(Self::Png),
+            other => Err(AppError::Custom(format!("Unknown screenshot format: {other}"))),
+        }
+    }
+}
+
+/// What the idle monitor does once input has been idle past `idle_timeout_minutes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleBehavior {
+    /// Pause the session until input returns.
+    #[default]
+    Pause,
+    /// Keep the clock running but leave the idle span out of the logged time.
+    Subtract,
+    /// Count idle time as practice.
+    Ignore,
+}
+
+impl Display for IdleBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdleBehavior::Pause => write!(f, "pause"),
+            IdleBehavior::Subtract => write!(f, "subtract"),
+            IdleBehavior::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl FromStr for IdleBehavior {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pause" => Ok(Self::Pause),
+            "subtract" => Ok(Self::Subtract),
+            "ignore" => Ok(Self::Ignore),
+            other => Err(AppError::Custom(format!("Unknown idle behavior: {other}"))),
+        }
+    }
+}
+
+/// Where the weekly summary is delivered; it is always emitted as `weekly:summary` as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeeklySummaryDelivery {
+    /// A JSON file under `exports/weekly`.
+    #[default]
+    File,
+    /// POSTed to `goal_webhook_url`.
+    Webhook,
+}
+
+impl Display for WeeklySummaryDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeeklySummaryDelivery::File => write!(f, "file"),
+            WeeklySummaryDelivery::Webhook => write!(f, "webhook"),
+        }
+    }
+}
+
+impl FromStr for WeeklySummaryDelivery {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Self::File),
+            "webhook" => Ok(Self::Webhook),
+            other => Err(AppError::Custom(format!("Unknown weekly summary delivery: {other}"))),
+        }
+    }
+}
+
+/// The day calendar weeks begin on, for the weekly total and weekly goal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekStartDay {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStartDay {
+    /// The first day of the week that contains `day`.
+    pub fn week_containing(self, day: NaiveDate) -> NaiveDate {
+        let offset = match self {
+            WeekStartDay::Monday => day.weekday().num_days_from_monday(),
+            WeekStartDay::Sunday => day.weekday().num_days_from_sunday(),
+        };
+        day - chrono::Duration::days(i64::from(offset))
+    }
+}
+
+impl Display for WeekStartDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeekStartDay::Monday => write!(f, "monday"),
+            WeekStartDay::Sunday => write!(f, "sunday"),
+        }
+    }
+}
+
+impl FromStr for WeekStartDay {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "monday" => Ok(Self::Monday),
+            "sunday" => Ok(Self::Sunday),
+            other => Err(AppError::Custom(format!("Unknown week start day: {other}"))),
+        }
+    }
+}
+
+/// Recap of the seven days ending on `to`, payload of `weekly:summary`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total_minutes: f64,
+    pub days_practiced: u32,
+    pub best_day: Option<DailyTotal>,
+    pub streak_days: u32,
+    /// One entry per day of the week, oldest first, including days without practice.
+    pub days: Vec<DailyTotal>,
+}
+
+/// Payload of `timer:idle-prompt`; the session stops itself if nobody answers within the timeout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlePrompt {
+    pub timeout_minutes: i64,
+}
+
+/// Where `append_note` writes when no session is running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickNoteTarget {
+    #[default]
+    RecentSession,
+    DailyNote,
+}
+
+impl Display for QuickNoteTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuickNoteTarget::RecentSession => write!(f, "recent_session"),
+            QuickNoteTarget::DailyNote => write!(f, "daily_note"),
+        }
+    }
+}
+
+impl FromStr for QuickNoteTarget {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recent_session" => Ok(Self::RecentSession),
+            "daily_note" => Ok(Self::DailyNote),
+            other => Err(AppError::Custom(format!("Unknown quick note target: {other}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickNoteResult {
+    pub session_id: Option<i64>,
+    pub daily_note_date: Option<NaiveDate>,
+    pub line: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductivitySnapshot {
     pub allowed_active: bool,
@@ -242,31 +1146,141 @@ pub struct ProductivitySnapshot {
 }
 
 impl ProductivitySnapshot {
-    pub fn idle(minutes: i64) -> Self {
+    /// Nothing to enforce: no allow or block rules are configured.
+    pub fn idle() -> Self {
         Self {
             allowed_active: true,
             blocked_triggered: false,
             offending_processes: Vec::new(),
-            timestamp: Utc::now() + Duration::minutes(minutes),
+            timestamp: Utc::now(),
         }
     }
 }
 
+/// The running session's clock. Practice time is `accumulated_seconds` plus the span since
+/// `last_resume_at`; pausing (manually, for idle, productivity or a Pomodoro break) banks that span,
+/// and stopping saves `elapsed_seconds`, so time before a pause is never lost.
 #[derive(Debug, Clone)]
 pub struct ActiveSession {
     pub session_id: i64,
     pub skill_id: i64,
     pub started_at: DateTime<Utc>,
     pub last_resume_at: DateTime<Utc>,
+    /// Monotonic twin of `last_resume_at`; it does not advance while the machine sleeps.
+    pub last_resume_instant: Instant,
     pub accumulated_seconds: i64,
     pub auto_paused: bool,
     pub last_reason: Option<String>,
+    /// Idle time already taken out of the session under `IdleBehavior::Subtract`.
+    pub idle_seconds: i64,
+    /// Last input before an idle span that is still going on.
+    pub idle_since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    Break,
 }
 
+/// Wall-clock time running ahead of monotonic time by more than this means the machine slept.
+pub const SLEEP_DRIFT_THRESHOLD_SECS: i64 = 60;
+
 impl ActiveSession {
     pub fn elapsed_seconds(&self) -> i64 {
-        let since_resume = (Utc::now() - self.last_resume_at).num_seconds();
-        self.accumulated_seconds + since_resume.max(0)
+        self.elapsed_seconds_at(Utc::now())
+    }
+
+    /// Practice time as of `now`: banked spans plus the running one, less idle time. Paused time
+    /// is never part of either span, so it can't leak into the total.
+    pub fn elapsed_seconds_at(&self, now: DateTime<Utc>) -> i64 {
+        if self.auto_paused {
+            return (self.accumulated_seconds - self.idle_seconds).max(0);
+        }
+        let since_resume = (now - self.last_resume_at).num_seconds();
+        let idle_now = self.idle_since.map_or(0, |since| (now - since).num_seconds().max(0));
+        (self.accumulated_seconds + since_resume.max(0) - self.idle_seconds - idle_now).max(0)
+    }
+
+    /// Opens an idle span starting at the last input, but never before the clock last resumed.
+    /// Returns false while paused or when a span is already open.
+    pub fn begin_idle(&mut self, last_input: DateTime<Utc>) -> bool {
+        if self.auto_paused || self.idle_since.is_some() {
+            return false;
+        }
+        self.idle_since = Some(last_input.max(self.last_resume_at));
+        true
+    }
+
+    /// Closes the open idle span at `until`, deducting it. Returns false when none was open.
+    pub fn end_idle(&mut self, until: DateTime<Utc>) -> bool {
+        let Some(since) = self.idle_since.take() else {
+            return false;
+        };
+        self.idle_seconds += (until - since).num_seconds().max(0);
+        true
+    }
+
+    /// Banks the running span and freezes the clock. Returns false if already paused.
+    pub fn pause(&mut self, reason: Option<String>) -> bool {
+        self.pause_at(reason, Utc::now(), Instant::now())
+    }
+
+    pub fn pause_at(&mut self, reason: Option<String>, now: DateTime<Utc>, now_instant: Instant) -> bool {
+        if self.auto_paused {
+            return false;
+        }
+        self.reconcile_at(now, now_instant);
+        self.end_idle(now);
+        let since_resume = (now - self.last_resume_at).num_seconds();
+        self.accumulated_seconds += since_resume.max(0);
+        self.auto_paused = true;
+        self.last_reason = reason;
+        true
+    }
+
+    /// Restarts the clock from now so the paused span is never counted. Returns false if not paused.
+    pub fn resume(&mut self) -> bool {
+        self.resume_at(Utc::now(), Instant::now())
+    }
+
+    pub fn resume_at(&mut self, now: DateTime<Utc>, now_instant: Instant) -> bool {
+        if !self.auto_paused {
+            return false;
+        }
+        self.last_resume_at = now;
+        self.last_resume_instant = now_instant;
+        self.auto_paused = false;
+        self.last_reason = None;
+        true
+    }
+
+    pub fn reconcile(&mut self) -> i64 {
+        self.reconcile_at(Utc::now(), Instant::now())
+    }
+
+    /// Folds the current span into `accumulated_seconds` crediting only monotonic time when
+    /// the wall clock jumped (sleep/hibernate). Returns the number of seconds discarded.
+    pub fn reconcile_at(&mut self, now: DateTime<Utc>, now_instant: Instant) -> i64 {
+        let wall = (now - self.last_resume_at).num_seconds().max(0);
+        let monotonic = now_instant
+            .saturating_duration_since(self.last_resume_instant)
+            .as_secs() as i64;
+        let drift = wall - monotonic;
+        if drift <= SLEEP_DRIFT_THRESHOLD_SECS {
+            return 0;
+        }
+
+        // Only the awake part of an idle span open across the sleep is deducted; the slept time is already gone
+        if let Some(since) = self.idle_since.take() {
+            let awake_before_idle = (since - self.last_resume_at).num_seconds().max(0);
+            self.idle_seconds += (monotonic - awake_before_idle).max(0);
+        }
+        self.accumulated_seconds += monotonic;
+        self.last_resume_at = now;
+        self.last_resume_instant = now_instant;
+        drift
     }
 
     pub fn as_status(&self) -> TimerStatus {
@@ -278,22 +1292,336 @@ impl ActiveSession {
             last_reason: self.last_reason.clone(),
         }
     }
+
+    pub fn as_info(&self, skill_name: String) -> ActiveSessionInfo {
+        ActiveSessionInfo {
+            session_id: self.session_id,
+            skill_id: self.skill_id,
+            skill_name,
+            started_at: self.started_at,
+            elapsed_seconds: self.elapsed_seconds(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEventKind {
+    Started,
+    Stopped,
+    Paused,
+    Resumed,
+    SleepSkipped,
+    ScreenshotCaptured,
+    GoalReached,
+    /// Idle time from here on is left out of the session without pausing it.
+    IdleStarted,
+    IdleEnded,
+}
+
+impl Display for SessionEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEventKind::Started => write!(f, "started"),
+            SessionEventKind::Stopped => write!(f, "stopped"),
+            SessionEventKind::Paused => write!(f, "paused"),
+            SessionEventKind::Resumed => write!(f, "resumed"),
+            SessionEventKind::SleepSkipped => write!(f, "sleep_skipped"),
+            SessionEventKind::ScreenshotCaptured => write!(f, "screenshot_captured"),
+            SessionEventKind::GoalReached => write!(f, "goal_reached"),
+            SessionEventKind::IdleStarted => write!(f, "idle_started"),
+            SessionEventKind::IdleEnded => write!(f, "idle_ended"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SessionEvent {
+    pub id: i64,
+    pub session_id: i64,
+    pub event_type: String,
+    pub at: String,
+    pub detail: Option<String>,
+}
+
+/// What a screenshot cleanup pass removed, captures and thumbnails together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScreenshotCleanup {
+    pub deleted_count: usize,
+    pub freed_mb: f64,
+}
+
+/// A capture taken while the session was running; the file itself stays in the screenshot folder.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SessionScreenshot {
+    pub id: i64,
+    pub session_id: i64,
+    pub path: String,
+    pub captured_at: String,
+}
+
+/// What happened to the session when the timer stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StopOutcome {
+    Saved { minutes: f64 },
+    /// Shorter than `minimum_session_minutes`; the row was deleted.
+    Discarded { minutes: f64, minimum_minutes: i64 },
+}
+
+impl StopOutcome {
+    pub fn decide(minutes: f64, minimum_minutes: i64) -> Self {
+        if minutes < minimum_minutes as f64 {
+            StopOutcome::Discarded { minutes, minimum_minutes }
+        } else {
+            StopOutcome::Saved { minutes }
+        }
+    }
+}
+
+/// Payload of `goal:approaching`, sent once a day when the goal reminder percentage is crossed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalApproachNotification {
+    pub percent: i64,
+    pub total_minutes: f64,
+    pub goal_minutes: i64,
+}
+
+/// Payload of `milestone:reached`, sent the first time the streak reaches a configured milestone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneNotification {
+    pub milestone_days: i64,
+    pub streak_days: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoalNotification {
     pub achieved_at: DateTime<Utc>,
     pub total_minutes: f64,
+    pub goal_minutes: i64,
 }
 
+/// Body POSTed to `goal_webhook_url` the first time the daily goal is met each day.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalWebhookPayload {
+    pub date: NaiveDate,
+    pub total_minutes: f64,
+    pub skill_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Skill {
+    pub id: i64,
+    pub skill_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SkillSummary {
     pub id: i64,
     pub skill_name: String,
     pub total_minutes: f64,
 }
 
+/// Result of folding one skill into another; `target` is the skill that remains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillMergeReport {
+    pub target: Skill,
+    pub moved_sessions: u64,
+}
+
+/// What `factory_reset` removed; `screenshots_removed` stays 0 unless the folder was cleared too.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FactoryResetReport {
+    pub sessions_removed: u64,
+    pub skills_removed: u64,
+    pub screenshots_removed: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateSkillPayload {
     pub skill_name: String,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// The value was adjusted to something that can be saved.
+    Warning,
+    /// The value cannot be saved as it is.
+    Error,
+}
+
+/// One finding from `AppSettings::review`; `field` is the settings key it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsIssue {
+    pub field: String,
+    pub message: String,
+    pub severity: IssueSeverity,
+}
+
+impl SettingsIssue {
+    fn warning(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into(), severity: IssueSeverity::Warning }
+    }
+
+    fn error(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into(), severity: IssueSeverity::Error }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goal_progress_caps_and_flags_met() {
+        let over = GoalProgress::new(600, 750.0);
+        assert_eq!(over.progress, 1.0);
+        assert!(over.met);
+
+        let partial = GoalProgress::new(600, 150.0);
+        assert_eq!(partial.progress, 0.25);
+        assert!(!partial.met);
+
+        assert!(!GoalProgress::new(0, 10.0).met);
+    }
+
+    #[test]
+    fn app_rules_accept_legacy_strings() {
+        let rules: Vec<AppRule> =
+            serde_json::from_str(r#"["code", {"pattern": "YouTube", "kind": "window_title"}]"#).unwrap();
+        assert_eq!(rules[0].kind, AppMatchKind::Process);
+        assert_eq!(rules[1].kind, AppMatchKind::WindowTitle);
+
+        let processes = vec!["chrome".to_string()];
+        assert!(!rules[1].matches(&processes, Some("Rust docs - Chrome")));
+        assert!(rules[1].matches(&processes, Some("Lofi beats - youtube - Chrome")));
+        assert!(!rules[0].matches(&processes, Some("code")));
+    }
+
+    #[test]
+    fn regex_rules_distinguish_similar_names() {
+        let block = AppRule::new(r"youtube\.com".into(), AppMatchKind::WindowTitle, true);
+        assert!(block.matches(&[], Some("YouTube.com - Chrome")));
+        assert!(!block.matches(&[], Some("youtube-dl --help")));
+
+        let broken = AppRule::new("youtube(".into(), AppMatchKind::Process, true);
+        assert!(broken.validate().is_err());
+        assert!(broken.matches(&["youtube(".to_string()], None));
+    }
+
+    #[test]
+    fn short_sessions_are_discarded() {
+        assert_eq!(
+            StopOutcome::decide(0.5, 2),
+            StopOutcome::Discarded { minutes: 0.5, minimum_minutes: 2 }
+        );
+        assert_eq!(StopOutcome::decide(2.0, 2), StopOutcome::Saved { minutes: 2.0 });
+        assert_eq!(StopOutcome::decide(0.1, 0), StopOutcome::Saved { minutes: 0.1 });
+    }
+
+    #[test]
+    fn goal_reminder_fires_between_threshold_and_goal() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let mut settings = AppSettings { daily_goal_minutes: 60, ..AppSettings::default() };
+        assert!(!settings.goal_reminder_due(monday, 50.0));
+        settings.goal_reminder_percent = 80;
+        assert!(!settings.goal_reminder_due(monday, 47.9));
+        assert!(settings.goal_reminder_due(monday, 48.0));
+        assert!(!settings.goal_reminder_due(monday, 60.0));
+
+        // Weekday goals replace the single goal: a rest day never reminds, a long day reminds later
+        settings.daily_goal_by_weekday = Some([60, 60, 60, 60, 60, 0, 120]);
+        let saturday = monday + chrono::Duration::days(5);
+        let sunday = monday + chrono::Duration::days(6);
+        assert_eq!(settings.daily_goal_for(saturday), 0);
+        assert!(!settings.goal_reminder_due(saturday, 50.0));
+        assert!(!settings.goal_reminder_due(sunday, 60.0));
+        assert!(settings.goal_reminder_due(sunday, 96.0));
+    }
+
+    #[test]
+    fn streak_milestone_is_announced_once() {
+        let settings = AppSettings::default();
+        assert_eq!(settings.streak_milestone_due(6, 0), None);
+        assert_eq!(settings.streak_milestone_due(7, 0), Some(7));
+        assert_eq!(settings.streak_milestone_due(12, 7), None);
+        // A streak that was never checked in between only gets its largest milestone
+        assert_eq!(settings.streak_milestone_due(45, 7), Some(30));
+        // Rebuilding a broken streak does not repeat milestones already reached
+        assert_eq!(settings.streak_milestone_due(7, 30), None);
+    }
+
+    #[test]
+    fn weeks_start_on_the_configured_day() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        // 2024-03-03 is a Sunday and 2024-03-04 a Monday
+        assert_eq!(WeekStartDay::Monday.week_containing(date(3)), date(3) - chrono::Duration::days(6));
+        assert_eq!(WeekStartDay::Monday.week_containing(date(4)), date(4));
+        assert_eq!(WeekStartDay::Sunday.week_containing(date(3)), date(3));
+        assert_eq!(WeekStartDay::Sunday.week_containing(date(4)), date(3));
+        assert_eq!(WeekStartDay::Sunday.week_containing(date(9)), date(3));
+        assert_eq!("sunday".parse::<WeekStartDay>().unwrap(), WeekStartDay::Sunday);
+        assert_eq!(WeekStartDay::Sunday.to_string(), "sunday");
+    }
+
+    #[test]
+    fn idle_poll_interval_stays_in_bounds() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.idle_poll_interval().as_secs(), 30);
+        settings.idle_poll_seconds = 0;
+        assert_eq!(settings.idle_poll_interval().as_secs(), 2);
+        settings.idle_poll_seconds = 600;
+        assert_eq!(settings.idle_poll_interval().as_secs(), 60);
+    }
+
+    #[test]
+    fn quarter_hour_rounding() {
+        let precision = HoursPrecision::QuarterHour;
+        assert_eq!(precision.format_hours(1.9833), "2h");
+        assert_eq!(precision.format_hours(1.3), "1.25h");
+        assert_eq!(precision.format_hours(0.4), "0.5h");
+        assert_eq!(precision.format_hours(0.1), "0h");
+    }
+
+    #[test]
+    fn decimal_rounding() {
+        assert_eq!(HoursPrecision::TwoDecimals.format_hours(1.9833), "1.98h");
+        assert_eq!(HoursPrecision::OneDecimal.format_hours(1.9833), "2.0h");
+    }
+
+    #[test]
+    fn review_reports_clamps_as_warnings_and_bad_values_as_errors() {
+        assert!(AppSettings::default().review().is_empty());
+
+        let mut settings = AppSettings {
+            daily_goal_minutes: 60,
+            weekly_goal_minutes: 30,
+            idle_poll_seconds: 600,
+            streak_milestones: vec![30, 7, 7, 0],
+            ..AppSettings::default()
+        };
+        let issues = settings.review();
+        assert!(issues.iter().all(|i| i.severity == IssueSeverity::Warning));
+        let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["weekly_goal_minutes", "idle_poll_seconds", "streak_milestones"]);
+        assert_eq!(settings.weekly_goal_minutes, 60);
+        assert_eq!(settings.idle_poll_seconds, MAX_IDLE_POLL_SECONDS);
+        assert_eq!(settings.streak_milestones, [7, 30]);
+        assert!(settings.review().is_empty());
+
+        let mut settings = AppSettings {
+            productivity_mode_enabled: true,
+            blocked_apps: serde_json::from_str(r#"[{"pattern": "(", "kind": "process", "regex": true}]"#).unwrap(),
+            goal_reminder_percent: 150,
+            ..AppSettings::default()
+        };
+        let errors: Vec<String> = settings
+            .review()
+            .into_iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(errors, ["blocked_apps", "goal_reminder_percent"]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveTime, Utc};
+use log::warn;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db;
+use crate::timezone::DayZone;
+use crate::AppState;
+
+const REMINDER_CHECK_SECS: u64 = 30;
+
+/// Shows a desktop notification; failures are only logged since the matching event still reaches the UI.
+pub fn desktop(app: &AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification '{title}': {e}");
+    }
+}
+
+/// Once a day, from `reminder_time` on, reminds about practice if nothing has been logged or started yet.
+pub async fn reminder_worker(app: AppHandle, state: AppState) {
+    let mut checked_on = None;
+    loop {
+        tokio::time::sleep(Duration::from_secs(REMINDER_CHECK_SECS)).await;
+
+        let settings = state.settings.read().await.clone();
+        let Ok(Some(at)) = settings.reminder_at() else {
+            continue;
+        };
+        let zone = DayZone::from_setting(settings.timezone.as_deref());
+        let now = Utc::now();
+        let today = zone.date_of(now);
+        if !reminder_due(at, zone.time_of(now), today, checked_on) {
+            continue;
+        }
+
+        let practiced = if state.timer.active_session_id().await.is_some() {
+            true
+        } else {
+            match db::todays_minutes(&state.pool.get(), zone).await {
+                Ok(minutes) => minutes > 0.0,
+                Err(e) => {
+                    warn!("Unable to check today's practice for the reminder: {e}");
+                    continue;
+                }
+            }
+        };
+        checked_on = Some(today);
+        if !practiced {
+            app.emit("reminder:practice", &today).ok();
+            desktop(&app, "Time to practice", "You haven't practiced yet today");
+        }
+    }
+}
+
+/// The reminder is checked the first time the clock passes `at` on a day it has not been checked yet,
+/// so starting the app later in the evening still reminds.
+fn reminder_due(at: NaiveTime, now: NaiveTime, today: NaiveDate, checked_on: Option<NaiveDate>) -> bool {
+    now >= at && checked_on != Some(today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reminder_is_due_once_per_day_after_its_time() {
+        let at = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let evening = NaiveTime::from_hms_opt(18, 0, 30).unwrap();
+
+        assert!(!reminder_due(at, NaiveTime::from_hms_opt(17, 59, 0).unwrap(), today, None));
+        assert!(reminder_due(at, evening, today, None));
+        assert!(reminder_due(at, evening, today, today.pred_opt()));
+        assert!(!reminder_due(at, evening, today, Some(today)));
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::NaiveDate;
+use printpdf::{BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+
+use crate::errors::{AppError, AppResult};
+use crate::models::SessionHistoryRow;
+use crate::timezone::DayZone;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 18.0;
+const BODY_SIZE: f32 = 10.0;
+/// Helvetica averages about half an em per character, close enough to wrap prose.
+const CHAR_WIDTH_EM: f32 = 0.5;
+const PT_TO_MM: f32 = 0.3528;
+
+/// Everything printed on a practice report, gathered up front because the PDF document is not `Send`.
+pub struct PracticeReport {
+    pub skill_name: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Sessions in the range, newest first as the history list returns them.
+    pub sessions: Vec<SessionHistoryRow>,
+    pub streak_days: u32,
+}
+
+fn pdf_error(e: printpdf::Error) -> AppError {
+    AppError::Custom(format!("Unable to build the PDF report: {e}"))
+}
+
+/// Lays text out top to bottom, starting a new A4 page whenever the current one fills up.
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl Writer {
+    fn new(title: &str) -> AppResult<Self> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_error)?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_error)?;
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(Self { doc, layer, regular, bold, y: PAGE_HEIGHT - MARGIN })
+    }
+
+    fn line_height(size: f32) -> f32 {
+        size * PT_TO_MM * 1.4
+    }
+
+    fn ensure_room(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    /// Writes one line of cells at the given x offsets from the left margin.
+    fn row(&mut self, cells: &[(f32, &str)], size: f32, bold: bool) {
+        let height = Self::line_height(size);
+        self.ensure_room(height);
+        self.y -= height;
+        let font = if bold { &self.bold } else { &self.regular };
+        for (x, text) in cells {
+            self.layer.use_text(*text, size, Mm(MARGIN + x), Mm(self.y), font);
+        }
+    }
+
+    /// Writes `text` wrapped to the page width, starting `indent` mm from the margin.
+    fn paragraph(&mut self, text: &str, size: f32, indent: f32) {
+        let width = PAGE_WIDTH - 2.0 * MARGIN - indent;
+        let max_chars = (width / (size * PT_TO_MM * CHAR_WIDTH_EM)) as usize;
+        for line in wrap(text, max_chars) {
+            self.row(&[(indent, &line)], size, false);
+        }
+    }
+
+    fn rule(&mut self) {
+        self.ensure_room(3.0);
+        self.y -= 2.0;
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+        self.y -= 1.0;
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+}
+
+/// Breaks `text` into lines of at most `max_chars`, splitting on whitespace and hard-breaking longer words.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > max_chars {
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                }
+                lines.push(word.drain(..max_chars).collect());
+            }
+            let word: String = word.into_iter().collect();
+            let needed = if current.is_empty() { word.chars().count() } else { current.chars().count() + 1 + word.chars().count() };
+            if needed > max_chars {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&word);
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+    }
+    lines
+}
+
+/// Renders the report as an A4 PDF. The built-in Helvetica only covers Windows-1252, so other
+/// characters are dropped from the output.
+pub fn render_pdf(report: &PracticeReport, zone: DayZone) -> AppResult<Vec<u8>> {
+    let title = format!("{} practice report", report.skill_name);
+    let mut out = Writer::new(&title)?;
+
+    out.row(&[(0.0, &title)], 18.0, true);
+    out.row(&[(0.0, &format!("{} to {}", report.from, report.to))], 12.0, false);
+    out.rule();
+
+    let columns = [0.0, 30.0, 60.0, 85.0];
+    out.row(
+        &[(columns[0], "Date"), (columns[1], "Time"), (columns[2], "Duration"), (columns[3], "Tags")],
+        BODY_SIZE,
+        true,
+    );
+    if report.sessions.is_empty() {
+        out.row(&[(0.0, "No sessions in this range.")], BODY_SIZE, false);
+    }
+    for session in report.sessions.iter().rev() {
+        let start = zone.time_of(session.start).format("%H:%M").to_string();
+        let time = match session.end {
+            Some(end) => format!("{start}-{}", zone.time_of(end).format("%H:%M")),
+            None => format!("{start}-"),
+        };
+        let date = zone.date_of(session.start).to_string();
+        let duration = format!("{:.0} min", session.duration_minutes);
+        let tags = session.tags.join(", ");
+        out.row(
+            &[(columns[0], &date), (columns[1], &time), (columns[2], &duration), (columns[3], &tags)],
+            BODY_SIZE,
+            false,
+        );
+        let reflections = [
+            ("What I practiced", &session.what_practiced),
+            ("What I learned", &session.what_learned),
+            ("Next focus", &session.next_focus),
+            ("Notes", &session.notes),
+        ];
+        for (label, value) in reflections {
+            if let Some(text) = value.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+                out.paragraph(&format!("{label}: {text}"), BODY_SIZE - 1.0, 6.0);
+            }
+        }
+        out.gap(1.5);
+    }
+
+    let total_minutes: f64 = report.sessions.iter().map(|s| s.duration_minutes).sum();
+    out.rule();
+    out.row(&[(0.0, &format!("Total: {:.1} hours", total_minutes / 60.0))], 12.0, true);
+    out.row(&[(0.0, &format!("Current streak: {} days", report.streak_days))], 12.0, true);
+
+    out.doc.save_to_bytes().map_err(pdf_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn wraps_on_words_and_splits_long_ones() {
+        assert_eq!(wrap("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("first\nsecond", 20), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn renders_long_reports_across_pages() {
+        let sessions = (0..80)
+            .map(|i| SessionHistoryRow {
+                id: i,
+                start: DateTime::parse_from_rfc3339("2024-03-01T08:00:00Z").unwrap().with_timezone(&Utc),
+                end: None,
+                duration_minutes: 30.0,
+                notes: Some("A long reflection that has to wrap. ".repeat(10)),
+                what_practiced: None,
+                what_learned: None,
+                next_focus: None,
+                tags: vec!["scales".into()],
+                reflection_extra: Default::default(),
+            })
+            .collect();
+        let report = PracticeReport {
+            skill_name: "Piano".into(),
+            from: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(),
+            sessions,
+            streak_days: 3,
+        };
+        let pdf = render_pdf(&report, DayZone::from_setting(Some("UTC"))).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::{PathBuf};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use chrono::Utc;
 use screenshots::Screen;
@@ -7,80 +7,83 @@ use tokio::sync::RwLock;
 use log::{error, info, warn};
 use tauri::Emitter;
 
+use crate::db::{self, SharedPool};
 use crate::errors::{AppError, AppResult};
-use crate::models::AppSettings;
+use crate::models::{AppSettings, ScreenshotCleanup, ScreenshotFormat, SessionEventKind};
+
+// Privacy-mode fingerprint size: enough to show presence, too small to read.
+const FINGERPRINT_WIDTH: u32 = 64;
+const FINGERPRINT_HEIGHT: u32 = 36;
+// Blur mode shrinks each side by this factor before scaling back up.
+const BLUR_FACTOR: u32 = 16;
+// Gallery thumbnails are scaled down to this width, keeping the aspect ratio.
+const THUMBNAIL_WIDTH: u32 = 240;
+const THUMBNAIL_SUFFIX: &str = "_thumb";
 
 pub struct ScreenshotService {
     settings: std::sync::Arc<RwLock<AppSettings>>,
     storage_path: PathBuf,
+    pool: SharedPool,
 }
 
 impl ScreenshotService {
-    pub fn new(settings: std::sync::Arc<RwLock<AppSettings>>, storage_path: PathBuf) -> Self {
+    pub fn new(settings: std::sync::Arc<RwLock<AppSettings>>, storage_path: PathBuf, pool: SharedPool) -> Self {
         Self {
             settings,
             storage_path,
+            pool,
         }
     }
 
-    pub async fn capture_screenshot(&self) -> AppResult<PathBuf> {
-        let screens = Screen::all()?;
-        if screens.is_empty() {
-            return Err(AppError::Custom("No screens available".into()));
+    /// Captures the primary screen and records it against `session_id`.
+    pub async fn capture_screenshot(&self, session_id: i64) -> AppResult<PathBuf> {
+        if !has_screen_capture_permission() {
+            return Err(AppError::ScreenPermissionDenied);
         }
 
-        // Capture primary screen (first screen)
-        let screen = &screens[0];
-        let image = screen.capture()?;
-
         // Create storage directory if it doesn't exist
         fs::create_dir_all(&self.storage_path).await?;
 
+        let (privacy_mode, blur, format) = {
+            let settings = self.settings.read().await;
+            (settings.screenshot_privacy_mode, settings.screenshot_blur, settings.screenshot_format)
+        };
+
         // Generate filename with timestamp
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f");
-        let filename = format!("screenshot_{}.jpg", timestamp);
+        let filename = format!("screenshot_{}.{}", timestamp, format.extension());
         let file_path = self.storage_path.join(&filename);
 
-        // Convert to JPEG with compression
-        // The screenshots crate Image struct has width(), height(), and rgba() methods
-        // rgba() returns &Vec<u8> in RGBA format (already converted from BGRA)
-        let width = image.width();
-        let height = image.height();
-        let rgba_data = image.rgba().clone();
-        
-        // Create RGBA image from buffer
-        let rgba_image = image::RgbaImage::from_raw(
-            width,
-            height,
-            rgba_data,
-        )
-        .ok_or_else(|| AppError::Custom("Failed to create image from buffer".into()))?;
-
-        // Save as JPEG using image crate (extension determines format)
-        let dynamic_image = image::DynamicImage::ImageRgba8(rgba_image);
-        dynamic_image.save(&file_path)
-            .map_err(|e| AppError::Io(format!("Failed to save JPEG: {}", e)))?;
+        // Capturing, resizing and encoding all block, so the whole tick runs off the async workers
+        let target = file_path.clone();
+        tauri::async_runtime::spawn_blocking(move || capture_to(&target, privacy_mode, blur, format))
+            .await
+            .map_err(|e| AppError::Io(format!("Screenshot task failed: {e}")))??;
+
+        // The file is what matters; a missing link only hides it from the session's screenshot list
+        if let Err(e) = db::record_screenshot(&self.pool.get(), session_id, &file_path).await {
+            warn!("Failed to link screenshot {} to session {}: {}", file_path.display(), session_id, e);
+        }
 
         info!("Screenshot saved: {}", file_path.display());
         Ok(file_path)
     }
 
-    pub async fn cleanup_old_screenshots(&self, retention_days: i64) -> AppResult<usize> {
-        let mut deleted_count = 0;
+    pub async fn cleanup_old_screenshots(&self, retention_days: i64) -> AppResult<ScreenshotCleanup> {
+        let mut cleanup = ScreenshotCleanup::default();
         let cutoff_time = Utc::now() - chrono::Duration::days(retention_days);
 
         let mut entries = fs::read_dir(&self.storage_path).await?;
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jpg") {
+            if path.is_file() && is_screenshot_file(&path) {
                 if let Ok(metadata) = entry.metadata().await {
                     if let Ok(modified) = metadata.modified() {
                         let modified_time: chrono::DateTime<Utc> = modified.into();
                         if modified_time < cutoff_time {
-                            if let Err(e) = fs::remove_file(&path).await {
-                                warn!("Failed to delete old screenshot {}: {}", path.display(), e);
-                            } else {
-                                deleted_count += 1;
+                            match self.remove_capture(&path).await {
+                                Ok(bytes) => record_removal(&mut cleanup, bytes),
+                                Err(e) => warn!("Failed to delete old screenshot {}: {}", path.display(), e),
                             }
                         }
                     }
@@ -88,10 +91,60 @@ impl ScreenshotService {
             }
         }
 
-        if deleted_count > 0 {
-            info!("Cleaned up {} old screenshots", deleted_count);
+        if cleanup.deleted_count > 0 {
+            info!("Cleaned up {} old screenshots", cleanup.deleted_count);
         }
-        Ok(deleted_count)
+        Ok(cleanup)
+    }
+
+    /// Deletes the oldest captures until the folder is back under `max_mb`; 0 means no cap.
+    pub async fn enforce_storage_cap(&self, max_mb: i64) -> AppResult<ScreenshotCleanup> {
+        let mut cleanup = ScreenshotCleanup::default();
+        if max_mb <= 0 {
+            return Ok(cleanup);
+        }
+        let mut excess_mb = self.get_storage_size_mb().await? - max_mb as f64;
+        if excess_mb <= 0.0 {
+            return Ok(cleanup);
+        }
+
+        let mut captures = Vec::new();
+        let mut entries = fs::read_dir(&self.storage_path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.is_file() && is_screenshot_file(&path) {
+                captures.push(path);
+            }
+        }
+        // Capture filenames start with their timestamp, so name order is capture order
+        captures.sort();
+        for path in captures {
+            if excess_mb <= 0.0 {
+                break;
+            }
+            match self.remove_capture(&path).await {
+                Ok(bytes) => {
+                    record_removal(&mut cleanup, bytes);
+                    excess_mb -= bytes_to_mb(bytes);
+                }
+                Err(e) => warn!("Failed to delete screenshot {} over the storage cap: {}", path.display(), e),
+            }
+        }
+
+        if cleanup.deleted_count > 0 {
+            info!(
+                "Deleted {} screenshots ({:.1} MB) to stay under {} MB",
+                cleanup.deleted_count, cleanup.freed_mb, max_mb
+            );
+        }
+        Ok(cleanup)
+    }
+
+    /// Deletes a capture along with its thumbnail and session link, returning the bytes freed.
+    async fn remove_capture(&self, path: &Path) -> AppResult<u64> {
+        let freed = remove_capture_files(path).await?;
+        db::forget_screenshot(&self.pool.get(), path).await.ok();
+        Ok(freed)
     }
 
     pub async fn get_storage_size_mb(&self) -> AppResult<f64> {
@@ -104,7 +157,209 @@ impl ScreenshotService {
                 }
             }
         }
-        Ok(total_size as f64 / (1024.0 * 1024.0))
+        Ok(bytes_to_mb(total_size))
+    }
+}
+
+/// The folder captures are written to: the configured path, or `screenshots` beside the database.
+pub fn storage_dir(settings: &AppSettings, db_path: &Path) -> PathBuf {
+    match settings.screenshot_storage_path {
+        Some(ref path) => PathBuf::from(path),
+        None => db_path.parent().unwrap_or(db_path).join("screenshots"),
+    }
+}
+
+fn bytes_to_mb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Deletes every capture and thumbnail in `dir`, for a factory reset. Other files are left alone.
+pub async fn delete_all(dir: &Path) -> AppResult<ScreenshotCleanup> {
+    let mut cleanup = ScreenshotCleanup::default();
+    if !fs::try_exists(dir).await? {
+        return Ok(cleanup);
+    }
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !path.is_file() || !is_screenshot_file(&path) {
+            continue;
+        }
+        record_removal(&mut cleanup, remove_capture_files(&path).await?);
+    }
+    Ok(cleanup)
+}
+
+/// Deletes a capture and its thumbnail, returning the bytes freed. A thumbnail that is missing or
+/// cannot be removed does not fail the capture's removal.
+async fn remove_capture_files(path: &Path) -> AppResult<u64> {
+    let mut freed = fs::metadata(path).await?.len();
+    fs::remove_file(path).await?;
+    let thumbnail = thumbnail_path(path);
+    if let Ok(metadata) = fs::metadata(&thumbnail).await {
+        if fs::remove_file(&thumbnail).await.is_ok() {
+            freed += metadata.len();
+        }
+    }
+    Ok(freed)
+}
+
+fn record_removal(cleanup: &mut ScreenshotCleanup, bytes: u64) {
+    cleanup.deleted_count += 1;
+    cleanup.freed_mb += bytes_to_mb(bytes);
+}
+
+/// Captures the primary screen into `file_path`, applying the privacy settings, and writes its
+/// gallery thumbnail beside it.
+fn capture_to(file_path: &Path, privacy_mode: bool, blur: bool, format: ScreenshotFormat) -> AppResult<()> {
+    let screens = Screen::all()?;
+    if screens.is_empty() {
+        return Err(AppError::Custom("No screens available".into()));
+    }
+
+    // Capture primary screen (first screen)
+    let screen = &screens[0];
+    let image = screen.capture()?;
+
+    // The screenshots crate Image struct has width(), height(), and rgba() methods
+    // rgba() returns &Vec<u8> in RGBA format (already converted from BGRA)
+    let width = image.width();
+    let height = image.height();
+    let rgba_data = image.rgba().clone();
+
+    // Create RGBA image from buffer
+    let rgba_image = image::RgbaImage::from_raw(
+        width,
+        height,
+        rgba_data,
+    )
+    .ok_or_else(|| AppError::Custom("Failed to create image from buffer".into()))?;
+
+    // Save using image crate (extension determines format)
+    let mut dynamic_image = image::DynamicImage::ImageRgba8(rgba_image);
+    if privacy_mode {
+        dynamic_image = dynamic_image
+            .resize_exact(FINGERPRINT_WIDTH, FINGERPRINT_HEIGHT, image::imageops::FilterType::Triangle)
+            .grayscale();
+    } else if blur {
+        // Downscale then upscale: text becomes unreadable, layout stays recognisable
+        let (width, height) = (dynamic_image.width(), dynamic_image.height());
+        dynamic_image = dynamic_image
+            .resize_exact(
+                (width / BLUR_FACTOR).max(1),
+                (height / BLUR_FACTOR).max(1),
+                image::imageops::FilterType::Triangle,
+            )
+            .resize_exact(width, height, image::imageops::FilterType::Triangle);
+    }
+    dynamic_image.save(file_path)
+        .map_err(|e| AppError::Io(format!("Failed to save {}: {}", format, e)))?;
+    // The capture itself is saved, so a missing thumbnail only costs the gallery some speed
+    if let Err(e) = save_thumbnail(&dynamic_image, &thumbnail_path(file_path)) {
+        warn!("Failed to save thumbnail for {}: {}", file_path.display(), e);
+    }
+    Ok(())
+}
+
+/// Grabs the primary screen and throws the image away, to check capture works at all.
+pub fn probe_capture() -> AppResult<()> {
+    if !has_screen_capture_permission() {
+        return Err(AppError::ScreenPermissionDenied);
+    }
+    let screens = Screen::all()?;
+    let screen = screens.first().ok_or_else(|| AppError::Custom("No screens available".into()))?;
+    screen.capture()?;
+    Ok(())
+}
+
+/// Whether `path` is a capture written by `capture_screenshot`, not one of its thumbnails.
+pub fn is_screenshot_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .and_then(ScreenshotFormat::from_extension)
+        .is_some()
+        && !is_thumbnail(path)
+}
+
+fn is_thumbnail(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|stem| stem.ends_with(THUMBNAIL_SUFFIX))
+}
+
+/// Where the gallery thumbnail of the capture at `path` is stored: `<stem>_thumb.jpg` beside it.
+pub fn thumbnail_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}{THUMBNAIL_SUFFIX}.jpg"))
+}
+
+/// Writes a `THUMBNAIL_WIDTH` wide JPEG of `image`. Images already that narrow, like privacy-mode
+/// fingerprints, get no thumbnail.
+fn save_thumbnail(image: &image::DynamicImage, path: &Path) -> AppResult<()> {
+    if image.width() <= THUMBNAIL_WIDTH {
+        return Ok(());
+    }
+    // Unbounded height so the width decides the scale; JPEG has no alpha channel
+    let thumbnail = image
+        .resize(THUMBNAIL_WIDTH, u32::MAX, image::imageops::FilterType::Triangle)
+        .to_rgb8();
+    thumbnail
+        .save(path)
+        .map_err(|e| AppError::Io(format!("Failed to save thumbnail: {e}")))
+}
+
+/// Whether the OS currently allows screen capture. Only macOS gates this.
+pub fn has_screen_capture_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// Shows the macOS screen recording prompt; returns whether access is granted.
+pub fn request_screen_capture_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        unsafe { CGRequestScreenCaptureAccess() }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+/// Picks a delay in `[min, max]` minutes; equal bounds give a fixed interval.
+fn capture_delay_minutes<R: rand::Rng>(min_minutes: i64, max_minutes: i64, rng: &mut R) -> u64 {
+    let min = min_minutes.max(1) as u64;
+    let max = max_minutes.max(1) as u64;
+    if min >= max {
+        min
+    } else {
+        rng.gen_range(min..=max)
+    }
+}
+
+const CAPTURES_PER_CLEANUP: u32 = 5;
+
+/// Counts a capture; returns true (and resets) once every `CAPTURES_PER_CLEANUP` captures.
+fn tick_cleanup_counter(counter: &mut u32) -> bool {
+    *counter += 1;
+    if *counter >= CAPTURES_PER_CLEANUP {
+        *counter = 0;
+        true
+    } else {
+        false
     }
 }
 
@@ -113,11 +368,25 @@ pub async fn screenshot_worker(
     app_handle: tauri::AppHandle,
     timer: crate::timer::TimerService,
 ) {
-    use rand::{Rng, SeedableRng};
+    use rand::SeedableRng;
     use rand::rngs::StdRng;
     use tokio::time::sleep;
 
+    // Set once permission is denied so we stop retrying (and logging) every interval
+    let mut awaiting_permission = false;
+    let mut cleanup_counter: u32 = 0;
+
     loop {
+        if awaiting_permission {
+            if has_screen_capture_permission() {
+                info!("Screen recording permission granted, resuming captures");
+                awaiting_permission = false;
+            } else {
+                sleep(Duration::from_secs(30)).await;
+                continue;
+            }
+        }
+
         // Check settings and timer status
         let should_capture = {
             let settings = service.settings.read().await;
@@ -130,7 +399,11 @@ pub async fn screenshot_worker(
         };
 
         if should_capture {
-            // Random interval between 10-20 minutes (average ~15 minutes)
+            // Re-read each loop so interval changes apply without a restart
+            let (min_minutes, max_minutes) = {
+                let settings = service.settings.read().await;
+                (settings.screenshot_interval_min_minutes, settings.screenshot_interval_max_minutes)
+            };
             // Use a Send-compatible seeded RNG
             let delay_minutes = {
                 use std::time::{SystemTime, UNIX_EPOCH};
@@ -139,7 +412,7 @@ pub async fn screenshot_worker(
                     .unwrap()
                     .as_nanos() as u64;
                 let mut rng = StdRng::seed_from_u64(seed);
-                rng.gen_range(10..=20)
+                capture_delay_minutes(min_minutes, max_minutes, &mut rng)
             };
             sleep(Duration::from_secs(delay_minutes * 60)).await;
 
@@ -152,33 +425,54 @@ pub async fn screenshot_worker(
             if !should_continue {
                 continue;
             }
+            let Some(session_id) = timer.active_session_id().await else {
+                continue;
+            };
 
             // Capture screenshot
-            match service.capture_screenshot().await {
+            match service.capture_screenshot(session_id).await {
                 Ok(path) => {
                     info!("Screenshot captured: {}", path.display());
+                    timer
+                        .record_event(SessionEventKind::ScreenshotCaptured, Some(&path.to_string_lossy()))
+                        .await;
                     // Optionally emit event to frontend
                     app_handle.emit("screenshot:captured", &path.to_string_lossy()).ok();
                 }
+                Err(AppError::ScreenPermissionDenied) => {
+                    warn!("Screen recording permission denied, pausing captures until granted");
+                    app_handle.emit("screenshot:permission-needed", &()).ok();
+                    awaiting_permission = true;
+                    continue;
+                }
                 Err(e) => {
                     error!("Failed to capture screenshot: {}", e);
                 }
             }
 
             // Periodic cleanup (every 5 screenshots, roughly every hour or so)
-            static mut CLEANUP_COUNTER: u32 = 0;
-            unsafe {
-                CLEANUP_COUNTER += 1;
-                if CLEANUP_COUNTER >= 5 {
-                    CLEANUP_COUNTER = 0;
-                    let retention_days = {
-                        let settings = service.settings.read().await;
-                        settings.screenshot_retention_days
-                    };
-                    if let Err(e) = service.cleanup_old_screenshots(retention_days).await {
-                        warn!("Failed to cleanup screenshots: {}", e);
+            if tick_cleanup_counter(&mut cleanup_counter) {
+                let (retention_days, max_storage_mb) = {
+                    let settings = service.settings.read().await;
+                    (settings.screenshot_retention_days, settings.screenshot_max_storage_mb)
+                };
+                let mut cleanup = ScreenshotCleanup::default();
+                // Age first, so the size cap only removes captures still inside the retention window
+                for pass in [
+                    service.cleanup_old_screenshots(retention_days).await,
+                    service.enforce_storage_cap(max_storage_mb).await,
+                ] {
+                    match pass {
+                        Ok(done) => {
+                            cleanup.deleted_count += done.deleted_count;
+                            cleanup.freed_mb += done.freed_mb;
+                        }
+                        Err(e) => warn!("Failed to cleanup screenshots: {}", e),
                     }
                 }
+                if cleanup.deleted_count > 0 {
+                    app_handle.emit("screenshot:cleaned-up", &cleanup).ok();
+                }
             }
         } else {
             // If not enabled, check every 30 seconds
@@ -187,3 +481,90 @@ pub async fn screenshot_worker(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn capture_delay_stays_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let delay = capture_delay_minutes(10, 20, &mut rng);
+            assert!((10..=20).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn cleanup_runs_every_fifth_capture() {
+        let mut counter = 0;
+        let runs: Vec<bool> = (0..10).map(|_| tick_cleanup_counter(&mut counter)).collect();
+        assert_eq!(
+            runs,
+            [false, false, false, false, true, false, false, false, false, true]
+        );
+        assert_eq!(counter, 0);
+    }
+
+    #[test]
+    fn thumbnails_sit_beside_captures_and_are_not_listed() {
+        let capture = Path::new("/shots/screenshot_20240301_080000_000.png");
+        let thumbnail = thumbnail_path(capture);
+        assert_eq!(thumbnail, Path::new("/shots/screenshot_20240301_080000_000_thumb.jpg"));
+        assert!(is_screenshot_file(capture));
+        assert!(!is_screenshot_file(&thumbnail));
+    }
+
+    #[test]
+    fn thumbnail_keeps_aspect_ratio() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-thumb-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shot_thumb.jpg");
+
+        save_thumbnail(&image::DynamicImage::new_rgba8(1920, 1080), &path).unwrap();
+        let thumbnail = image::open(&path).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (240, 135));
+
+        let fingerprint = dir.join("fingerprint_thumb.jpg");
+        save_thumbnail(&image::DynamicImage::new_rgba8(FINGERPRINT_WIDTH, FINGERPRINT_HEIGHT), &fingerprint).unwrap();
+        assert!(!fingerprint.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn storage_cap_deletes_oldest_captures_first() {
+        let dir = std::env::temp_dir().join(format!("masterytrack-cap-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let names = [
+            "screenshot_20240301_080000_000.jpg",
+            "screenshot_20240301_081000_000.jpg",
+            "screenshot_20240301_082000_000.jpg",
+        ];
+        for name in names {
+            std::fs::write(dir.join(name), vec![0u8; 600 * 1024]).unwrap();
+        }
+        std::fs::write(thumbnail_path(&dir.join(names[0])), vec![0u8; 10 * 1024]).unwrap();
+
+        let settings = std::sync::Arc::new(RwLock::new(AppSettings::default()));
+        let pool = SharedPool::new(db::test_pool().await, None);
+        let service = ScreenshotService::new(settings, dir.clone(), pool);
+
+        assert_eq!(service.enforce_storage_cap(0).await.unwrap(), ScreenshotCleanup::default());
+        let cleanup = service.enforce_storage_cap(1).await.unwrap();
+        assert_eq!(cleanup.deleted_count, 2);
+        assert!((cleanup.freed_mb - 1210.0 / 1024.0).abs() < 1e-9);
+        let left: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(left, [names[2]]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn equal_bounds_use_fixed_interval() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(capture_delay_minutes(15, 15, &mut rng), 15);
+        assert_eq!(capture_delay_minutes(0, 0, &mut rng), 1);
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Utc, Weekday};
+use log::{info, warn};
+use tauri::{AppHandle, Emitter};
+
+use crate::db;
+use crate::errors::AppResult;
+use crate::models::{AppSettings, WeeklySummary, WeeklySummaryDelivery};
+use crate::timezone::DayZone;
+use crate::{webhook, AppState};
+
+const SUMMARY_CHECK_SECS: u64 = 60;
+
+/// Sends the weekly recap once its day and time have passed. A week missed while the app was closed
+/// is sent on the next launch; only the first week after enabling it is skipped.
+pub async fn weekly_summary_worker(app: AppHandle, state: AppState) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(SUMMARY_CHECK_SECS)).await;
+
+        let settings = state.settings.read().await.clone();
+        let Ok(Some((day, at))) = settings.weekly_summary_schedule() else {
+            continue;
+        };
+        let zone = DayZone::from_setting(settings.timezone.as_deref());
+        let now = Utc::now();
+        let week_end = latest_week_end(day, at, zone.date_of(now), zone.time_of(now));
+
+        let pool = state.pool.get();
+        let last_sent = match db::load_weekly_summary_sent(&pool).await {
+            Ok(last_sent) => last_sent,
+            Err(e) => {
+                warn!("Unable to check when the weekly summary was last sent: {e}");
+                continue;
+            }
+        };
+        if last_sent.is_some_and(|sent| sent >= week_end) {
+            continue;
+        }
+        // Recorded before delivering so a failing webhook or disk is not retried every minute
+        if let Err(e) = db::save_weekly_summary_sent(&pool, week_end).await {
+            warn!("Unable to record the weekly summary: {e}");
+            continue;
+        }
+        if last_sent.is_none() {
+            continue;
+        }
+
+        match db::weekly_summary(&pool, &settings, week_end).await {
+            Ok(summary) => {
+                deliver(&state, &settings, &summary).await;
+                app.emit("weekly:summary", &summary).ok();
+            }
+            Err(e) => warn!("Unable to build the weekly summary: {e}"),
+        }
+    }
+}
+
+/// The most recent summary day whose time has passed, which is the last day of the week to recap.
+fn latest_week_end(day: Weekday, at: NaiveTime, today: NaiveDate, now: NaiveTime) -> NaiveDate {
+    let days_back = (7 + today.weekday().num_days_from_monday() - day.num_days_from_monday()) % 7;
+    let latest = today - chrono::Duration::days(i64::from(days_back));
+    if days_back == 0 && now < at {
+        latest - chrono::Duration::days(7)
+    } else {
+        latest
+    }
+}
+
+async fn deliver(state: &AppState, settings: &AppSettings, summary: &WeeklySummary) {
+    match settings.weekly_summary_delivery {
+        WeeklySummaryDelivery::Webhook => {
+            if let Some(url) = settings.goal_webhook_url.as_deref() {
+                webhook::send_weekly_summary(url, summary.clone());
+            }
+        }
+        WeeklySummaryDelivery::File => match write_summary_file(state, summary).await {
+            Ok(path) => info!("Weekly summary written to {}", path.display()),
+            Err(e) => warn!("Unable to write the weekly summary: {e}"),
+        },
+    }
+}
+
+async fn write_summary_file(state: &AppState, summary: &WeeklySummary) -> AppResult<std::path::PathBuf> {
+    let dir = crate::exports_dir(state, None).await?.join("weekly");
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!("week-{}.json", summary.to));
+    tokio::fs::write(&path, serde_json::to_vec_pretty(summary)?).await?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn week_ends_on_the_last_scheduled_day_that_has_passed() {
+        let at = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let before = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+
+        assert_eq!(latest_week_end(Weekday::Sun, at, sunday, at), sunday);
+        assert_eq!(latest_week_end(Weekday::Sun, at, sunday, before), sunday - chrono::Duration::days(7));
+        let wednesday = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        assert_eq!(latest_week_end(Weekday::Sun, at, wednesday, before), sunday);
+    }
+}
@@ -1,33 +1,46 @@
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
+use chrono::{NaiveDate, NaiveTime, Utc};
 use tokio::sync::{Mutex, RwLock};
 use sysinfo::System;
 use tauri::{AppHandle, Emitter};
 
-use crate::db;
+use crate::db::{self, SharedPool};
 use crate::errors::{AppError, AppResult};
+use crate::notify;
+use crate::timezone::DayZone;
 use crate::models::{
-    ActiveSession, AppSettings, ReflectionInput, StartTimerResponse, TimerStatus,
+    ActiveSession, AppSettings, IdleBehavior, IdlePrompt, PomodoroPhase, ProductivitySnapshot, ReflectionInput, SessionEventKind, StartTimerResponse, StopOutcome, TimerStatus,
 };
 
+const IDLE_REASON: &str = "idle";
+const PRODUCTIVITY_REASON: &str = "productivity mode";
+const PRODUCTIVITY_POLL_SECS: u64 = 5;
+const POMODORO_REASON: &str = "pomodoro break";
+const QUIT_REASON: &str = "app quit";
+const MAX_LENGTH_REASON: &str = "max session length";
+const CHECKPOINT_INTERVAL_SECS: u64 = 30;
+
 #[derive(Clone)]
 pub struct TimerService {
     inner: Arc<TimerInner>,
 }
 
 struct TimerInner {
-    pool: sqlx::SqlitePool,
+    pool: SharedPool,
     state: Mutex<Option<ActiveSession>>,
     settings: Arc<RwLock<AppSettings>>,
     db_path: PathBuf,
+    goal_notified_on: Mutex<Option<NaiveDate>>,
+    goal_approach_notified_on: Mutex<Option<NaiveDate>>,
+    pomodoro: Mutex<PomodoroState>,
 }
 
 impl TimerService {
     pub fn new(
-        pool: sqlx::SqlitePool,
+        pool: SharedPool,
         settings: Arc<RwLock<AppSettings>>,
         db_path: PathBuf,
     ) -> Self {
@@ -37,31 +50,54 @@ impl TimerService {
                 state: Mutex::new(None),
                 settings,
                 db_path,
+                goal_notified_on: Mutex::new(None),
+                goal_approach_notified_on: Mutex::new(None),
+                pomodoro: Mutex::new(PomodoroState::default()),
             }),
         }
     }
 
     pub async fn start(&self) -> AppResult<StartTimerResponse> {
+        self.start_for_skill(None).await
+    }
+
+    /// Starts against `skill_id`, or the settings skill when none is given.
+    pub async fn start_for_skill(&self, skill_id: Option<i64>) -> AppResult<StartTimerResponse> {
         let mut guard = self.inner.state.lock().await;
         if guard.is_some() {
             return Err(AppError::TimerAlreadyRunning);
         }
 
-        let settings = self.inner.settings.read().await.clone();
-        let skill_id = db::ensure_skill(&self.inner.pool, &settings.skill_name).await?;
+        let skill_id = match skill_id {
+            Some(id) => db::fetch_skill(&self.inner.pool.get(), id)
+                .await?
+                .map(|skill| skill.id)
+                .ok_or_else(|| AppError::Custom(format!("Skill {id} does not exist")))?,
+            None => {
+                let settings = self.inner.settings.read().await.clone();
+                db::ensure_skill(&self.inner.pool.get(), &settings.skill_name).await?
+            }
+        };
         let now = Utc::now();
-        let session_id = db::insert_session(&self.inner.pool, skill_id, now).await?;
+        let session_id = db::insert_session(&self.inner.pool.get(), skill_id, now).await?;
 
         let active = ActiveSession {
             session_id,
             skill_id,
             started_at: now,
             last_resume_at: now,
+            last_resume_instant: Instant::now(),
             accumulated_seconds: 0,
             auto_paused: false,
             last_reason: None,
+            idle_seconds: 0,
+            idle_since: None,
         };
         *guard = Some(active);
+        drop(guard);
+        *self.inner.pomodoro.lock().await = PomodoroState::default();
+
+        self.log_event(session_id, SessionEventKind::Started, None).await;
 
         Ok(StartTimerResponse {
             session_id,
@@ -69,44 +105,214 @@ impl TimerService {
         })
     }
 
-    pub async fn stop(&self, reflections: ReflectionInput) -> AppResult<f64> {
-        self.stop_internal(reflections, None).await
+    pub async fn stop(&self, reflections: ReflectionInput) -> AppResult<StopOutcome> {
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.take().ok_or(AppError::TimerNotRunning)?;
+        drop(guard);
+        self.finish(active, &reflections, None).await
     }
 
-    pub async fn force_pause(&self, reason: &str) -> AppResult<Option<f64>> {
-        let reflections = ReflectionInput {
-            notes: Some(format!("Auto pause: {reason}")),
-            what_practiced: None,
-            what_learned: None,
-            next_focus: None,
+    /// Ends the session only while it is still paused for `reason`, recording that as why it stopped.
+    pub async fn stop_if_paused_for(&self, reason: &str, reflections: ReflectionInput) -> AppResult<Option<StopOutcome>> {
+        let mut guard = self.inner.state.lock().await;
+        match guard.as_ref() {
+            Some(active) if active.auto_paused && active.last_reason.as_deref() == Some(reason) => {}
+            _ => return Ok(None),
+        }
+        let Some(active) = guard.take() else {
+            return Ok(None);
         };
+        drop(guard);
+        self.finish(active, &reflections, Some(reason)).await.map(Some)
+    }
 
-        match self.stop_internal(reflections, Some(reason.to_string())).await {
-            Ok(v) => Ok(Some(v)),
-            Err(AppError::TimerNotRunning) => Ok(None),
+    async fn finish(&self, active: ActiveSession, reflections: &ReflectionInput, reason: Option<&str>) -> AppResult<StopOutcome> {
+        let total_seconds = active.elapsed_seconds();
+        self.finish_after(active, total_seconds, Utc::now(), reflections, reason).await
+    }
+
+    /// Saves the session as `total_seconds` long and ending at `ended_at`, or discards it when that
+    /// is under the minimum length.
+    async fn finish_after(
+        &self,
+        active: ActiveSession,
+        total_seconds: i64,
+        ended_at: chrono::DateTime<Utc>,
+        reflections: &ReflectionInput,
+        reason: Option<&str>,
+    ) -> AppResult<StopOutcome> {
+        let minutes = (total_seconds as f64 / 60.0).max(0.0);
+        let minimum = self.inner.settings.read().await.minimum_session_minutes;
+
+        let outcome = StopOutcome::decide(minutes, minimum);
+        match outcome {
+            StopOutcome::Saved { .. } => {
+                db::finalize_session(&self.inner.pool.get(), active.session_id, ended_at, minutes, reflections).await?;
+                self.log_event(active.session_id, SessionEventKind::Stopped, reason).await;
+            }
+            StopOutcome::Discarded { .. } => {
+                // The row goes for good, taking its event timeline with it, so the app log keeps the record
+                log::info!("Discarding {minutes:.1}-minute session below the {minimum}-minute minimum");
+                match db::discard_session(&self.inner.pool.get(), active.session_id).await {
+                    Err(AppError::NotFound(_)) => log::warn!("Session {} was already gone", active.session_id),
+                    other => other?,
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    pub async fn pause(&self) -> AppResult<TimerStatus> {
+        self.pause_internal(None).await.map(|(status, _)| status)
+    }
+
+    pub async fn resume(&self) -> AppResult<TimerStatus> {
+        self.resume_internal(None).await
+    }
+
+    /// Resumes the session, noting `reason` on the timeline when a monitor rather than the user did it.
+    async fn resume_internal(&self, reason: Option<&str>) -> AppResult<TimerStatus> {
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.as_mut().ok_or(AppError::TimerNotRunning)?;
+        let resumed = active.resume();
+        let (session_id, status) = (active.session_id, active.as_status());
+        drop(guard);
+
+        if resumed {
+            self.log_event(session_id, SessionEventKind::Resumed, reason).await;
+        }
+        Ok(status)
+    }
+
+    /// Pauses the open session for a monitor; returns true only when this call paused it.
+    pub async fn force_pause(&self, reason: &str) -> AppResult<bool> {
+        match self.pause_internal(Some(reason.to_string())).await {
+            Ok((_, paused)) => Ok(paused),
+            Err(AppError::TimerNotRunning) => Ok(false),
             Err(err) => Err(err),
         }
     }
 
-    async fn stop_internal(
-        &self,
-        reflections: ReflectionInput,
-        reason: Option<String>,
-    ) -> AppResult<f64> {
+    /// Whether the session is paused waiting on an answer to the idle prompt.
+    pub async fn paused_for_idle(&self) -> bool {
+        let guard = self.inner.state.lock().await;
+        guard
+            .as_ref()
+            .is_some_and(|active| active.auto_paused && active.last_reason.as_deref() == Some(IDLE_REASON))
+    }
+
+    /// Continues a session the idle monitor paused; false when it is not waiting on the idle prompt.
+    pub async fn resume_from_idle(&self) -> AppResult<bool> {
+        self.resume_if_paused_for(IDLE_REASON).await
+    }
+
+    /// Ends a session paused at the idle prompt, keeping the practice before the user went idle.
+    pub async fn stop_from_idle(&self) -> AppResult<Option<StopOutcome>> {
+        self.stop_if_paused_for(IDLE_REASON, ReflectionInput::default()).await
+    }
+
+    /// Ends the session at `max_session_minutes` once it has run that long, so a forgotten timer
+    /// does not count the whole night. The session is saved as exactly the cap, ending when it was hit.
+    pub async fn stop_if_over_max(&self) -> AppResult<Option<StopOutcome>> {
+        self.stop_if_over_max_at(Utc::now()).await
+    }
+
+    async fn stop_if_over_max_at(&self, now: chrono::DateTime<Utc>) -> AppResult<Option<StopOutcome>> {
+        let max_minutes = self.inner.settings.read().await.max_session_minutes;
+        if max_minutes <= 0 {
+            return Ok(None);
+        }
+        let cap_seconds = max_minutes * 60;
         let mut guard = self.inner.state.lock().await;
-        let active = guard.take().ok_or(AppError::TimerNotRunning)?;
+        let Some(active) = guard.take() else {
+            return Ok(None);
+        };
+        let elapsed = active.elapsed_seconds_at(now);
+        if elapsed < cap_seconds {
+            *guard = Some(active);
+            return Ok(None);
+        }
         drop(guard);
 
-        let total_seconds = active.elapsed_seconds();
-        let minutes = (total_seconds as f64 / 60.0).max(0.0);
+        let ended_at = now - chrono::Duration::seconds(elapsed - cap_seconds);
+        let reflections = ReflectionInput {
+            notes: Some("Auto-stopped at max length".into()),
+            ..ReflectionInput::default()
+        };
+        self.finish_after(active, cap_seconds, ended_at, &reflections, Some(MAX_LENGTH_REASON))
+            .await
+            .map(Some)
+    }
 
-        db::finalize_session(&self.inner.pool, active.session_id, minutes, &reflections).await?;
+    /// Ends whatever session is running because the app is quitting, so it is not left open until the
+    /// next launch offers to recover it.
+    pub async fn stop_on_exit(&self) -> AppResult<Option<StopOutcome>> {
+        let Some(active) = self.inner.state.lock().await.take() else {
+            return Ok(None);
+        };
+        let reflections = ReflectionInput {
+            notes: Some("Stopped when MasteryTrack quit".into()),
+            ..ReflectionInput::default()
+        };
+        self.finish(active, &reflections, Some(QUIT_REASON)).await.map(Some)
+    }
+
+    /// Starts leaving idle time out of the running session, counted from the last input.
+    pub async fn begin_idle(&self, last_input: chrono::DateTime<Utc>) -> bool {
+        self.mark_idle(last_input, SessionEventKind::IdleStarted, ActiveSession::begin_idle).await
+    }
+
+    /// Ends the open idle span at the input that ended it; false when none was open.
+    pub async fn end_idle(&self, last_input: chrono::DateTime<Utc>) -> bool {
+        self.mark_idle(last_input, SessionEventKind::IdleEnded, ActiveSession::end_idle).await
+    }
 
-        if let Some(reason) = reason {
-            log::info!("Timer auto-paused due to {reason}");
+    async fn mark_idle(
+        &self,
+        last_input: chrono::DateTime<Utc>,
+        kind: SessionEventKind,
+        mark: fn(&mut ActiveSession, chrono::DateTime<Utc>) -> bool,
+    ) -> bool {
+        let mut guard = self.inner.state.lock().await;
+        let Some(active) = guard.as_mut() else {
+            return false;
+        };
+        if !mark(active, last_input) {
+            return false;
         }
+        let session_id = active.session_id;
+        drop(guard);
 
-        Ok(minutes)
+        self.log_event(session_id, kind, Some(&last_input.to_rfc3339())).await;
+        true
+    }
+
+    /// Resumes a session only if it was paused for `reason`, leaving manual pauses alone.
+    pub async fn resume_if_paused_for(&self, reason: &str) -> AppResult<bool> {
+        {
+            let guard = self.inner.state.lock().await;
+            match guard.as_ref() {
+                Some(active) if active.auto_paused && active.last_reason.as_deref() == Some(reason) => {}
+                _ => return Ok(false),
+            }
+        }
+        self.resume_internal(Some(reason)).await.map(|status| status.running)
+    }
+
+    async fn pause_internal(&self, reason: Option<String>) -> AppResult<(TimerStatus, bool)> {
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.as_mut().ok_or(AppError::TimerNotRunning)?;
+        let paused = active.pause(reason.clone());
+        let (session_id, status) = (active.session_id, active.as_status());
+        drop(guard);
+
+        if paused {
+            if let Some(reason) = reason.as_deref() {
+                log::info!("Timer auto-paused due to {reason}");
+            }
+            self.log_event(session_id, SessionEventKind::Paused, reason.as_deref()).await;
+        }
+        Ok((status, paused))
     }
 
     pub async fn status(&self) -> TimerStatus {
@@ -123,6 +329,145 @@ impl TimerService {
         }
     }
 
+    /// Drops wall-clock time spent asleep from the active session; returns the seconds skipped.
+    pub async fn reconcile(&self) -> i64 {
+        let mut guard = self.inner.state.lock().await;
+        let (session_id, skipped) = match guard.as_mut() {
+            Some(active) if !active.auto_paused => (active.session_id, active.reconcile()),
+            _ => return 0,
+        };
+        drop(guard);
+
+        if skipped > 0 {
+            log::info!("Timer skipped {skipped}s of sleep on resume");
+            let detail = format!("{skipped}s");
+            self.log_event(session_id, SessionEventKind::SleepSkipped, Some(&detail)).await;
+        }
+        skipped
+    }
+
+    /// Persists the active session's elapsed time so it survives a crash.
+    pub async fn checkpoint(&self) -> AppResult<()> {
+        let snapshot = {
+            let guard = self.inner.state.lock().await;
+            guard.as_ref().map(|a| (a.session_id, a.elapsed_seconds()))
+        };
+        if let Some((session_id, seconds)) = snapshot {
+            db::checkpoint_session(&self.inner.pool.get(), session_id, seconds as f64 / 60.0).await?;
+        }
+        Ok(())
+    }
+
+    /// Moves between Pomodoro work and break intervals; returns the phase just entered.
+    pub async fn pomodoro_tick(&self) -> Option<PomodoroPhase> {
+        let (enabled, work_secs, break_secs) = {
+            let settings = self.inner.settings.read().await;
+            (
+                settings.pomodoro_enabled,
+                settings.pomodoro_work_minutes * 60,
+                settings.pomodoro_break_minutes * 60,
+            )
+        };
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.as_mut()?;
+        let mut pomodoro = self.inner.pomodoro.lock().await;
+        let phase = if enabled {
+            pomodoro.advance(active, work_secs, break_secs, Instant::now())
+        } else {
+            // Turning the mode off mid-break must not strand the session paused
+            pomodoro.end_break(active).then_some(PomodoroPhase::Work)
+        }?;
+        let session_id = active.session_id;
+        drop(pomodoro);
+        drop(guard);
+
+        let kind = match phase {
+            PomodoroPhase::Break => SessionEventKind::Paused,
+            PomodoroPhase::Work => SessionEventKind::Resumed,
+        };
+        self.log_event(session_id, kind, Some(POMODORO_REASON)).await;
+        Some(phase)
+    }
+
+    /// Ends the current Pomodoro break early; false when no break is running.
+    pub async fn skip_break(&self) -> AppResult<bool> {
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.as_mut().ok_or(AppError::TimerNotRunning)?;
+        let ended = self.inner.pomodoro.lock().await.end_break(active);
+        let session_id = active.session_id;
+        drop(guard);
+
+        if ended {
+            self.log_event(session_id, SessionEventKind::Resumed, Some(POMODORO_REASON)).await;
+        }
+        Ok(ended)
+    }
+
+    /// True the first time it is called on a given day, so goal events fire once per day.
+    pub async fn claim_goal_notification(&self, today: NaiveDate) -> bool {
+        let mut guard = self.inner.goal_notified_on.lock().await;
+        claim_day(&mut guard, today)
+    }
+
+    /// Whether the goal reminder may still fire today; unlike the claim this does not use it up.
+    pub async fn goal_approach_pending(&self, today: NaiveDate) -> bool {
+        *self.inner.goal_approach_notified_on.lock().await != Some(today)
+    }
+
+    /// True the first time it is called on a given day, so the goal reminder fires once per day.
+    pub async fn claim_goal_approach_notification(&self, today: NaiveDate) -> bool {
+        let mut guard = self.inner.goal_approach_notified_on.lock().await;
+        claim_day(&mut guard, today)
+    }
+
+    /// Adds a row to the active session's timeline, if a session is running.
+    pub async fn record_event(&self, kind: SessionEventKind, detail: Option<&str>) {
+        if let Some(session_id) = self.active_session_id().await {
+            self.log_event(session_id, kind, detail).await;
+        }
+    }
+
+    async fn log_event(&self, session_id: i64, kind: SessionEventKind, detail: Option<&str>) {
+        // Timeline rows are best-effort; never fail a timer transition over them
+        if let Err(err) = db::record_session_event(&self.inner.pool.get(), session_id, kind, detail).await {
+            log::warn!("Failed to record {kind} event for session {session_id}: {err}");
+        }
+    }
+
+    pub async fn active_session_id(&self) -> Option<i64> {
+        let guard = self.inner.state.lock().await;
+        guard.as_ref().map(|a| a.session_id)
+    }
+
+    /// A copy of the running session, if there is one.
+    pub async fn active_session(&self) -> Option<ActiveSession> {
+        self.inner.state.lock().await.clone()
+    }
+
+    /// Replaces the running session's notes right away, before it is stopped.
+    pub async fn annotate(&self, notes: Option<&str>) -> AppResult<()> {
+        // Held across the write so the session cannot be stopped and finalized in between
+        let guard = self.inner.state.lock().await;
+        let active = guard.as_ref().ok_or(AppError::TimerNotRunning)?;
+        db::set_session_notes(&self.inner.pool.get(), active.session_id, notes).await
+    }
+
+    /// Relabels the running session with `skill_name`, creating the skill if needed.
+    pub async fn change_skill(&self, skill_name: &str) -> AppResult<ActiveSession> {
+        let mut guard = self.inner.state.lock().await;
+        let active = guard.as_mut().ok_or(AppError::TimerNotRunning)?;
+        let pool = self.inner.pool.get();
+        let skill_id = db::ensure_skill(&pool, skill_name).await?;
+        db::set_session_skill(&pool, active.session_id, skill_id).await?;
+        active.skill_id = skill_id;
+        Ok(active.clone())
+    }
+
+    pub async fn active_skill_id(&self) -> Option<i64> {
+        let guard = self.inner.state.lock().await;
+        guard.as_ref().map(|a| a.skill_id)
+    }
+
     pub async fn active_seconds(&self) -> i64 {
         let guard = self.inner.state.lock().await;
         guard.as_ref().map(|a| a.elapsed_seconds()).unwrap_or(0)
@@ -146,20 +491,91 @@ pub async fn idle_monitor(
     timer: TimerService,
     app: tauri::AppHandle,
 ) {
+    // When the idle prompt went out; cleared once the user answers it or it times out
+    let mut prompted_at: Option<Instant> = None;
+    // Idle time lookups can fail for a while (e.g. a locked session); warn once instead of every poll
+    let mut idle_lookup_failing = false;
     loop {
         {
             let settings = timer.settings().await;
             if settings.idle_timeout_minutes > 0 {
-                if let Ok(idle) = user_idle_time::get_idle_time() {
-                    if idle.as_secs() as i64 >= settings.idle_timeout_minutes * 60 {
-                        if let Ok(Some(_)) = timer.force_pause("idle").await {
-                            let _ = app.emit("timer:auto-paused", &reason_payload("Idle timeout"));
+                let idle = match user_idle_time::get_idle_time() {
+                    Ok(idle) => {
+                        if idle_lookup_failing {
+                            log::info!("Idle time is readable again");
+                            idle_lookup_failing = false;
+                        }
+                        Some(idle)
+                    }
+                    Err(err) => {
+                        if !idle_lookup_failing {
+                            log::warn!("Unable to read idle time, retrying every poll: {err}");
+                            idle_lookup_failing = true;
+                        }
+                        None
+                    }
+                };
+                if let Some(idle) = idle {
+                    let idle_secs = idle.as_secs() as i64;
+                    let last_input = Utc::now() - chrono::Duration::seconds(idle_secs);
+                    if idle_secs >= settings.idle_timeout_minutes * 60 {
+                        match settings.idle_behavior {
+                            IdleBehavior::Pause => {
+                                match timer.force_pause(IDLE_REASON).await {
+                                    Ok(true) => {
+                                        let _ = app.emit("timer:auto-paused", &reason_payload("Idle timeout"));
+                                        let prompt = IdlePrompt { timeout_minutes: settings.idle_prompt_timeout_minutes };
+                                        let _ = app.emit("timer:idle-prompt", &prompt);
+                                        notify::desktop(&app, "Still practicing?", "The timer is paused while you're away.");
+                                        prompted_at = Some(Instant::now());
+                                    }
+                                    Ok(false) => {}
+                                    Err(err) => log::error!("Failed to pause the idle session, retrying next poll: {err}"),
+                                }
+                            }
+                            IdleBehavior::Subtract => {
+                                if timer.begin_idle(last_input).await {
+                                    log::info!("Idle since {last_input}; leaving it out of the session");
+                                }
+                            }
+                            IdleBehavior::Ignore => {}
+                        }
+                    } else {
+                        timer.end_idle(last_input).await;
+                        // With Pause the prompt decides; this covers a session paused before switching away from it
+                        if settings.idle_behavior != IdleBehavior::Pause {
+                            match timer.resume_if_paused_for(IDLE_REASON).await {
+                                Ok(true) => {
+                                    let _ = app.emit("timer:resumed", &reason_payload("Activity detected"));
+                                }
+                                Ok(false) => {}
+                                Err(err) => log::error!("Failed to resume after idle, retrying next poll: {err}"),
+                            }
                         }
                     }
                 }
             }
+
+            if let Some(at) = prompted_at {
+                let timeout = Duration::from_secs(settings.idle_prompt_timeout_minutes.max(1) as u64 * 60);
+                if !timer.paused_for_idle().await {
+                    prompted_at = None;
+                } else if at.elapsed() >= timeout {
+                    prompted_at = None;
+                    match timer.stop_from_idle().await {
+                        Ok(Some(outcome @ StopOutcome::Discarded { .. })) => {
+                            let _ = app.emit("timer:discarded", &outcome);
+                        }
+                        Ok(Some(StopOutcome::Saved { .. })) => {
+                            let _ = app.emit("timer:stopped", &reason_payload("No answer to the idle prompt"));
+                        }
+                        Ok(None) => {}
+                        Err(err) => log::error!("Failed to stop the idle session: {err}"),
+                    }
+                }
+            }
         }
-        tokio::time::sleep(Duration::from_secs(30)).await;
+        tokio::time::sleep(timer.settings().await.idle_poll_interval()).await;
     }
 }
 
@@ -167,40 +583,254 @@ pub async fn productivity_monitor(
     timer: TimerService,
     app: tauri::AppHandle,
 ) {
+    let mut was_enforcing = None;
+    let mut grace = FocusGrace::default();
     loop {
         let settings = timer.settings().await;
-        if settings.productivity_mode_enabled {
-            let mut offending = Vec::new();
-            let mut allowed_match = settings.allowed_apps.is_empty();
-            let mut blocked_hit = false;
-
-            let mut sys = System::new();
-            sys.refresh_processes();
-
-            for process in sys.processes().values() {
-                let name = process.name().to_ascii_lowercase();
-                if !allowed_match && settings.allowed_apps.iter().any(|a| name.contains(&a.to_ascii_lowercase())) {
-                    allowed_match = true;
+        let zone = DayZone::from_setting(settings.timezone.as_deref());
+        let window = settings.productivity_window().unwrap_or_else(|err| {
+            log::warn!("Ignoring productivity schedule: {err}");
+            None
+        });
+        let enforcing =
+            settings.productivity_mode_enabled && within_window(window, zone.time_of(Utc::now()));
+        if was_enforcing != Some(enforcing) {
+            let _ = app.emit("productivity:state", &serde_json::json!({ "enforcing": enforcing }));
+            was_enforcing = Some(enforcing);
+        }
+
+        if enforcing {
+            let scan_settings = settings.clone();
+            // Listing windows and processes blocks, so keep it off the async workers
+            let snapshot = match tauri::async_runtime::spawn_blocking(move || scan_productivity(&scan_settings)).await {
+                Ok(snapshot) => snapshot,
+                Err(err) => {
+                    log::warn!("Productivity scan failed: {err}");
+                    tokio::time::sleep(Duration::from_secs(PRODUCTIVITY_POLL_SECS)).await;
+                    continue;
                 }
-                if settings.blocked_apps.iter().any(|b| name.contains(&b.to_ascii_lowercase())) {
-                    blocked_hit = true;
-                    offending.push(process.name().to_string());
+            };
+            let allowed_match = snapshot.allowed_active;
+            let blocked_hit = snapshot.blocked_triggered;
+
+            let status = timer.status().await;
+            if !status.running || status.auto_paused {
+                // A sighting left over from an earlier session must not skip the next one's warning
+                grace.clear();
+            }
+            if (!allowed_match || blocked_hit) && status.running && !status.auto_paused {
+                let reason = if blocked_hit {
+                    format!("Blocked apps: {}", snapshot.offending_processes.join(", "))
+                } else {
+                    "No focus app active".into()
+                };
+                let grace_period = Duration::from_secs(settings.productivity_grace_seconds.max(0) as u64);
+                match grace.observe_violation(Instant::now(), grace_period) {
+                    GraceStep::Warn => {
+                        let _ = app.emit(
+                            "productivity:warning",
+                            &serde_json::json!({
+                                "reason": reason,
+                                "grace_seconds": settings.productivity_grace_seconds,
+                            }),
+                        );
+                    }
+                    GraceStep::Wait => {}
+                    GraceStep::Pause => {
+                        grace.clear();
+                        if let Ok(true) = timer.force_pause(PRODUCTIVITY_REASON).await {
+                            let _ = app.emit("timer:auto-paused", &reason_payload(&reason));
+                        }
+                    }
                 }
+            } else if allowed_match && !blocked_hit {
+                grace.clear();
+                if let Ok(true) = timer.resume_if_paused_for(PRODUCTIVITY_REASON).await {
+                    let _ = app.emit("timer:resumed", &reason_payload("Focus restored"));
+                }
+            }
+        } else if settings.productivity_mode_enabled {
+            grace.clear();
+            // The schedule ended while we were holding the timer
+            if let Ok(true) = timer.resume_if_paused_for(PRODUCTIVITY_REASON).await {
+                let _ = app.emit("timer:resumed", &reason_payload("Outside productivity hours"));
             }
+        } else {
+            grace.clear();
+        }
 
-            if (!allowed_match || blocked_hit) && timer.status().await.running {
-                if let Ok(Some(_)) = timer.force_pause("productivity mode").await {
-                    let reason = if blocked_hit {
-                        format!("Blocked apps: {}", offending.join(", "))
-                    } else {
-                        "No focus app active".into()
-                    };
-                    let _ = app.emit("timer:auto-paused", &reason_payload(&reason));
+        tokio::time::sleep(Duration::from_secs(PRODUCTIVITY_POLL_SECS)).await;
+    }
+}
+
+/// Where the active session is in its Pomodoro cycle.
+#[derive(Default)]
+struct PomodoroState {
+    /// Elapsed practice seconds when the current work interval began.
+    work_started_at_seconds: i64,
+    break_started: Option<Instant>,
+}
+
+impl PomodoroState {
+    /// Breaks are pauses, so break time never reaches `accumulated_seconds`.
+    fn advance(
+        &mut self,
+        active: &mut ActiveSession,
+        work_secs: i64,
+        break_secs: i64,
+        now: Instant,
+    ) -> Option<PomodoroPhase> {
+        match self.break_started {
+            Some(started) => {
+                let on_break = active.auto_paused && active.last_reason.as_deref() == Some(POMODORO_REASON);
+                let over = now.saturating_duration_since(started).as_secs() as i64 >= break_secs;
+                if !on_break || over {
+                    self.end_break(active);
+                    return Some(PomodoroPhase::Work);
                 }
+                None
+            }
+            None if !active.auto_paused && active.elapsed_seconds() - self.work_started_at_seconds >= work_secs => {
+                active.pause(Some(POMODORO_REASON.into()));
+                self.break_started = Some(now);
+                Some(PomodoroPhase::Break)
+            }
+            None => None,
+        }
+    }
+
+    fn end_break(&mut self, active: &mut ActiveSession) -> bool {
+        if self.break_started.take().is_none() {
+            return false;
+        }
+        if active.auto_paused && active.last_reason.as_deref() == Some(POMODORO_REASON) {
+            active.resume();
+        }
+        self.work_started_at_seconds = active.elapsed_seconds();
+        true
+    }
+}
+
+/// Tracks how long focus has been lost so a brief alt-tab only warns.
+#[derive(Default)]
+struct FocusGrace {
+    since: Option<Instant>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum GraceStep {
+    /// First sighting of the violation.
+    Warn,
+    Wait,
+    Pause,
+}
+
+impl FocusGrace {
+    fn observe_violation(&mut self, now: Instant, grace: Duration) -> GraceStep {
+        match self.since {
+            _ if grace.is_zero() => GraceStep::Pause,
+            None => {
+                self.since = Some(now);
+                GraceStep::Warn
             }
+            Some(since) if now.duration_since(since) >= grace => GraceStep::Pause,
+            Some(_) => GraceStep::Wait,
         }
+    }
 
-        tokio::time::sleep(Duration::from_secs(20)).await;
+    fn clear(&mut self) {
+        self.since = None;
+    }
+}
+
+/// Whether `now` falls in `[from, to)`; windows that end before they start wrap past midnight.
+fn within_window(window: Option<(NaiveTime, NaiveTime)>, now: NaiveTime) -> bool {
+    match window {
+        None => true,
+        Some((from, to)) if from == to => true,
+        Some((from, to)) if from < to => from <= now && now < to,
+        Some((from, to)) => now >= from || now < to,
+    }
+}
+
+/// Checks running processes and the focused window against the allow and block rules.
+pub fn scan_productivity(settings: &AppSettings) -> ProductivitySnapshot {
+    if settings.allowed_apps.is_empty() && settings.blocked_apps.is_empty() {
+        return ProductivitySnapshot::idle();
+    }
+
+    let mut sys = System::new();
+    sys.refresh_processes();
+    let processes: Vec<String> = sys
+        .processes()
+        .values()
+        .map(|p| p.name().to_ascii_lowercase())
+        .collect();
+    let focused = foreground_window();
+    let title = focused.title.as_deref();
+
+    let offending: Vec<String> = settings
+        .blocked_apps
+        .iter()
+        .filter(|rule| rule.matches(&processes, title))
+        .map(|rule| rule.pattern.clone())
+        .collect();
+    ProductivitySnapshot {
+        allowed_active: allowed_active(settings, &processes, &focused),
+        blocked_triggered: !offending.is_empty(),
+        offending_processes: offending,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Any allowed app running counts, unless `active_requires_focus_app` asks for one in the foreground.
+fn allowed_active(settings: &AppSettings, processes: &[String], focused: &ForegroundWindow) -> bool {
+    if settings.allowed_apps.is_empty() {
+        return true;
+    }
+    let focused_process: Vec<String> = focused.process.iter().cloned().collect();
+    let candidates = if settings.active_requires_focus_app { &focused_process[..] } else { processes };
+    settings
+        .allowed_apps
+        .iter()
+        .any(|rule| rule.matches(candidates, focused.title.as_deref()))
+}
+
+/// The focused window, with its process name lowercased the way `sysinfo` reports process names.
+/// Both parts are `None` where the platform won't tell us (e.g. Wayland).
+#[derive(Default)]
+struct ForegroundWindow {
+    process: Option<String>,
+    title: Option<String>,
+}
+
+fn foreground_window() -> ForegroundWindow {
+    let Ok(window) = active_win_pos_rs::get_active_window() else {
+        return ForegroundWindow::default();
+    };
+    ForegroundWindow {
+        process: window
+            .process_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase()),
+        title: Some(window.title).filter(|title| !title.is_empty()),
+    }
+}
+
+fn claim_day(last: &mut Option<NaiveDate>, today: NaiveDate) -> bool {
+    if *last == Some(today) {
+        return false;
+    }
+    *last = Some(today);
+    true
+}
+
+pub async fn checkpoint_worker(timer: TimerService) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECKPOINT_INTERVAL_SECS)).await;
+        if let Err(err) = timer.checkpoint().await {
+            log::warn!("Failed to checkpoint active session: {err}");
+        }
     }
 }
 
@@ -211,20 +841,108 @@ fn reason_payload(reason: &str) -> serde_json::Value {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Duration;
+    use chrono::{DateTime, Duration};
+    use sqlx::SqlitePool;
+    use crate::models::{AppMatchKind, AppRule};
 
-    #[test]
-    fn active_session_elapsed_includes_accumulated_time() {
-        let now = Utc::now();
-        let session = ActiveSession {
+    /// Session 1 on skill 1, running from the given clocks with no idle time.
+    fn active_session(
+        started_at: DateTime<Utc>,
+        last_resume_at: DateTime<Utc>,
+        last_resume_instant: Instant,
+        accumulated_seconds: i64,
+    ) -> ActiveSession {
+        ActiveSession {
             session_id: 1,
             skill_id: 1,
-            started_at: now - Duration::minutes(10),
-            last_resume_at: now - Duration::seconds(120),
-            accumulated_seconds: 240,
+            started_at,
+            last_resume_at,
+            last_resume_instant,
+            accumulated_seconds,
             auto_paused: false,
             last_reason: None,
-        };
+            idle_seconds: 0,
+            idle_since: None,
+        }
+    }
+
+    fn test_service(pool: &SqlitePool, settings: AppSettings) -> TimerService {
+        TimerService::new(
+            SharedPool::new(pool.clone(), None),
+            Arc::new(RwLock::new(settings)),
+            PathBuf::new(),
+        )
+    }
+
+    #[test]
+    fn focus_loss_warns_before_pausing() {
+        let start = Instant::now();
+        let grace_period = std::time::Duration::from_secs(15);
+        let mut grace = FocusGrace::default();
+
+        assert_eq!(grace.observe_violation(start, grace_period), GraceStep::Warn);
+        let later = start + std::time::Duration::from_secs(10);
+        assert_eq!(grace.observe_violation(later, grace_period), GraceStep::Wait);
+        let expired = start + std::time::Duration::from_secs(15);
+        assert_eq!(grace.observe_violation(expired, grace_period), GraceStep::Pause);
+
+        grace.clear();
+        assert_eq!(grace.observe_violation(expired, grace_period), GraceStep::Warn);
+        assert_eq!(grace.observe_violation(expired, std::time::Duration::ZERO), GraceStep::Pause);
+    }
+
+    #[test]
+    fn productivity_window_wraps_past_midnight() {
+        let t = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let office = Some((t(9, 0), t(17, 0)));
+        assert!(within_window(office, t(9, 0)));
+        assert!(!within_window(office, t(17, 0)));
+        assert!(!within_window(office, t(22, 30)));
+
+        let night = Some((t(22, 0), t(6, 0)));
+        assert!(within_window(night, t(23, 15)));
+        assert!(within_window(night, t(2, 0)));
+        assert!(!within_window(night, t(12, 0)));
+
+        assert!(within_window(None, t(3, 0)));
+    }
+
+    #[test]
+    fn goal_notification_claimed_once_per_day() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let mut last = None;
+        assert!(claim_day(&mut last, monday));
+        assert!(!claim_day(&mut last, monday));
+        assert!(claim_day(&mut last, monday.succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn pomodoro_break_is_not_counted_as_practice() {
+        let now = Utc::now();
+        // 26 minutes already banked, so no clock has to reach back before the test started
+        let mut session = active_session(now - Duration::minutes(26), now, Instant::now(), 26 * 60);
+        let mut pomodoro = PomodoroState::default();
+        let tick = Instant::now();
+
+        assert_eq!(pomodoro.advance(&mut session, 25 * 60, 5 * 60, tick), Some(PomodoroPhase::Break));
+        let banked = session.elapsed_seconds();
+        assert!(session.auto_paused);
+
+        let mid_break = tick + std::time::Duration::from_secs(60);
+        assert_eq!(pomodoro.advance(&mut session, 25 * 60, 5 * 60, mid_break), None);
+        assert_eq!(session.elapsed_seconds(), banked);
+
+        let after_break = tick + std::time::Duration::from_secs(5 * 60);
+        assert_eq!(pomodoro.advance(&mut session, 25 * 60, 5 * 60, after_break), Some(PomodoroPhase::Work));
+        assert!(!session.auto_paused);
+        assert_eq!(pomodoro.work_started_at_seconds, banked);
+    }
+
+    #[test]
+    fn active_session_elapsed_includes_accumulated_time() {
+        let now = Utc::now();
+        // Elapsed time is wall-clock based; the monotonic clock only matters for reconciling
+        let session = active_session(now - Duration::minutes(10), now - Duration::seconds(120), Instant::now(), 240);
 
         let elapsed = session.elapsed_seconds();
         assert!(
@@ -235,17 +953,346 @@ mod tests {
 
     #[test]
     fn active_session_status_reflects_pause_state() {
-        let session = ActiveSession {
-            session_id: 1,
-            skill_id: 1,
-            started_at: Utc::now(),
-            last_resume_at: Utc::now(),
-            accumulated_seconds: 0,
-            auto_paused: true,
-            last_reason: Some("idle".into()),
-        };
+        let mut session = active_session(Utc::now(), Utc::now(), Instant::now(), 0);
+        session.auto_paused = true;
+        session.last_reason = Some("idle".into());
         let status = session.as_status();
         assert!(!status.running, "running flag should respect auto pause");
         assert_eq!(status.last_reason.as_deref(), Some("idle"));
     }
+
+    #[test]
+    fn reconcile_discards_wall_clock_jump_during_sleep() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        // Three hours of wall clock passed, but the monotonic clock only saw 90 seconds
+        let now = start + Duration::hours(3);
+        let now_instant = start_instant + std::time::Duration::from_secs(90);
+        let mut session = active_session(start, start, start_instant, 600);
+
+        let skipped = session.reconcile_at(now, now_instant);
+        assert_eq!(skipped, 3 * 3600 - 90);
+        assert_eq!(session.accumulated_seconds, 690);
+        assert_eq!(session.last_resume_at, now);
+    }
+
+    #[test]
+    fn reconcile_ignores_small_drift() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        let now = start + Duration::seconds(300);
+        let now_instant = start_instant + std::time::Duration::from_secs(295);
+        let mut session = active_session(start, start, start_instant, 0);
+
+        assert_eq!(session.reconcile_at(now, now_instant), 0);
+        assert_eq!(session.accumulated_seconds, 0);
+    }
+
+    #[test]
+    fn idle_pause_banks_time_already_practiced() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        let mut session = active_session(start - Duration::minutes(15), start, start_instant, 600);
+
+        let paused_at = start + Duration::seconds(300);
+        let paused_instant = start_instant + std::time::Duration::from_secs(300);
+        assert!(session.pause_at(Some(IDLE_REASON.into()), paused_at, paused_instant));
+        let banked = session.accumulated_seconds;
+        assert_eq!(banked, 900);
+        // However long the machine then sits idle, the banked time is what stop would save
+        session.last_resume_at = start - Duration::hours(2);
+        assert_eq!(session.elapsed_seconds(), banked);
+    }
+
+    #[test]
+    fn paused_minutes_stay_out_of_the_total() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        let at = |minutes: i64| {
+            (start + Duration::minutes(minutes), start_instant + std::time::Duration::from_secs(minutes as u64 * 60))
+        };
+        let mut session = active_session(start, start, start_instant, 0);
+
+        // 10 minutes of practice, a 5-minute pause, then 10 more
+        let (now, instant) = at(10);
+        assert!(session.pause_at(Some(IDLE_REASON.into()), now, instant));
+        assert_eq!(session.elapsed_seconds_at(at(15).0), 600, "the clock is frozen while paused");
+        let (now, instant) = at(15);
+        assert!(session.resume_at(now, instant));
+        assert_eq!(session.elapsed_seconds_at(at(25).0), 20 * 60);
+
+        // A second pause banks the same way and is just as invisible
+        let (now, instant) = at(25);
+        assert!(session.pause_at(None, now, instant));
+        assert!(!session.pause_at(None, now, instant), "already paused");
+        let (now, instant) = at(30);
+        assert!(session.resume_at(now, instant));
+        assert!(!session.resume_at(now, instant), "already running");
+        assert_eq!(session.elapsed_seconds_at(at(35).0), 25 * 60);
+    }
+
+    /// Banks `seconds` of practice on the running session, as if that much had happened. No clock
+    /// is wound back, since an `Instant` cannot reach before the host booted.
+    async fn backdate(timer: &TimerService, seconds: i64) {
+        let mut guard = timer.inner.state.lock().await;
+        let active = guard.as_mut().unwrap();
+        active.started_at -= Duration::seconds(seconds);
+        active.accumulated_seconds += seconds;
+    }
+
+    #[tokio::test]
+    async fn idle_pause_then_resume_saves_both_spans() {
+        let pool = db::test_pool().await;
+        let timer = test_service(&pool, AppSettings::default());
+        let started = timer.start().await.unwrap();
+
+        backdate(&timer, 600).await;
+        assert!(timer.force_pause(IDLE_REASON).await.unwrap());
+        // Five minutes go by while paused; none of them may reach the saved duration
+        timer.inner.state.lock().await.as_mut().unwrap().last_resume_at -= Duration::minutes(5);
+        assert!(!timer.resume_if_paused_for(PRODUCTIVITY_REASON).await.unwrap());
+        assert!(timer.resume_if_paused_for(IDLE_REASON).await.unwrap());
+        backdate(&timer, 300).await;
+
+        let StopOutcome::Saved { minutes } = timer.stop(ReflectionInput::default()).await.unwrap() else {
+            panic!("a 15-minute session should be kept");
+        };
+        assert!((15.0..15.1).contains(&minutes), "{minutes}");
+        let saved: f64 = sqlx::query_scalar("SELECT duration_minutes FROM sessions WHERE id = ?1")
+            .bind(started.session_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(saved, minutes);
+    }
+
+    #[tokio::test]
+    async fn short_sessions_keep_their_seconds_in_totals() {
+        let pool = db::test_pool().await;
+        let settings = AppSettings { minimum_session_minutes: 0, ..AppSettings::default() };
+        let timer = test_service(&pool, settings.clone());
+        for _ in 0..3 {
+            timer.start().await.unwrap();
+            backdate(&timer, 90).await;
+            timer.stop(ReflectionInput::default()).await.unwrap();
+        }
+
+        // Durations are fractional minutes, so three 90 s sessions are 4.5 minutes rather than 3
+        let stats = db::fetch_dashboard_stats(&pool, &settings, 0).await.unwrap();
+        assert!((stats.total_hours * 60.0 - 4.5).abs() < 0.01, "{}", stats.total_hours * 60.0);
+    }
+
+    #[tokio::test]
+    async fn running_session_can_be_relabelled() {
+        let pool = db::test_pool().await;
+        let settings = AppSettings { minimum_session_minutes: 0, ..AppSettings::default() };
+        let timer = test_service(&pool, settings);
+        assert!(matches!(timer.annotate(Some("early")).await, Err(AppError::TimerNotRunning)));
+
+        let started = timer.start().await.unwrap();
+        timer.annotate(Some("Bach prelude")).await.unwrap();
+        let relabelled = timer.change_skill("Harpsichord").await.unwrap();
+        assert_eq!(timer.active_skill_id().await, Some(relabelled.skill_id));
+        timer.stop(ReflectionInput::default()).await.unwrap();
+
+        let (skill_id, notes): (i64, Option<String>) =
+            sqlx::query_as("SELECT skill_id, notes FROM sessions WHERE id = ?1")
+                .bind(started.session_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(skill_id, relabelled.skill_id);
+        assert_eq!(db::fetch_skill(&pool, skill_id).await.unwrap().unwrap().skill_name, "Harpsichord");
+        assert_eq!(notes.as_deref(), Some("Bach prelude"));
+    }
+
+    #[tokio::test]
+    async fn forgotten_sessions_stop_at_the_max_length() {
+        let pool = db::test_pool().await;
+        let settings = AppSettings { max_session_minutes: 60, ..AppSettings::default() };
+        let timer = test_service(&pool, settings);
+        let started = timer.start().await.unwrap();
+        let base = Utc::now();
+        assert!(timer.stop_if_over_max_at(base + Duration::minutes(59)).await.unwrap().is_none());
+        assert!(timer.active_session_id().await.is_some());
+
+        let now = base + Duration::minutes(59) + Duration::hours(8);
+        let outcome = timer.stop_if_over_max_at(now).await.unwrap();
+        assert_eq!(outcome, Some(StopOutcome::Saved { minutes: 60.0 }));
+        assert!(timer.active_session_id().await.is_none());
+
+        let (end, minutes, notes): (String, f64, Option<String>) =
+            sqlx::query_as("SELECT end_time, duration_minutes, notes FROM sessions WHERE id = ?1")
+                .bind(started.session_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(minutes, 60.0);
+        assert_eq!(notes.as_deref(), Some("Auto-stopped at max length"));
+        // Ended when the cap was hit: 8h59m elapsed less the 60-minute cap
+        let overrun = now - chrono::DateTime::parse_from_rfc3339(&end).unwrap().with_timezone(&Utc);
+        assert!((overrun.num_seconds() - (8 * 3600 - 60)).abs() <= 2, "{overrun}");
+    }
+
+    #[tokio::test]
+    async fn quitting_finishes_the_running_session() {
+        let pool = db::test_pool().await;
+        let settings = AppSettings { minimum_session_minutes: 0, ..AppSettings::default() };
+        let timer = test_service(&pool, settings);
+        let started = timer.start().await.unwrap();
+        backdate(&timer, 120).await;
+        assert_eq!(timer.active_session().await.map(|a| a.session_id), Some(started.session_id));
+
+        assert!(matches!(timer.stop_on_exit().await.unwrap(), Some(StopOutcome::Saved { .. })));
+        assert!(db::fetch_unfinished_session(&pool).await.unwrap().is_none());
+        assert!(timer.active_session().await.is_none());
+        assert!(timer.stop_on_exit().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn transitions_land_in_the_event_log_with_their_reasons() {
+        let pool = db::test_pool().await;
+        let settings = AppSettings { minimum_session_minutes: 5, ..AppSettings::default() };
+        let timer = test_service(&pool, settings);
+        let started = timer.start().await.unwrap();
+        assert!(timer.force_pause(IDLE_REASON).await.unwrap());
+        assert!(timer.resume_if_paused_for(IDLE_REASON).await.unwrap());
+        assert!(timer.begin_idle(Utc::now() - Duration::seconds(60)).await);
+        assert!(timer.end_idle(Utc::now()).await);
+        assert!(!timer.end_idle(Utc::now()).await, "no span is open any more");
+
+        let events = db::recent_session_events(&pool, db::DEFAULT_RECENT_EVENTS).await.unwrap();
+        assert!(events.iter().all(|e| e.session_id == started.session_id));
+        let log: Vec<(&str, Option<&str>)> =
+            events.iter().map(|e| (e.event_type.as_str(), e.detail.as_deref())).rev().collect();
+        assert_eq!(log[..3], [("started", None), ("paused", Some(IDLE_REASON)), ("resumed", Some(IDLE_REASON))]);
+        assert_eq!(log[3].0, "idle_started");
+        assert_eq!(log[4].0, "idle_ended");
+        assert_eq!(log.len(), 5);
+        assert_eq!(db::recent_session_events(&pool, 2).await.unwrap().len(), 2);
+
+        // A discarded session is deleted outright, timeline included, rather than trashed
+        assert!(matches!(timer.stop(ReflectionInput::default()).await.unwrap(), StopOutcome::Discarded { .. }));
+        assert!(db::recent_session_events(&pool, db::DEFAULT_RECENT_EVENTS).await.unwrap().is_empty());
+        assert!(matches!(
+            db::restore_session(&pool, started.session_id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn subtracted_idle_spans_leave_the_elapsed_time() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        let now = start + Duration::seconds(900);
+        let now_instant = start_instant + std::time::Duration::from_secs(900);
+        let mut session = active_session(start, start, start_instant, 0);
+
+        assert!(session.begin_idle(now - Duration::seconds(600)));
+        assert!(!session.begin_idle(now - Duration::seconds(500)), "span already open");
+        assert!(session.end_idle(now - Duration::seconds(300)));
+        assert_eq!(session.idle_seconds, 300);
+        assert_eq!(session.elapsed_seconds_at(now), 600);
+
+        // An open span counts against the clock until input returns
+        assert!(session.begin_idle(now - Duration::seconds(120)));
+        assert_eq!(session.elapsed_seconds_at(now), 480);
+        // Pausing closes it, so the paused time is not deducted a second time
+        assert!(session.pause_at(Some("manual".into()), now, now_instant));
+        assert!(session.idle_since.is_none());
+        assert_eq!(session.elapsed_seconds_at(now + Duration::minutes(5)), 480);
+    }
+
+    #[test]
+    fn idle_span_never_reaches_back_before_a_resume() {
+        let resumed = Utc::now();
+        let now = resumed + Duration::seconds(60);
+        let mut session = active_session(resumed - Duration::minutes(29), resumed, Instant::now(), 1200);
+
+        // Input stopped before the resume; only the time since resuming is idle
+        assert!(session.begin_idle(now - Duration::seconds(600)));
+        assert!(session.end_idle(now));
+        assert_eq!(session.idle_seconds, 60);
+        assert_eq!(session.elapsed_seconds_at(now), 1200);
+    }
+
+    #[tokio::test]
+    async fn stop_saves_duration_without_subtracted_idle_time() {
+        let pool = db::test_pool().await;
+        let timer = test_service(&pool, AppSettings::default());
+        let started = timer.start().await.unwrap();
+        backdate(&timer, 1200).await;
+        // Idle spans never reach back before the resume, so this one runs forward from the start
+        assert!(timer.begin_idle(started.started_at).await);
+        assert!(timer.end_idle(started.started_at + Duration::seconds(300)).await);
+
+        let StopOutcome::Saved { minutes } = timer.stop(ReflectionInput::default()).await.unwrap() else {
+            panic!("a 15-minute session should be kept");
+        };
+        assert!((15.0..15.1).contains(&minutes), "{minutes}");
+    }
+
+    #[tokio::test]
+    async fn idle_prompt_keeps_the_session_until_answered() {
+        let pool = db::test_pool().await;
+        let timer = test_service(&pool, AppSettings::default());
+        timer.start().await.unwrap();
+        backdate(&timer, 600).await;
+
+        assert!(timer.stop_from_idle().await.unwrap().is_none(), "not paused for idle yet");
+        assert!(timer.force_pause(IDLE_REASON).await.unwrap());
+        assert!(timer.paused_for_idle().await);
+        assert!(timer.resume_from_idle().await.unwrap());
+        assert!(!timer.paused_for_idle().await);
+
+        assert!(timer.force_pause(IDLE_REASON).await.unwrap());
+        let Some(StopOutcome::Saved { minutes }) = timer.stop_from_idle().await.unwrap() else {
+            panic!("the ten minutes before going idle should be kept");
+        };
+        assert!((10.0..10.1).contains(&minutes), "{minutes}");
+        assert!(timer.active_session_id().await.is_none());
+    }
+
+    #[test]
+    fn focus_mode_only_counts_the_foreground_app() {
+        let mut settings = AppSettings {
+            allowed_apps: vec![AppRule::new("code".into(), AppMatchKind::Process, false)],
+            ..AppSettings::default()
+        };
+        let running = vec!["code".to_string(), "firefox".to_string()];
+        let browsing = ForegroundWindow {
+            process: Some("firefox".into()),
+            title: Some("Videos".into()),
+        };
+        let coding = ForegroundWindow {
+            process: Some("code".into()),
+            title: Some("main.rs".into()),
+        };
+
+        assert!(allowed_active(&settings, &running, &browsing));
+        settings.active_requires_focus_app = true;
+        assert!(!allowed_active(&settings, &running, &browsing));
+        assert!(allowed_active(&settings, &running, &coding));
+        assert!(!allowed_active(&settings, &running, &ForegroundWindow::default()));
+    }
+
+    #[test]
+    fn pause_and_resume_keep_the_session_open() {
+        let start = Utc::now();
+        let start_instant = Instant::now();
+        let mut session = active_session(start, start, start_instant, 0);
+
+        let paused_at = start + Duration::seconds(300);
+        let paused_instant = start_instant + std::time::Duration::from_secs(300);
+        assert!(session.pause_at(Some("idle".into()), paused_at, paused_instant));
+        assert!(!session.pause(None), "second pause is a no-op");
+        let status = session.as_status();
+        assert!(!status.running);
+        assert_eq!(status.started_at, Some(session.started_at));
+        assert!(status.elapsed_seconds >= 300);
+
+        assert!(session.resume());
+        assert!(session.as_status().running);
+        assert_eq!(session.last_reason, None);
+    }
 }
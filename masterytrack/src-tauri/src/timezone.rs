@@ -0,0 +1,103 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::errors::{AppError, AppResult};
+
+/// The zone used to decide which calendar day a session belongs to.
+#[derive(Debug, Clone, Copy)]
+pub enum DayZone {
+    /// Follow the operating system's current offset.
+    System,
+    Named(Tz),
+}
+
+impl DayZone {
+    /// Resolves the `timezone` setting, falling back to the system zone when unset or unknown.
+    pub fn from_setting(name: Option<&str>) -> Self {
+        match name.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(name) => match name.parse::<Tz>() {
+                Ok(tz) => DayZone::Named(tz),
+                Err(_) => {
+                    log::warn!("Unknown timezone '{name}', using system local time");
+                    DayZone::System
+                }
+            },
+            None => DayZone::System,
+        }
+    }
+
+    pub fn date_of(&self, instant: DateTime<Utc>) -> NaiveDate {
+        match self {
+            DayZone::System => instant.with_timezone(&Local).date_naive(),
+            DayZone::Named(tz) => instant.with_timezone(tz).date_naive(),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(Utc::now())
+    }
+
+    pub fn time_of(&self, instant: DateTime<Utc>) -> NaiveTime {
+        match self {
+            DayZone::System => instant.with_timezone(&Local).time(),
+            DayZone::Named(tz) => instant.with_timezone(tz).time(),
+        }
+    }
+
+    /// The instant local midnight begins on `date`; zones that skip midnight start an hour later.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        self.local_to_utc(midnight)
+            .or_else(|| self.local_to_utc(midnight + chrono::Duration::hours(1)))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+    }
+
+    fn local_to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            DayZone::System => Local.from_local_datetime(&local).earliest().map(|dt| dt.with_timezone(&Utc)),
+            DayZone::Named(tz) => tz.from_local_datetime(&local).earliest().map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
+}
+
+/// Rejects names `chrono-tz` does not know; an empty name means "system".
+pub fn validate_timezone(name: Option<&str>) -> AppResult<()> {
+    match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => name
+            .parse::<Tz>()
+            .map(|_| ())
+            .map_err(|_| AppError::Custom(format!("Unknown timezone: {name}"))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn late_evening_in_negative_offset_stays_on_local_day() {
+        let zone = DayZone::from_setting(Some("America/New_York"));
+        // 22:30 on March 1st in New York is already March 2nd in UTC
+        let session = utc("2024-03-02T03:30:00Z");
+        assert_eq!(zone.date_of(session), NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+    }
+
+    #[test]
+    fn start_of_day_is_local_midnight() {
+        let zone = DayZone::from_setting(Some("America/New_York"));
+        let start = zone.start_of_day(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(start, utc("2024-03-01T05:00:00Z"));
+    }
+
+    #[test]
+    fn unknown_zone_falls_back_to_system() {
+        assert!(matches!(DayZone::from_setting(Some("Mars/Olympus")), DayZone::System));
+        assert!(validate_timezone(Some("Mars/Olympus")).is_err());
+        assert!(validate_timezone(Some("")).is_ok());
+    }
+}
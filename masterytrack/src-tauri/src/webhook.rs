@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::Serialize;
+use tauri::async_runtime;
+
+use crate::models::{GoalWebhookPayload, WeeklySummary};
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+/// POSTs `payload` to `url` in the background; failures are only logged so stopping the timer never waits on it.
+pub fn send_goal_webhook(url: &str, payload: GoalWebhookPayload) {
+    let delivered = format!("Goal webhook delivered for {}", payload.date);
+    post_json(url, "Goal webhook", delivered, payload);
+}
+
+/// POSTs the weekly recap to `url` in the background, like `send_goal_webhook`.
+pub fn send_weekly_summary(url: &str, summary: WeeklySummary) {
+    let delivered = format!("Weekly summary delivered for {} to {}", summary.from, summary.to);
+    post_json(url, "Weekly summary webhook", delivered, summary);
+}
+
+fn post_json<T: Serialize + Send + 'static>(url: &str, label: &'static str, delivered: String, payload: T) {
+    let url = url.trim().to_string();
+    if url.is_empty() {
+        return;
+    }
+    async_runtime::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Unable to create webhook client: {e}");
+                return;
+            }
+        };
+        match client.post(&url).json(&payload).send().await {
+            Ok(response) if response.status().is_success() => info!("{delivered}"),
+            Ok(response) => warn!("{label} returned {}", response.status()),
+            Err(e) => warn!("{label} failed: {e}"),
+        }
+    });
+}
@@ -12,15 +12,21 @@ import { AboutPage } from './components/AboutPage'
 import { SplashScreen } from './components/SplashScreen'
 import { ScreenshotGallery } from './components/ScreenshotGallery'
 import { useTrackerStore } from './store'
-import type { SessionHistoryRow, TimerStatus } from './types'
+import type { AppError, FactoryResetReport, GoalApproachNotification, IdlePrompt, MilestoneNotification, WeeklySummary, ProductivitySnapshot, SelfTestReport, SessionEvent, SessionHistoryRow, SettingsIssue, TimerStatus } from './types'
 import './App.css'
 
 type Tab = 'dashboard' | 'history' | 'settings' | 'about'
 
+const isNotFound = (err: unknown) => (err as AppError | null)?.type === 'NotFound'
+const errorText = (err: unknown) => (err as AppError | null)?.message ?? String(err)
+
 function App() {
   const timer = useTrackerStore((s) => s.timer)
   const stats = useTrackerStore((s) => s.stats)
+  const todaysSessions = useTrackerStore((s) => s.todaysSessions)
   const sessions = useTrackerStore((s) => s.sessions)
+  const sessionsTotal = useTrackerStore((s) => s.sessionsTotal)
+  const loadMoreSessions = useTrackerStore((s) => s.loadMoreSessions)
   const settings = useTrackerStore((s) => s.settings)
   const reflectionDraft = useTrackerStore((s) => s.reflectionDraft)
   const reflectionOpen = useTrackerStore((s) => s.reflectionOpen)
@@ -30,12 +36,20 @@ function App() {
 
   const startTimer = useTrackerStore((s) => s.startTimer)
   const stopTimer = useTrackerStore((s) => s.stopTimer)
+  const pauseTimer = useTrackerStore((s) => s.pauseTimer)
+  const resumeTimer = useTrackerStore((s) => s.resumeTimer)
+  const skipBreak = useTrackerStore((s) => s.skipBreak)
   const loadInitial = useTrackerStore((s) => s.loadInitial)
   const refreshSessions = useTrackerStore((s) => s.refreshSessions)
   const refreshStats = useTrackerStore((s) => s.refreshStats)
   const saveSettings = useTrackerStore((s) => s.saveSettings)
   const exportData = useTrackerStore((s) => s.exportData)
+  const exportDashboard = useTrackerStore((s) => s.exportDashboard)
+  const exportPdf = useTrackerStore((s) => s.exportPdf)
   const importData = useTrackerStore((s) => s.importData)
+  const restoreBackup = useTrackerStore((s) => s.restoreBackup)
+  const factoryReset = useTrackerStore((s) => s.factoryReset)
+  const importSessions = useTrackerStore((s) => s.importSessions)
   const setReflectionOpen = useTrackerStore((s) => s.setReflectionOpen)
   const updateReflectionDraft = useTrackerStore((s) => s.updateReflectionDraft)
   const setTimerState = useTrackerStore((s) => s.setTimer)
@@ -43,9 +57,14 @@ function App() {
   const [tab, setTab] = useState<Tab>('dashboard')
   const [theme, setTheme] = useState<'light' | 'dark'>('dark')
   const [busy, setBusy] = useState(false)
-  const [banner, setBanner] = useState<{ message: string; tone?: 'info' | 'success' | 'warning' } | null>(null)
+  const [banner, setBanner] = useState<{
+    message: string
+    tone?: 'info' | 'success' | 'warning'
+    action?: { label: string; onClick: () => void }
+  } | null>(null)
   const [reflectionSaving, setReflectionSaving] = useState(false)
   const [galleryOpen, setGalleryOpen] = useState(false)
+  const [productivityEnforcing, setProductivityEnforcing] = useState(false)
 
   useEffect(() => {
     document.documentElement.dataset.theme = theme
@@ -58,6 +77,20 @@ function App() {
     })
   }, [loadInitial])
 
+  useEffect(() => {
+    const offerRecovery = async () => {
+      const pending = await invoke<SessionHistoryRow | null>('unfinished_session')
+      if (!pending) return
+      const minutes = Math.round(pending.duration_minutes)
+      if (window.confirm(`A session from ${new Date(pending.start).toLocaleString()} was not stopped. Recover it with ${minutes} minutes?`)) {
+        await invoke<SessionHistoryRow | null>('recover_session')
+        await Promise.all([refreshSessions(), refreshStats()])
+        setBanner({ message: 'Unfinished session recovered.', tone: 'success' })
+      }
+    }
+    offerRecovery().catch((err) => console.error(err))
+  }, [refreshSessions, refreshStats])
+
   useEffect(() => {
     const disposers: Array<() => void> = []
     const run = async () => {
@@ -73,17 +106,109 @@ function App() {
           refreshStats()
         }),
       )
+      disposers.push(
+        await listen<IdlePrompt>('timer:idle-prompt', (event) => {
+          setBanner({
+            message: `You went idle, so the timer is paused. It stops in ${event.payload.timeout_minutes} min unless you resume.`,
+            tone: 'warning',
+            action: {
+              label: 'Resume',
+              onClick: () => {
+                invoke('resume_from_idle')
+                  .then(() => setBanner(null))
+                  .catch((err) => setBanner({ message: `Unable to resume: ${errorText(err)}`, tone: 'warning' }))
+              },
+            },
+          })
+        }),
+      )
+      disposers.push(
+        await listen('timer:stopped', () => {
+          setBanner({ message: 'Session stopped.', tone: 'info' })
+          Promise.all([refreshSessions(), refreshStats()]).catch((err) => console.error(err))
+        }),
+      )
+      disposers.push(
+        await listen('timer:auto-stopped', () => {
+          setBanner({ message: 'Session stopped at its maximum length.', tone: 'warning' })
+          Promise.all([refreshSessions(), refreshStats()]).catch((err) => console.error(err))
+        }),
+      )
       disposers.push(
         await listen('goal:reached', () => {
           setBanner({ message: 'Daily practice goal met 🎯', tone: 'success' })
         }),
       )
+      disposers.push(
+        await listen<GoalApproachNotification>('goal:approaching', (event) => {
+          const left = Math.max(1, Math.ceil(event.payload.goal_minutes - event.payload.total_minutes))
+          setBanner({ message: `Almost at today's goal: ${left} min to go.`, tone: 'info' })
+        }),
+      )
+      disposers.push(
+        await listen<MilestoneNotification>('milestone:reached', (event) => {
+          setBanner({ message: `${event.payload.milestone_days}-day streak reached!`, tone: 'success' })
+        }),
+      )
+      disposers.push(
+        await listen<WeeklySummary>('weekly:summary', (event) => {
+          const { total_minutes, days_practiced } = event.payload
+          setBanner({
+            message: `This week: ${(total_minutes / 60).toFixed(1)}h over ${days_practiced} day${days_practiced === 1 ? '' : 's'}.`,
+            tone: 'info',
+          })
+        }),
+      )
+      disposers.push(
+        await listen('reminder:practice', () => {
+          setBanner({ message: "You haven't practiced yet today.", tone: 'info' })
+        }),
+      )
+      disposers.push(
+        await listen('pomodoro:break-start', (event) => {
+          setBanner({ message: `Pomodoro break: ${event.payload as number} minutes`, tone: 'info' })
+        }),
+      )
+      disposers.push(
+        await listen('pomodoro:break-end', () => {
+          setBanner({ message: 'Break over, back to practice.', tone: 'info' })
+        }),
+      )
+      disposers.push(
+        await listen('productivity:warning', (event) => {
+          const payload = event.payload as { reason?: string; grace_seconds?: number }
+          setBanner({
+            message: `${payload?.reason ?? 'Focus lost'} — pausing in ${payload?.grace_seconds ?? 0}s`,
+            tone: 'warning',
+          })
+        }),
+      )
+      disposers.push(
+        await listen('productivity:state', (event) => {
+          setProductivityEnforcing(Boolean((event.payload as { enforcing?: boolean })?.enforcing))
+        }),
+      )
+      disposers.push(
+        await listen('db:restored', () => {
+          setBanner({ message: 'Database restored from backup.', tone: 'success' })
+          loadInitial().catch((err) => console.error(err))
+        }),
+      )
+      disposers.push(
+        await listen<FactoryResetReport>('data:reset', (event) => {
+          setBanner({
+            message: `All data cleared: ${event.payload.sessions_removed} sessions removed.`,
+            tone: 'success',
+          })
+          loadInitial().catch((err) => console.error(err))
+        }),
+      )
     }
     run().catch((err) => console.error(err))
     return () => {
       disposers.forEach((off) => off())
     }
-  }, [refreshStats, setTimerState])
+  }, [loadInitial, refreshSessions, refreshStats, setTimerState])
 
   const handleStart = async () => {
     setBusy(true)
@@ -105,8 +230,15 @@ function App() {
   const handleReflectionSubmit = async () => {
     setReflectionSaving(true)
     try {
-      await stopTimer(reflectionDraft)
-      setBanner({ message: 'Session saved with reflection.', tone: 'success' })
+      const outcome = await stopTimer(reflectionDraft)
+      if (outcome.status === 'discarded') {
+        setBanner({
+          message: `Session too short (under ${outcome.minimum_minutes} min), discarded.`,
+          tone: 'info',
+        })
+      } else {
+        setBanner({ message: 'Session saved with reflection.', tone: 'success' })
+      }
     } catch (err) {
       console.error(err)
       setBanner({ message: 'Unable to save session.', tone: 'warning' })
@@ -124,27 +256,93 @@ function App() {
     what_practiced?: string | null
     what_learned?: string | null
     next_focus?: string | null
+    tags?: string[]
   }) => {
-    await invoke('update_session', {
-      payload: {
-        id: draft.id,
-        start_time: new Date(draft.start).toISOString(),
-        end_time: draft.end ? new Date(draft.end).toISOString() : null,
-        duration_minutes: draft.duration_minutes,
-        notes: draft.notes,
-        what_practiced: draft.what_practiced,
-        what_learned: draft.what_learned,
-        next_focus: draft.next_focus,
-      },
-    })
+    try {
+      await invoke('update_session', {
+        payload: {
+          id: draft.id,
+          start_time: new Date(draft.start).toISOString(),
+          end_time: draft.end ? new Date(draft.end).toISOString() : null,
+          duration_minutes: draft.duration_minutes,
+          notes: draft.notes,
+          what_practiced: draft.what_practiced,
+          what_learned: draft.what_learned,
+          next_focus: draft.next_focus,
+          tags: draft.tags ?? [],
+        },
+      })
+    } catch (err) {
+      if (isNotFound(err)) {
+        await refreshSessions()
+        setBanner({ message: 'That session was deleted elsewhere; nothing to update.', tone: 'info' })
+        return
+      }
+      setBanner({ message: `Unable to update session: ${errorText(err)}`, tone: 'warning' })
+      throw err
+    }
     await refreshSessions()
     setBanner({ message: 'Session updated.', tone: 'success' })
   }
 
+  const handleCreateSession = async (draft: {
+    start: string
+    end?: string | null
+    notes?: string | null
+    what_practiced?: string | null
+    what_learned?: string | null
+    next_focus?: string | null
+    tags?: string[]
+  }) => {
+    try {
+      await invoke('create_manual_session', {
+        start: new Date(draft.start).toISOString(),
+        end: draft.end ? new Date(draft.end).toISOString() : null,
+        reflections: {
+          notes: draft.notes || null,
+          what_practiced: draft.what_practiced || null,
+          what_learned: draft.what_learned || null,
+          next_focus: draft.next_focus || null,
+          tags: draft.tags ?? [],
+        },
+      })
+    } catch (err) {
+      setBanner({ message: `Unable to log session: ${String(err)}`, tone: 'warning' })
+      throw err
+    }
+    await Promise.all([refreshSessions(), refreshStats()])
+    setBanner({ message: 'Session logged.', tone: 'success' })
+  }
+
   const handleDeleteSession = async (id: number) => {
-    await invoke('delete_session', { session_id: id })
-    await refreshSessions()
-    setBanner({ message: 'Session removed.', tone: 'info' })
+    try {
+      await invoke('delete_session', { session_id: id })
+    } catch (err) {
+      if (!isNotFound(err)) {
+        setBanner({ message: `Unable to delete session: ${errorText(err)}`, tone: 'warning' })
+        return
+      }
+      await refreshSessions()
+      setBanner({ message: 'That session was already deleted.', tone: 'info' })
+      return
+    }
+    await Promise.all([refreshSessions(), refreshStats()])
+    setBanner({
+      message: 'Session removed.',
+      tone: 'info',
+      action: { label: 'Undo', onClick: () => void handleRestoreSession(id) },
+    })
+  }
+
+  const handleRestoreSession = async (id: number) => {
+    try {
+      await invoke('restore_session', { session_id: id })
+    } catch (err) {
+      setBanner({ message: `Unable to restore session: ${errorText(err)}`, tone: 'warning' })
+      return
+    }
+    await Promise.all([refreshSessions(), refreshStats()])
+    setBanner({ message: 'Session restored.', tone: 'success' })
   }
 
   const dashboardContent = useMemo(() => {
@@ -158,11 +356,17 @@ function App() {
           stats={stats}
           onStart={handleStart}
           onStop={handleStop}
+          onPause={() => pauseTimer().catch((err) => console.error(err))}
+          onResume={() => resumeTimer().catch((err) => console.error(err))}
+          onSkipBreak={() => skipBreak().catch((err) => console.error(err))}
           disabled={busy}
         />
         <div className="card progress-card">
           <div className="progress-flex">
-            <ProgressRing progress={stats.goal_progress} label="toward 10,000h" />
+            <ProgressRing
+              progress={stats.mastery_progress}
+              label={`toward ${stats.total_hours_target.toLocaleString()}h`}
+            />
             <div className="progress-meta">
               <div>
                 <span className="muted tiny">Total hours</span>
@@ -170,12 +374,22 @@ function App() {
               </div>
               <div>
                 <span className="muted tiny">Remaining</span>
-                <strong>{Math.max(10000 - stats.total_hours, 0).toFixed(1)}h</strong>
+                <strong>{Math.max(stats.total_hours_target - stats.total_hours, 0).toFixed(1)}h</strong>
               </div>
               <div>
                 <span className="muted tiny">Daily goal</span>
                 <strong>{stats.daily_goal_hours.toFixed(2)}h</strong>
               </div>
+              <div>
+                <span className="muted tiny">At current pace</span>
+                <strong>
+                  {stats.eta_days_remaining == null
+                    ? '—'
+                    : stats.eta_days_remaining >= 365
+                      ? `~${(stats.eta_days_remaining / 365).toFixed(1)} years left`
+                      : `~${Math.ceil(stats.eta_days_remaining)} days left`}
+                </strong>
+              </div>
             </div>
           </div>
         </div>
@@ -183,6 +397,9 @@ function App() {
           <article>
             <p className="muted tiny">Today</p>
             <h2>{stats.today_hours.toFixed(2)}h</h2>
+            <span className="muted tiny">
+              {todaysSessions.length} {todaysSessions.length === 1 ? 'session' : 'sessions'}
+            </span>
           </article>
           <article>
             <p className="muted tiny">This week</p>
@@ -192,6 +409,17 @@ function App() {
             <p className="muted tiny">This month</p>
             <h2>{stats.month_hours.toFixed(2)}h</h2>
           </article>
+          <article>
+            <p className="muted tiny">Avg session</p>
+            <h2>{Math.round(stats.avg_session_minutes)}m</h2>
+            <span className="muted tiny">
+              longest {Math.round(stats.longest_session_minutes)}m
+            </span>
+          </article>
+          <article>
+            <p className="muted tiny">Sessions this week</p>
+            <h2>{stats.sessions_this_week}</h2>
+          </article>
         </div>
       </div>
     )
@@ -206,6 +434,7 @@ function App() {
           <p className="muted">{settings?.skill_name ?? 'Loading skill…'}</p>
         </div>
         <div className="top-bar-actions">
+          {productivityEnforcing && <span className="muted tiny">Productivity mode active</span>}
           <button className="ghost" onClick={() => setTheme(theme === 'dark' ? 'light' : 'dark')}>
             {theme === 'dark' ? 'Light Mode' : 'Dark Mode'}
           </button>
@@ -225,7 +454,7 @@ function App() {
       </nav>
 
       {banner && (
-        <Banner message={banner.message} tone={banner.tone} onClose={() => setBanner(null)} />
+        <Banner message={banner.message} tone={banner.tone} action={banner.action} onClose={() => setBanner(null)} />
       )}
 
       <main>
@@ -233,16 +462,30 @@ function App() {
         {tab === 'history' && (
           <HistoryTable
             sessions={sessions as SessionHistoryRow[]}
+            total={sessionsTotal}
+            onLoadMore={loadMoreSessions}
             onSave={handleUpdateSession}
             onDelete={handleDeleteSession}
+            onCreate={handleCreateSession}
           />
         )}
         {tab === 'settings' && (
           <SettingsPanel
             settings={settings}
             onSave={saveSettings}
+            onValidate={(newSettings) => invoke<SettingsIssue[]>('validate_settings', { newSettings })}
             onExport={exportData}
+            onExportDashboard={exportDashboard}
+            onExportPdf={exportPdf}
             onImport={importData}
+            onRestore={restoreBackup}
+            onFactoryReset={factoryReset}
+            onSetDataDir={(path) => invoke('set_data_dir', { path })}
+            onImportSessions={importSessions}
+            onChangePassphrase={(passphrase) => invoke('change_passphrase', { passphrase })}
+            onCheckProductivity={() => invoke<ProductivitySnapshot>('productivity_status')}
+            onSelfTest={() => invoke<SelfTestReport>('self_test')}
+            onLoadEvents={() => invoke<SessionEvent[]>('recent_events', { limit: 100 })}
             exporting={exporting}
             importing={importing}
             lastExportPath={lastExportPath}
@@ -258,6 +501,7 @@ function App() {
         onSubmit={handleReflectionSubmit}
         onClose={() => setReflectionOpen(false)}
         saving={reflectionSaving}
+        prompts={settings?.reflection_prompts}
       />
 
       <MusicPlayer
@@ -1,13 +1,19 @@
 interface Props {
   message: string
   tone?: 'info' | 'success' | 'warning'
+  action?: { label: string; onClick: () => void }
   onClose?: () => void
 }
 
-export const Banner = ({ message, tone = 'info', onClose }: Props) => {
+export const Banner = ({ message, tone = 'info', action, onClose }: Props) => {
   return (
     <div className={`banner banner--${tone}`}>
       <span>{message}</span>
+      {action && (
+        <button className="ghost" onClick={action.onClick}>
+          {action.label}
+        </button>
+      )}
       {onClose && (
         <button className="ghost" onClick={onClose}>
           ×
@@ -12,17 +12,25 @@ interface EditDraft {
   what_practiced?: string | null
   what_learned?: string | null
   next_focus?: string | null
+  tags?: string[]
 }
 
 interface Props {
   sessions: SessionHistoryRow[]
+  total?: number
+  onLoadMore?: () => Promise<void>
   onSave: (draft: EditDraft) => Promise<void>
   onDelete: (id: number) => Promise<void>
+  onCreate?: (draft: EditDraft) => Promise<void>
 }
 
-export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
+// Drafts with this id are new manual entries rather than edits
+const NEW_SESSION_ID = 0
+
+export const HistoryTable = ({ sessions, total, onLoadMore, onSave, onDelete, onCreate }: Props) => {
   const [editing, setEditing] = useState<EditDraft | null>(null)
   const [saving, setSaving] = useState(false)
+  const [loadingMore, setLoadingMore] = useState(false)
 
   const handleEdit = (session: SessionHistoryRow) => {
     setEditing({
@@ -34,14 +42,36 @@ export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
       what_practiced: session.what_practiced ?? '',
       what_learned: session.what_learned ?? '',
       next_focus: session.next_focus ?? '',
+      tags: session.tags ?? [],
+    })
+  }
+
+  const handleNew = () => {
+    const end = dayjs()
+    setEditing({
+      id: NEW_SESSION_ID,
+      start: end.subtract(1, 'hour').format('YYYY-MM-DDTHH:mm'),
+      end: end.format('YYYY-MM-DDTHH:mm'),
+      duration_minutes: 60,
+      notes: '',
+      what_practiced: '',
+      what_learned: '',
+      next_focus: '',
+      tags: [],
     })
   }
 
+  const isNew = editing?.id === NEW_SESSION_ID
+
   const handleSave = async () => {
     if (!editing) return
     setSaving(true)
     try {
-      await onSave(editing)
+      if (isNew && onCreate) {
+        await onCreate(editing)
+      } else {
+        await onSave(editing)
+      }
       setEditing(null)
     } finally {
       setSaving(false)
@@ -55,6 +85,11 @@ export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
           <h3>Session History</h3>
           <p className="muted">Edit or clean up past practice blocks.</p>
         </div>
+        {onCreate && (
+          <button className="ghost" onClick={handleNew}>
+            Log past session
+          </button>
+        )}
       </header>
       <div className="table-wrapper">
         <table>
@@ -94,6 +129,27 @@ export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
           </tbody>
         </table>
       </div>
+      {onLoadMore && total !== undefined && sessions.length < total && (
+        <div className="load-more">
+          <span className="muted tiny">
+            Showing {sessions.length} of {total} sessions
+          </span>
+          <button
+            className="ghost"
+            disabled={loadingMore}
+            onClick={async () => {
+              setLoadingMore(true)
+              try {
+                await onLoadMore()
+              } finally {
+                setLoadingMore(false)
+              }
+            }}
+          >
+            {loadingMore ? 'Loading…' : 'Load older sessions'}
+          </button>
+        </div>
+      )}
 
       {editing && (
         <div className="edit-panel">
@@ -115,17 +171,19 @@ export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
               />
             </label>
           </div>
-          <label>
-            Duration (minutes)
-            <input
-              type="number"
-              min={1}
-              value={editing.duration_minutes}
-              onChange={(e) =>
-                setEditing({ ...editing, duration_minutes: Number(e.target.value) })
-              }
-            />
-          </label>
+          {!isNew && (
+            <label>
+              Duration (minutes)
+              <input
+                type="number"
+                min={1}
+                value={editing.duration_minutes}
+                onChange={(e) =>
+                  setEditing({ ...editing, duration_minutes: Number(e.target.value) })
+                }
+              />
+            </label>
+          )}
           <label>
             Session Notes
             <textarea
@@ -162,7 +220,7 @@ export const HistoryTable = ({ sessions, onSave, onDelete }: Props) => {
               Cancel
             </button>
             <button className="primary" disabled={saving} onClick={handleSave}>
-              {saving ? 'Saving…' : 'Save Changes'}
+              {saving ? 'Saving…' : isNew ? 'Log Session' : 'Save Changes'}
             </button>
           </div>
         </div>
@@ -7,9 +7,10 @@ interface Props {
   onSubmit: () => Promise<void>
   onClose: () => void
   saving: boolean
+  prompts?: string[]
 }
 
-export const ReflectionModal = ({ open, draft, onChange, onSubmit, onClose, saving }: Props) => {
+export const ReflectionModal = ({ open, draft, onChange, onSubmit, onClose, saving, prompts = [] }: Props) => {
   if (!open) return null
 
   return (
@@ -132,6 +133,47 @@ export const ReflectionModal = ({ open, draft, onChange, onSubmit, onClose, savi
               }}
             />
           </label>
+          {prompts.map((prompt) => (
+            <label key={prompt} style={{ display: 'flex', flexDirection: 'column', gap: '6px' }}>
+              <span style={{ fontSize: '13px', fontWeight: '500', color: 'var(--text-color, #fff)' }}>
+                {prompt}
+              </span>
+              <textarea
+                rows={2}
+                value={draft.extra?.[prompt] ?? ''}
+                onChange={(e) => onChange({ ...draft, extra: { ...draft.extra, [prompt]: e.target.value } })}
+                style={{
+                  width: '100%',
+                  padding: '10px 12px',
+                  borderRadius: '8px',
+                  border: '1px solid var(--border-color, #2d2d44)',
+                  backgroundColor: 'rgba(255, 255, 255, 0.05)',
+                  color: 'var(--text-color, #fff)',
+                  fontSize: '14px',
+                  resize: 'none',
+                }}
+              />
+            </label>
+          ))}
+          <label style={{ display: 'flex', flexDirection: 'column', gap: '6px' }}>
+            <span style={{ fontSize: '13px', fontWeight: '500', color: 'var(--muted-color, #888)' }}>
+              Tags (comma separated, optional)
+            </span>
+            <input
+              value={(draft.tags ?? []).join(', ')}
+              placeholder="scales, repertoire"
+              onChange={(e) => onChange({ ...draft, tags: e.target.value.split(',').map((t) => t.trim()) })}
+              style={{
+                width: '100%',
+                padding: '10px 12px',
+                borderRadius: '8px',
+                border: '1px solid var(--border-color, #2d2d44)',
+                backgroundColor: 'rgba(255, 255, 255, 0.05)',
+                color: 'var(--text-color, #fff)',
+                fontSize: '14px',
+              }}
+            />
+          </label>
           <label style={{ display: 'flex', flexDirection: 'column', gap: '6px' }}>
             <span style={{ fontSize: '13px', fontWeight: '500', color: 'var(--muted-color, #888)' }}>
               Session notes (optional)
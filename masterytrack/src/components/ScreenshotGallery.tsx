@@ -1,12 +1,15 @@
 import { useEffect, useState, useCallback } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import type { AppError } from '../types'
 
 interface ScreenshotInfo {
   filename: string
   path: string
   timestamp: string
   size_kb: number
-  imageData?: string // base64 data URL
+  size_bytes: number
+  thumbnail_path: string | null
+  imageData?: string // base64 data URL of the thumbnail, or the full image when there is none
 }
 
 interface Props {
@@ -18,6 +21,7 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
   const [screenshots, setScreenshots] = useState<ScreenshotInfo[]>([])
   const [loading, setLoading] = useState(true)
   const [selectedImage, setSelectedImage] = useState<ScreenshotInfo | null>(null)
+  const [fullImage, setFullImage] = useState<string | null>(null)
   const [deleting, setDeleting] = useState<string | null>(null)
   const [viewMode, setViewMode] = useState<'grid' | 'list'>('grid')
 
@@ -26,11 +30,13 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
     try {
       const result = await invoke<ScreenshotInfo[]>('list_screenshots')
       
-      // Load image data for each screenshot
+      // Load the small thumbnails up front; full images are fetched when one is opened
       const screenshotsWithImages = await Promise.all(
         result.map(async (screenshot) => {
           try {
-            const imageData = await invoke<string>('read_screenshot_base64', { path: screenshot.path })
+            const imageData = await invoke<string>('read_screenshot_base64', {
+              path: screenshot.thumbnail_path ?? screenshot.path,
+            })
             return { ...screenshot, imageData }
           } catch (error) {
             console.error('Failed to load image:', screenshot.path, error)
@@ -53,6 +59,20 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
     }
   }, [isOpen, loadScreenshots])
 
+  useEffect(() => {
+    setFullImage(null)
+    if (!selectedImage?.thumbnail_path) return
+    let cancelled = false
+    invoke<string>('read_screenshot_base64', { path: selectedImage.path })
+      .then((data) => {
+        if (!cancelled) setFullImage(data)
+      })
+      .catch((error) => console.error('Failed to load image:', selectedImage.path, error))
+    return () => {
+      cancelled = true
+    }
+  }, [selectedImage])
+
   const handleDelete = async (screenshot: ScreenshotInfo, e: React.MouseEvent) => {
     e.stopPropagation()
     if (!confirm(`Delete screenshot from ${screenshot.timestamp}?`)) return
@@ -60,15 +80,19 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
     setDeleting(screenshot.path)
     try {
       await invoke('delete_screenshot', { path: screenshot.path })
-      setScreenshots(prev => prev.filter(s => s.path !== screenshot.path))
-      if (selectedImage?.path === screenshot.path) {
-        setSelectedImage(null)
-      }
     } catch (error) {
-      console.error('Failed to delete screenshot:', error)
+      // A file that is already gone can still leave the list; anything else keeps it visible
+      if ((error as AppError | null)?.type !== 'NotFound') {
+        console.error('Failed to delete screenshot:', error)
+        return
+      }
     } finally {
       setDeleting(null)
     }
+    setScreenshots(prev => prev.filter(s => s.path !== screenshot.path))
+    if (selectedImage?.path === screenshot.path) {
+      setSelectedImage(null)
+    }
   }
 
   const formatTimestamp = (timestamp: string) => {
@@ -189,6 +213,28 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
               </button>
             </div>
 
+            {/* Open folder */}
+            <button
+              onClick={() =>
+                invoke<string>('open_screenshot_folder').catch((error) =>
+                  console.error('Failed to open screenshot folder:', error)
+                )
+              }
+              style={{
+                padding: '10px',
+                border: 'none',
+                borderRadius: '10px',
+                backgroundColor: 'rgba(255, 255, 255, 0.05)',
+                color: 'var(--text-color, #fff)',
+                cursor: 'pointer',
+                fontSize: '18px',
+                transition: 'background-color 0.2s',
+              }}
+              title="Open folder"
+            >
+              📂
+            </button>
+
             {/* Refresh */}
             <button
               onClick={loadScreenshots}
@@ -502,9 +548,9 @@ export const ScreenshotGallery = ({ isOpen, onClose }: Props) => {
             }}
             onClick={() => setSelectedImage(null)}
           >
-            {selectedImage.imageData ? (
+            {fullImage ?? selectedImage.imageData ? (
               <img
-                src={selectedImage.imageData}
+                src={fullImage ?? selectedImage.imageData}
                 alt={selectedImage.filename}
                 style={{
                   maxWidth: '95%',
@@ -1,12 +1,31 @@
 import { useEffect, useState } from 'react'
-import type { AppSettings } from '../types'
+import type { AppError, AppRule, AppSettings, FactoryResetReport, ImportSummary, ProductivitySnapshot, SelfTestReport, SessionEvent, SettingsIssue } from '../types'
 import { PLAYLISTS, type PlaylistType } from '../utils/playlists'
 
+const WEEKDAYS = ['Monday', 'Tuesday', 'Wednesday', 'Thursday', 'Friday', 'Saturday', 'Sunday']
+
+// Weekday names paired with their index (0 = Monday), listed from the configured first day of the week
+const weekdayOptions = (weekStart: AppSettings['week_start_day']): [string, number][] => {
+  const entries = WEEKDAYS.map((day, index): [string, number] => [day, index])
+  return weekStart === 'sunday' ? [entries[6], ...entries.slice(0, 6)] : entries
+}
+
 interface Props {
   settings?: AppSettings
   onSave: (settings: AppSettings) => Promise<void>
-  onExport: (format: 'csv' | 'json') => Promise<string>
-  onImport?: (file: File) => Promise<void>
+  onValidate?: (settings: AppSettings) => Promise<SettingsIssue[]>
+  onExport: (format: 'csv' | 'json' | 'markdown' | 'ics' | 'json_lines') => Promise<string>
+  onExportDashboard?: (format: 'csv' | 'json') => Promise<string>
+  onExportPdf?: (from: string, to: string) => Promise<string>
+  onImport?: (file: File) => Promise<ImportSummary>
+  onRestore?: (path: string) => Promise<void>
+  onFactoryReset?: (confirm: string, clearScreenshots: boolean) => Promise<FactoryResetReport>
+  onSetDataDir?: (path: string | null) => Promise<void>
+  onImportSessions?: (path: string) => Promise<ImportSummary>
+  onChangePassphrase?: (passphrase: string) => Promise<void>
+  onCheckProductivity?: () => Promise<ProductivitySnapshot>
+  onSelfTest?: () => Promise<SelfTestReport>
+  onLoadEvents?: () => Promise<SessionEvent[]>
   exporting: boolean
   importing?: boolean
   lastExportPath?: string
@@ -15,8 +34,19 @@ interface Props {
 export const SettingsPanel = ({
   settings,
   onSave,
+  onValidate,
   onExport,
+  onExportDashboard,
+  onExportPdf,
   onImport,
+  onRestore,
+  onFactoryReset,
+  onSetDataDir,
+  onImportSessions,
+  onChangePassphrase,
+  onCheckProductivity,
+  onSelfTest,
+  onLoadEvents,
   exporting,
   importing = false,
   lastExportPath,
@@ -25,7 +55,24 @@ export const SettingsPanel = ({
   const [draft, setDraft] = useState<AppSettings | undefined>(undefined)
   const [saving, setSaving] = useState(false)
   const [message, setMessage] = useState<string | null>(null)
+  const [issues, setIssues] = useState<SettingsIssue[]>([])
   const [initialized, setInitialized] = useState(false)
+  const [restorePath, setRestorePath] = useState('')
+  const [restoring, setRestoring] = useState(false)
+  const [dataDir, setDataDir] = useState('')
+  const [resetConfirm, setResetConfirm] = useState('')
+  const [resetScreenshots, setResetScreenshots] = useState(false)
+  const [resetting, setResetting] = useState(false)
+  const [sessionImportPath, setSessionImportPath] = useState('')
+  const [importingSessions, setImportingSessions] = useState(false)
+  const [passphrase, setPassphrase] = useState('')
+  const [passphraseConfirm, setPassphraseConfirm] = useState('')
+  const [reportFrom, setReportFrom] = useState('')
+  const [reportTo, setReportTo] = useState('')
+  const [productivity, setProductivity] = useState<ProductivitySnapshot | null>(null)
+  const [selfTest, setSelfTest] = useState<SelfTestReport | null>(null)
+  const [events, setEvents] = useState<SessionEvent[] | null>(null)
+  const [testing, setTesting] = useState(false)
 
   // Only sync from props on initial load (when draft is not yet set)
   useEffect(() => {
@@ -49,9 +96,37 @@ export const SettingsPanel = ({
       .map((item) => item.trim())
       .filter(Boolean)
 
+  // One rule per line; "title:" matches the focused window title, /.../ marks a regex
+  const parseRule = (line: string): AppRule => {
+    const isTitle = line.toLowerCase().startsWith('title:')
+    const body = isTitle ? line.slice(6).trim() : line
+    const isRegex = body.length > 2 && body.startsWith('/') && body.endsWith('/')
+    return {
+      pattern: isRegex ? body.slice(1, -1) : body,
+      kind: isTitle ? 'window_title' : 'process',
+      regex: isRegex,
+    }
+  }
+
+  const parseRules = (value: string): AppRule[] => parseList(value).map(parseRule)
+
+  const formatRules = (rules: AppRule[]) =>
+    rules
+      .map((rule) => {
+        const body = rule.regex ? `/${rule.pattern}/` : rule.pattern
+        return rule.kind === 'window_title' ? `title: ${body}` : body
+      })
+      .join('\n')
+
   const handleSave = async () => {
     setSaving(true)
     try {
+      // Show what the backend would clamp or reject; errors keep the draft unsaved
+      const found = onValidate ? await onValidate(draft) : []
+      setIssues(found)
+      if (found.some((issue) => issue.severity === 'error')) {
+        return
+      }
       await onSave(draft)
       setMessage('Settings saved.')
       setTimeout(() => setMessage(null), 3000)
@@ -60,19 +135,166 @@ export const SettingsPanel = ({
     }
   }
 
-  const handleExport = async (format: 'csv' | 'json') => {
+  const handleExport = async (format: 'csv' | 'json' | 'markdown' | 'ics' | 'json_lines') => {
     const path = await onExport(format)
     setMessage(`Exported to ${path}`)
     setTimeout(() => setMessage(null), 4000)
   }
 
+  const handleExportDashboard = async (format: 'csv' | 'json') => {
+    if (!onExportDashboard) return
+    const path = await onExportDashboard(format)
+    setMessage(`Dashboard summary exported to ${path}`)
+    setTimeout(() => setMessage(null), 4000)
+  }
+
+  const handleExportPdf = async () => {
+    if (!onExportPdf || !reportFrom || !reportTo) return
+    try {
+      const path = await onExportPdf(reportFrom, reportTo)
+      setMessage(`Report saved to ${path}`)
+      setTimeout(() => setMessage(null), 4000)
+    } catch (err) {
+      console.error('PDF export failed:', err)
+      setMessage(`Report failed: ${(err as AppError)?.message ?? String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    }
+  }
+
+  const handleRestore = async () => {
+    if (!onRestore || !restorePath.trim()) return
+    if (!window.confirm('Replace all current data with this backup?')) return
+
+    setRestoring(true)
+    try {
+      await onRestore(restorePath.trim())
+      setMessage('Backup restored.')
+      setTimeout(() => setMessage(null), 4000)
+      setRestorePath('')
+    } catch (err) {
+      console.error('Restore failed:', err)
+      setMessage(`Restore failed: ${err instanceof Error ? err.message : String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    } finally {
+      setRestoring(false)
+    }
+  }
+
+  const handleImportSessions = async () => {
+    if (!onImportSessions || !sessionImportPath.trim()) return
+
+    setImportingSessions(true)
+    try {
+      const report = await onImportSessions(sessionImportPath.trim())
+      setMessage(`Imported ${report.inserted} new, updated ${report.updated}, skipped ${report.skipped} unchanged sessions.`)
+      setTimeout(() => setMessage(null), 4000)
+      setSessionImportPath('')
+    } catch (err) {
+      console.error('Session import failed:', err)
+      setMessage(`Import failed: ${err instanceof Error ? err.message : String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    } finally {
+      setImportingSessions(false)
+    }
+  }
+
+  const handleSetDataDir = async (path: string | null) => {
+    if (!onSetDataDir) return
+    try {
+      await onSetDataDir(path)
+      setMessage(path ? `Data folder set to ${path}. Restart MasteryTrack to use it.` : 'Data folder reset to the default. Restart MasteryTrack to use it.')
+      setTimeout(() => setMessage(null), 6000)
+      setDataDir('')
+    } catch (err) {
+      console.error('Setting the data folder failed:', err)
+      setMessage(`Data folder not changed: ${(err as AppError)?.message ?? String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    }
+  }
+
+  const handleFactoryReset = async () => {
+    if (!onFactoryReset || resetConfirm !== 'DELETE') return
+    if (!window.confirm('Delete every session and reset all settings? This cannot be undone.')) return
+
+    setResetting(true)
+    try {
+      const report = await onFactoryReset(resetConfirm, resetScreenshots)
+      setMessage(
+        `Removed ${report.sessions_removed} sessions, ${report.skills_removed} skills and ${report.screenshots_removed} screenshots.`,
+      )
+      setTimeout(() => setMessage(null), 5000)
+      setResetConfirm('')
+      // Pick up the default settings instead of keeping the old draft
+      setInitialized(false)
+    } catch (err) {
+      console.error('Factory reset failed:', err)
+      setMessage(`Reset failed: ${(err as AppError)?.message ?? String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    } finally {
+      setResetting(false)
+    }
+  }
+
+  const handleChangePassphrase = async () => {
+    if (!onChangePassphrase || !passphrase) return
+    if (passphrase !== passphraseConfirm) {
+      setMessage('Passphrases do not match.')
+      setTimeout(() => setMessage(null), 4000)
+      return
+    }
+
+    try {
+      await onChangePassphrase(passphrase)
+      setMessage('Database encrypted. Launch MasteryTrack with MASTERYTRACK_DB_PASSPHRASE set to this passphrase.')
+      setTimeout(() => setMessage(null), 8000)
+      setPassphrase('')
+      setPassphraseConfirm('')
+    } catch (err) {
+      console.error('Passphrase change failed:', err)
+      setMessage(`Encryption failed: ${(err as AppError)?.message ?? String(err)}`)
+      setTimeout(() => setMessage(null), 5000)
+    }
+  }
+
+  const handleCheckProductivity = async () => {
+    if (!onCheckProductivity) return
+    try {
+      setProductivity(await onCheckProductivity())
+    } catch (err) {
+      console.error('Productivity check failed:', err)
+    }
+  }
+
+  const handleSelfTest = async () => {
+    if (!onSelfTest) return
+    setTesting(true)
+    try {
+      setSelfTest(await onSelfTest())
+    } catch (err) {
+      console.error('Self-test failed:', err)
+    } finally {
+      setTesting(false)
+    }
+  }
+
+  const handleLoadEvents = async () => {
+    if (!onLoadEvents) return
+    try {
+      setEvents(await onLoadEvents())
+    } catch (err) {
+      console.error('Loading timer events failed:', err)
+    }
+  }
+
   const handleImport = async (event: React.ChangeEvent<HTMLInputElement>) => {
     const file = event.target.files?.[0]
     if (!file || !onImport) return
 
     try {
-      await onImport(file)
-      setMessage('Data imported successfully!')
+      const summary = await onImport(file)
+      setMessage(
+        `Imported ${summary.inserted} new, updated ${summary.updated}, skipped ${summary.skipped} unchanged sessions.`,
+      )
       setTimeout(() => setMessage(null), 4000)
       // Reset file input
       event.target.value = ''
@@ -101,6 +323,17 @@ export const SettingsPanel = ({
             onChange={(e) => setDraft({ ...draft, skill_name: e.target.value })}
           />
         </label>
+        <label>
+          Mastery target (hours)
+          <input
+            type="number"
+            min={1}
+            value={draft.mastery_target_hours}
+            onChange={(e) =>
+              setDraft({ ...draft, mastery_target_hours: Number(e.target.value) })
+            }
+          />
+        </label>
         <label>
           Daily Goal (minutes)
           <input
@@ -112,8 +345,274 @@ export const SettingsPanel = ({
             }
           />
         </label>
+        <label>
+          Weekly Goal (minutes)
+          <input
+            type="number"
+            min={draft.daily_goal_minutes}
+            value={draft.weekly_goal_minutes}
+            onChange={(e) =>
+              setDraft({ ...draft, weekly_goal_minutes: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          Weeks start on
+          <select
+            value={draft.week_start_day}
+            onChange={(e) =>
+              setDraft({ ...draft, week_start_day: e.target.value as AppSettings['week_start_day'] })
+            }
+          >
+            <option value="monday">Monday</option>
+            <option value="sunday">Sunday</option>
+          </select>
+        </label>
+        <label>
+          Daily goal applies
+          <select
+            value={draft.daily_goal_by_weekday ? 'weekday' : 'every_day'}
+            onChange={(e) =>
+              setDraft({
+                ...draft,
+                daily_goal_by_weekday:
+                  e.target.value === 'weekday' ? Array(7).fill(draft.daily_goal_minutes) : null,
+              })
+            }
+          >
+            <option value="every_day">The same every day</option>
+            <option value="weekday">Per weekday (0 = rest day)</option>
+          </select>
+        </label>
+        {draft.daily_goal_by_weekday &&
+          weekdayOptions(draft.week_start_day).map(([day, index]) => (
+            <label key={day}>
+              {day} goal (minutes)
+              <input
+                type="number"
+                min={0}
+                value={draft.daily_goal_by_weekday?.[index] ?? 0}
+                onChange={(e) => {
+                  const goals = [...(draft.daily_goal_by_weekday ?? [])]
+                  goals[index] = Number(e.target.value)
+                  setDraft({ ...draft, daily_goal_by_weekday: goals })
+                }}
+              />
+            </label>
+          ))}
+        <label>
+          Remind me at % of daily goal (0 = off)
+          <input
+            type="number"
+            min={0}
+            max={99}
+            value={draft.goal_reminder_percent}
+            onChange={(e) =>
+              setDraft({ ...draft, goal_reminder_percent: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          Discard sessions shorter than (minutes)
+          <input
+            type="number"
+            min={0}
+            value={draft.minimum_session_minutes}
+            onChange={(e) =>
+              setDraft({ ...draft, minimum_session_minutes: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          Streak grace days
+          <input
+            type="number"
+            min={0}
+            max={7}
+            value={draft.streak_grace_days}
+            onChange={(e) =>
+              setDraft({ ...draft, streak_grace_days: Number(e.target.value) })
+            }
+          />
+        </label>
       </div>
 
+      <label>
+        Streak milestones to celebrate (days, comma separated)
+        <input
+          placeholder="e.g. 7, 30, 100"
+          defaultValue={draft.streak_milestones.join(', ')}
+          onChange={(e) =>
+            setDraft({
+              ...draft,
+              streak_milestones: parseList(e.target.value).map(Number).filter((days) => days > 0),
+            })
+          }
+        />
+      </label>
+
+      <label className="toggle-row">
+        <span>Remind me if I haven't practiced by</span>
+        <input
+          type="checkbox"
+          checked={draft.reminder_enabled}
+          onChange={(e) => setDraft({ ...draft, reminder_enabled: e.target.checked })}
+        />
+      </label>
+      {draft.reminder_enabled && (
+        <label>
+          Reminder time
+          <input
+            type="time"
+            value={draft.reminder_time ?? ''}
+            onChange={(e) => setDraft({ ...draft, reminder_time: e.target.value || null })}
+          />
+        </label>
+      )}
+
+      <label className="toggle-row">
+        <span>Weekly summary</span>
+        <input
+          type="checkbox"
+          checked={draft.weekly_summary_enabled}
+          onChange={(e) => setDraft({ ...draft, weekly_summary_enabled: e.target.checked })}
+        />
+      </label>
+      {draft.weekly_summary_enabled && (
+        <div className="grid two">
+          <label>
+            Every
+            <select
+              value={draft.weekly_summary_weekday}
+              onChange={(e) => setDraft({ ...draft, weekly_summary_weekday: Number(e.target.value) })}
+            >
+              {weekdayOptions(draft.week_start_day).map(([day, index]) => (
+                <option key={day} value={index}>
+                  {day}
+                </option>
+              ))}
+            </select>
+          </label>
+          <label>
+            At
+            <input
+              type="time"
+              value={draft.weekly_summary_time ?? ''}
+              onChange={(e) => setDraft({ ...draft, weekly_summary_time: e.target.value || null })}
+            />
+          </label>
+          <label>
+            Deliver to
+            <select
+              value={draft.weekly_summary_delivery}
+              onChange={(e) =>
+                setDraft({
+                  ...draft,
+                  weekly_summary_delivery: e.target.value as AppSettings['weekly_summary_delivery'],
+                })
+              }
+            >
+              <option value="file">File in exports/weekly</option>
+              <option value="webhook">Goal webhook URL</option>
+            </select>
+          </label>
+        </div>
+      )}
+
+      <label className="toggle-row">
+        <span>Pomodoro mode</span>
+        <input
+          type="checkbox"
+          checked={draft.pomodoro_enabled}
+          onChange={(e) => setDraft({ ...draft, pomodoro_enabled: e.target.checked })}
+        />
+      </label>
+      {draft.pomodoro_enabled && (
+        <div className="grid two">
+          <label>
+            Work interval (minutes)
+            <input
+              type="number"
+              min={1}
+              value={draft.pomodoro_work_minutes}
+              onChange={(e) =>
+                setDraft({ ...draft, pomodoro_work_minutes: Number(e.target.value) })
+              }
+            />
+          </label>
+          <label>
+            Break (minutes)
+            <input
+              type="number"
+              min={1}
+              value={draft.pomodoro_break_minutes}
+              onChange={(e) =>
+                setDraft({ ...draft, pomodoro_break_minutes: Number(e.target.value) })
+              }
+            />
+          </label>
+        </div>
+      )}
+
+      <label>
+        Extra reflection prompts (one per line)
+        <textarea
+          rows={2}
+          placeholder="e.g. Which passage was hardest?"
+          value={draft.reflection_prompts.join('\n')}
+          onChange={(e) => setDraft({ ...draft, reflection_prompts: e.target.value.split('\n') })}
+        />
+      </label>
+
+      <label>
+        Timezone (IANA name, leave empty for system time)
+        <input
+          placeholder="e.g. America/New_York"
+          value={draft.timezone ?? ''}
+          onChange={(e) => setDraft({ ...draft, timezone: e.target.value || null })}
+        />
+      </label>
+
+      <label>
+        Goal webhook URL (POSTed once a day when the daily goal is met)
+        <input
+          placeholder="https://discord.com/api/webhooks/..."
+          value={draft.goal_webhook_url ?? ''}
+          onChange={(e) => setDraft({ ...draft, goal_webhook_url: e.target.value || null })}
+        />
+      </label>
+
+      <label className="toggle-row">
+        <span>Local API (read-only, 127.0.0.1 only)</span>
+        <input
+          type="checkbox"
+          checked={draft.api_enabled}
+          onChange={(e) => setDraft({ ...draft, api_enabled: e.target.checked })}
+        />
+      </label>
+      {draft.api_enabled && (
+        <div className="grid two">
+          <label>
+            Port
+            <input
+              type="number"
+              min={1024}
+              max={65535}
+              value={draft.api_port}
+              onChange={(e) => setDraft({ ...draft, api_port: Number(e.target.value) })}
+            />
+          </label>
+          <label>
+            Access token (sent as "Authorization: Bearer …")
+            <input
+              type="password"
+              value={draft.api_token ?? ''}
+              onChange={(e) => setDraft({ ...draft, api_token: e.target.value || null })}
+            />
+          </label>
+        </div>
+      )}
+
       <div className="grid two">
         <label>
           Idle timeout (minutes)
@@ -126,6 +625,55 @@ export const SettingsPanel = ({
             }
           />
         </label>
+        <label>
+          Stop sessions after (minutes, 0 = never)
+          <input
+            type="number"
+            min={0}
+            value={draft.max_session_minutes}
+            onChange={(e) =>
+              setDraft({ ...draft, max_session_minutes: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          Check for idle every (seconds)
+          <input
+            type="number"
+            min={2}
+            max={60}
+            value={draft.idle_poll_seconds}
+            onChange={(e) =>
+              setDraft({ ...draft, idle_poll_seconds: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          When idle
+          <select
+            value={draft.idle_behavior}
+            onChange={(e) =>
+              setDraft({ ...draft, idle_behavior: e.target.value as AppSettings['idle_behavior'] })
+            }
+          >
+            <option value="pause">Pause the session</option>
+            <option value="subtract">Keep running, subtract idle time</option>
+            <option value="ignore">Keep counting</option>
+          </select>
+        </label>
+        {draft.idle_behavior === 'pause' && (
+          <label>
+            Stop a paused idle session after (minutes)
+            <input
+              type="number"
+              min={1}
+              value={draft.idle_prompt_timeout_minutes}
+              onChange={(e) =>
+                setDraft({ ...draft, idle_prompt_timeout_minutes: Number(e.target.value) })
+              }
+            />
+          </label>
+        )}
         <label className="toggle-row">
           <span>Productivity mode</span>
           <input
@@ -154,13 +702,54 @@ export const SettingsPanel = ({
         </label>
       </div>
 
+      <div className="grid two">
+        <label>
+          Enforce productivity from (empty = all day)
+          <input
+            type="time"
+            value={draft.productivity_active_from ?? ''}
+            onChange={(e) =>
+              setDraft({ ...draft, productivity_active_from: e.target.value || null })
+            }
+          />
+        </label>
+        <label>
+          Until
+          <input
+            type="time"
+            value={draft.productivity_active_to ?? ''}
+            onChange={(e) => setDraft({ ...draft, productivity_active_to: e.target.value || null })}
+          />
+        </label>
+        <label>
+          Grace before pausing (seconds)
+          <input
+            type="number"
+            min={0}
+            value={draft.productivity_grace_seconds}
+            onChange={(e) =>
+              setDraft({ ...draft, productivity_grace_seconds: Number(e.target.value) })
+            }
+          />
+        </label>
+      </div>
+
+      <label className="toggle-row">
+        <span>Only count time while an allowed app is in the foreground</span>
+        <input
+          type="checkbox"
+          checked={draft.active_requires_focus_app}
+          onChange={(e) => setDraft({ ...draft, active_requires_focus_app: e.target.checked })}
+        />
+      </label>
+
       <label>
         Allow only when these apps are active
         <textarea
           rows={2}
-          placeholder="e.g. code, figma"
-          value={draft.allowed_apps.join('\n')}
-          onChange={(e) => setDraft({ ...draft, allowed_apps: parseList(e.target.value) })}
+          placeholder="e.g. code, figma, title: rust docs"
+          value={formatRules(draft.allowed_apps)}
+          onChange={(e) => setDraft({ ...draft, allowed_apps: parseRules(e.target.value) })}
         />
       </label>
 
@@ -168,12 +757,29 @@ export const SettingsPanel = ({
         Pause when these apps appear
         <textarea
           rows={2}
-          placeholder="e.g. youtube, netflix"
-          value={draft.blocked_apps.join('\n')}
-          onChange={(e) => setDraft({ ...draft, blocked_apps: parseList(e.target.value) })}
+          placeholder="e.g. steam, title: /youtube\.com/"
+          value={formatRules(draft.blocked_apps)}
+          onChange={(e) => setDraft({ ...draft, blocked_apps: parseRules(e.target.value) })}
         />
       </label>
 
+      {onCheckProductivity && (
+        <div className="toggle-row">
+          <span className="muted tiny">
+            {productivity
+              ? productivity.blocked_triggered
+                ? `Blocked now: ${productivity.offending_processes.join(', ')}`
+                : productivity.allowed_active
+                  ? 'Focus apps detected, nothing blocked'
+                  : 'No focus app running'
+              : 'Check which configured apps are running'}
+          </span>
+          <button type="button" className="ghost" onClick={handleCheckProductivity}>
+            Check now
+          </button>
+        </div>
+      )}
+
       <label>
         Auto-backup folder
         <input
@@ -183,6 +789,143 @@ export const SettingsPanel = ({
         />
       </label>
 
+      <div className="grid two">
+        <label>
+          Backup every (hours, 0 = only on stop)
+          <input
+            type="number"
+            min={0}
+            value={draft.auto_backup_interval_hours}
+            onChange={(e) =>
+              setDraft({ ...draft, auto_backup_interval_hours: Number(e.target.value) })
+            }
+          />
+        </label>
+        <label>
+          Backups to keep (0 = all)
+          <input
+            type="number"
+            min={0}
+            value={draft.auto_backup_keep}
+            onChange={(e) => setDraft({ ...draft, auto_backup_keep: Number(e.target.value) })}
+          />
+        </label>
+      </div>
+      <label className="toggle-row">
+        <span>Compress backups (.db.gz)</span>
+        <input
+          type="checkbox"
+          checked={draft.backup_compress}
+          onChange={(e) => setDraft({ ...draft, backup_compress: e.target.checked })}
+        />
+      </label>
+      {onRestore && (
+        <label>
+          Restore from backup
+          <div className="grid two">
+            <input
+              placeholder="/path/to/backups/masterytrack-20240101-120000.db.gz"
+              value={restorePath}
+              onChange={(e) => setRestorePath(e.target.value)}
+            />
+            <button type="button" className="ghost" onClick={handleRestore} disabled={restoring}>
+              {restoring ? 'Restoring...' : 'Restore'}
+            </button>
+          </div>
+        </label>
+      )}
+      {onImportSessions && (
+        <label>
+          Import sessions from an export
+          <div className="grid two">
+            <input
+              placeholder="/path/to/exports/masterytrack-export-20240101-120000.csv"
+              value={sessionImportPath}
+              onChange={(e) => setSessionImportPath(e.target.value)}
+            />
+            <button type="button" className="ghost" onClick={handleImportSessions} disabled={importingSessions}>
+              {importingSessions ? 'Importing...' : 'Import'}
+            </button>
+          </div>
+        </label>
+      )}
+      {onChangePassphrase && (
+        <label>
+          Database passphrase (SQLCipher builds only)
+          <div className="grid two">
+            <input
+              type="password"
+              placeholder="New passphrase"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+            />
+            <input
+              type="password"
+              placeholder="Repeat passphrase"
+              value={passphraseConfirm}
+              onChange={(e) => setPassphraseConfirm(e.target.value)}
+            />
+          </div>
+          <span className="muted tiny">
+            Encrypts your journal. The passphrase is never stored: start the app with MASTERYTRACK_DB_PASSPHRASE
+            set to it, or the data cannot be opened.
+          </span>
+          <button type="button" className="ghost" onClick={handleChangePassphrase} disabled={!passphrase}>
+            Set passphrase
+          </button>
+        </label>
+      )}
+      {onSetDataDir && (
+        <label>
+          Data folder
+          <div className="grid two">
+            <input
+              placeholder="/media/usb/MasteryTrack"
+              value={dataDir}
+              onChange={(e) => setDataDir(e.target.value)}
+            />
+            <button type="button" className="ghost" onClick={() => handleSetDataDir(dataDir.trim())} disabled={!dataDir.trim()}>
+              Use folder
+            </button>
+          </div>
+          <span className="muted tiny">
+            Applies from the next launch; existing data is not moved. MASTERYTRACK_DATA_DIR overrides this.
+          </span>
+          <button type="button" className="ghost" onClick={() => handleSetDataDir(null)}>
+            Use default folder
+          </button>
+        </label>
+      )}
+      {onFactoryReset && (
+        <label>
+          Delete all data
+          <div className="grid two">
+            <input
+              placeholder="Type DELETE to confirm"
+              value={resetConfirm}
+              onChange={(e) => setResetConfirm(e.target.value)}
+            />
+            <button
+              type="button"
+              className="ghost"
+              onClick={handleFactoryReset}
+              disabled={resetting || resetConfirm !== 'DELETE'}
+            >
+              {resetting ? 'Deleting...' : 'Delete everything'}
+            </button>
+          </div>
+          <span className="muted tiny">
+            Removes every session and skill and restores default settings. Your PIN is kept.
+          </span>
+        </label>
+      )}
+      {onFactoryReset && (
+        <label className="toggle-row">
+          <span>Also delete screenshots</span>
+          <input type="checkbox" checked={resetScreenshots} onChange={(e) => setResetScreenshots(e.target.checked)} />
+        </label>
+      )}
+
       <div className="section-divider"></div>
 
       <div className="screenshot-section">
@@ -203,7 +946,7 @@ export const SettingsPanel = ({
           marginBottom: '16px'
         }}>
           <strong>⚠️ Privacy Notice:</strong> When enabled, this feature will automatically capture
-          screenshots of your screen every 10-20 minutes while the timer is running. Screenshots are
+          screenshots of your screen at the interval you choose while the timer is running. Screenshots are
           stored locally and can be reviewed or deleted at any time. Only enable this if you're
           comfortable with automatic screen capture.
         </div>
@@ -250,10 +993,35 @@ export const SettingsPanel = ({
                 }
               />
               <small className="muted">
-                Screenshots are compressed JPEG files (~100-500 KB each)
+                Screenshots are compressed JPEG files (~100-500 KB each) unless PNG is selected
               </small>
             </label>
 
+            <label className="toggle-row">
+              <span>Blur screenshots before saving</span>
+              <input
+                type="checkbox"
+                checked={draft.screenshot_blur}
+                onChange={(e) => setDraft({ ...draft, screenshot_blur: e.target.checked })}
+              />
+            </label>
+
+            <label>
+              Image format
+              <select
+                value={draft.screenshot_format}
+                onChange={(e) =>
+                  setDraft({
+                    ...draft,
+                    screenshot_format: e.target.value as AppSettings['screenshot_format'],
+                  })
+                }
+              >
+                <option value="jpeg">JPEG (smaller)</option>
+                <option value="png">PNG (lossless)</option>
+              </select>
+            </label>
+
             <label>
               Retention period (days)
               <input
@@ -273,6 +1041,52 @@ export const SettingsPanel = ({
               </small>
             </label>
 
+            <label>
+              Storage cap (MB, 0 = no cap)
+              <input
+                type="number"
+                min={0}
+                value={draft.screenshot_max_storage_mb}
+                onChange={(e) =>
+                  setDraft({ ...draft, screenshot_max_storage_mb: Number(e.target.value) })
+                }
+              />
+              <small className="muted">
+                Over the cap, the oldest screenshots are deleted first
+              </small>
+            </label>
+
+            <div className="grid two">
+              <label>
+                Minimum interval (minutes)
+                <input
+                  type="number"
+                  min={1}
+                  value={draft.screenshot_interval_min_minutes}
+                  onChange={(e) =>
+                    setDraft({
+                      ...draft,
+                      screenshot_interval_min_minutes: Number(e.target.value),
+                    })
+                  }
+                />
+              </label>
+              <label>
+                Maximum interval (minutes)
+                <input
+                  type="number"
+                  min={draft.screenshot_interval_min_minutes}
+                  value={draft.screenshot_interval_max_minutes}
+                  onChange={(e) =>
+                    setDraft({
+                      ...draft,
+                      screenshot_interval_max_minutes: Number(e.target.value),
+                    })
+                  }
+                />
+              </label>
+            </div>
+
             <div className="info-box" style={{
               padding: '10px',
               backgroundColor: 'var(--info-bg, rgba(33, 150, 243, 0.1))',
@@ -280,8 +1094,8 @@ export const SettingsPanel = ({
               fontSize: '0.9em',
               marginTop: '8px'
             }}>
-              <strong>How it works:</strong> Screenshots are captured randomly every 10-20 minutes
-              (average ~15 minutes) only when the practice timer is running and not paused. Old
+              <strong>How it works:</strong> Screenshots are captured at a random point between
+              your minimum and maximum interval only when the practice timer is running and not paused. Old
               screenshots are cleaned up automatically based on your retention setting.
             </div>
           </>
@@ -430,6 +1244,16 @@ export const SettingsPanel = ({
         </button>
         {message && <span className="muted">{message}</span>}
       </div>
+      {issues.length > 0 && (
+        <ul className="settings-issues">
+          {issues.map((issue) => (
+            <li key={`${issue.field}:${issue.message}`} className={issue.severity}>
+              {issue.severity === 'error' ? 'Not saved: ' : 'Adjusted: '}
+              {issue.message}
+            </li>
+          ))}
+        </ul>
+      )}
 
       <div className="export-row">
         <div>
@@ -447,6 +1271,20 @@ export const SettingsPanel = ({
           <button disabled={exporting} onClick={() => handleExport('json')}>
             Export JSON
           </button>
+          <button disabled={exporting} onClick={() => handleExport('markdown')}>
+            Export Markdown
+          </button>
+          <button disabled={exporting} onClick={() => handleExport('ics')}>
+            Export Calendar (.ics)
+          </button>
+          <button disabled={exporting} onClick={() => handleExport('json_lines')}>
+            Export JSON Lines
+          </button>
+          {onExportDashboard && (
+            <button disabled={exporting} onClick={() => handleExportDashboard('csv')}>
+              Export Summary CSV
+            </button>
+          )}
           {onImport && (
             <label className="import-button">
               <input
@@ -463,6 +1301,71 @@ export const SettingsPanel = ({
           )}
         </div>
       </div>
+      {onSelfTest && (
+        <div className="export-row">
+          <div>
+            <h4>Diagnostics</h4>
+            <p className="muted">Check that screenshots, idle detection, the database and notifications work on this machine.</p>
+            {selfTest && (
+              <ul className="muted tiny">
+                {([
+                  ['Screenshots', selfTest.screenshots_ok, selfTest.screenshots_error],
+                  ['Idle detection', selfTest.idle_ok, selfTest.idle_error],
+                  ['Database writable', selfTest.db_writable, selfTest.db_error],
+                  ['Notifications', selfTest.notifications_ok, selfTest.notifications_error],
+                ] as const).map(([label, ok, error]) => (
+                  <li key={label}>
+                    {ok ? '✓' : '✗'} {label}
+                    {!ok && error ? `: ${error}` : ''}
+                  </li>
+                ))}
+              </ul>
+            )}
+          </div>
+          <button type="button" className="ghost" onClick={handleSelfTest} disabled={testing}>
+            {testing ? 'Testing…' : 'Run self-test'}
+          </button>
+        </div>
+      )}
+      {onLoadEvents && (
+        <div className="export-row">
+          <div>
+            <h4>Timer events</h4>
+            <p className="muted">Starts, stops, pauses and idle spans, newest first, with why they happened.</p>
+            {events && (
+              <ul className="muted tiny">
+                {events.length === 0 && <li>No events yet.</li>}
+                {events.map((e) => (
+                  <li key={e.id}>
+                    {new Date(e.at).toLocaleString()} · session #{e.session_id} · {e.event_type.replace(/_/g, ' ')}
+                    {e.detail ? ` (${e.detail})` : ''}
+                  </li>
+                ))}
+              </ul>
+            )}
+          </div>
+          <button type="button" className="ghost" onClick={handleLoadEvents}>
+            {events ? 'Refresh events' : 'Show events'}
+          </button>
+        </div>
+      )}
+      {onExportPdf && (
+        <label>
+          PDF practice report
+          <div className="grid two">
+            <input type="date" value={reportFrom} onChange={(e) => setReportFrom(e.target.value)} />
+            <input type="date" value={reportTo} onChange={(e) => setReportTo(e.target.value)} />
+          </div>
+          <button
+            type="button"
+            className="ghost"
+            onClick={handleExportPdf}
+            disabled={exporting || !reportFrom || !reportTo}
+          >
+            Export PDF Report
+          </button>
+        </label>
+      )}
     </div>
   )
 }
@@ -6,15 +6,33 @@ interface Props {
   stats?: DashboardStats
   onStart: () => void
   onStop: () => void
+  onPause: () => void
+  onResume: () => void
+  onSkipBreak?: () => void
   disabled?: boolean
 }
 
-export const TimerCard = ({ timer, stats, onStart, onStop, disabled }: Props) => {
-  const label = timer.running ? 'Stop Practice' : 'Start Practice'
-  const action = timer.running ? onStop : onStart
+export const TimerCard = ({
+  timer,
+  stats,
+  onStart,
+  onStop,
+  onPause,
+  onResume,
+  onSkipBreak,
+  disabled,
+}: Props) => {
+  const paused = !timer.running && !!timer.started_at
+  const onBreak = paused && timer.last_reason === 'pomodoro break'
+  const label = timer.running || paused ? 'Stop Practice' : 'Start Practice'
+  const action = timer.running || paused ? onStop : onStart
   const description = timer.running
     ? 'Tracking deliberate practice…'
-    : 'Ready when you are.'
+    : onBreak
+      ? 'Pomodoro break — time off the clock.'
+      : paused
+        ? 'Session paused.'
+        : 'Ready when you are.'
 
   return (
     <div className="card timer-card">
@@ -27,6 +45,22 @@ export const TimerCard = ({ timer, stats, onStart, onStop, disabled }: Props) =>
         <button className="primary large" disabled={disabled} onClick={action}>
           {label}
         </button>
+        {timer.running && (
+          <button className="ghost" disabled={disabled} onClick={onPause}>
+            Pause
+          </button>
+        )}
+        {onBreak && onSkipBreak ? (
+          <button className="ghost" disabled={disabled} onClick={onSkipBreak}>
+            Skip break
+          </button>
+        ) : (
+          paused && (
+            <button className="ghost" disabled={disabled} onClick={onResume}>
+              Resume
+            </button>
+          )
+        )}
         {timer.last_reason && !timer.running && (
           <span className="muted tiny">Paused: {timer.last_reason}</span>
         )}
@@ -41,6 +75,10 @@ export const TimerCard = ({ timer, stats, onStart, onStop, disabled }: Props) =>
             <span className="muted tiny">Streak</span>
             <strong>{stats.streak_days} days</strong>
           </div>
+          <div>
+            <span className="muted tiny">Practice days</span>
+            <strong>{stats.total_practice_days}</strong>
+          </div>
           <div>
             <span className="muted tiny">Goal</span>
             <strong>
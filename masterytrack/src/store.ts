@@ -3,8 +3,13 @@ import { invoke } from '@tauri-apps/api/core'
 import type {
   AppSettings,
   DashboardStats,
+  FactoryResetReport,
+  ImportSummary,
   ReflectionInput,
+  SessionFilter,
   SessionHistoryRow,
+  SessionPage,
+  StopOutcome,
   TimerStatus,
 } from './types'
 
@@ -12,6 +17,8 @@ interface TrackerState {
   timer: TimerStatus
   stats?: DashboardStats
   sessions: SessionHistoryRow[]
+  sessionsTotal: number
+  todaysSessions: SessionHistoryRow[]
   settings?: AppSettings
   reflectionDraft: ReflectionInput
   reflectionOpen: boolean
@@ -20,18 +27,30 @@ interface TrackerState {
   lastExportPath?: string
   loadInitial: () => Promise<void>
   refreshStats: () => Promise<void>
-  refreshSessions: () => Promise<void>
+  refreshSessions: (filter?: SessionFilter) => Promise<void>
+  loadMoreSessions: () => Promise<void>
+  refreshTodaysSessions: () => Promise<void>
   refreshSettings: () => Promise<void>
   startTimer: () => Promise<void>
-  stopTimer: (payload: ReflectionInput) => Promise<void>
+  stopTimer: (payload: ReflectionInput) => Promise<StopOutcome>
+  pauseTimer: () => Promise<void>
+  resumeTimer: () => Promise<void>
+  skipBreak: () => Promise<void>
   saveSettings: (settings: AppSettings) => Promise<void>
-  exportData: (format: 'csv' | 'json', includeSettings?: boolean) => Promise<string>
-  importData: (file: File) => Promise<void>
+  exportData: (format: 'csv' | 'json' | 'markdown' | 'ics' | 'json_lines', includeSettings?: boolean) => Promise<string>
+  exportDashboard: (format: 'csv' | 'json') => Promise<string>
+  exportPdf: (from: string, to: string) => Promise<string>
+  importData: (file: File) => Promise<ImportSummary>
+  importSessions: (path: string) => Promise<ImportSummary>
+  restoreBackup: (path: string) => Promise<void>
+  factoryReset: (confirm: string, clearScreenshots: boolean) => Promise<FactoryResetReport>
   setReflectionOpen: (open: boolean, preset?: ReflectionInput) => void
   setTimer: (status: TimerStatus) => void
   updateReflectionDraft: (draft: ReflectionInput) => void
 }
 
+const SESSION_PAGE_SIZE = 200
+
 const defaultTimer: TimerStatus = {
   running: false,
   started_at: null,
@@ -43,13 +62,15 @@ const defaultTimer: TimerStatus = {
 export const useTrackerStore = create<TrackerState>((set, get) => ({
   timer: defaultTimer,
   sessions: [],
+  sessionsTotal: 0,
+  todaysSessions: [],
   reflectionDraft: {},
   reflectionOpen: false,
   exporting: false,
   importing: false,
   loadInitial: async () => {
     try {
-      const [timer, stats, sessions, settings] = await Promise.all([
+      const [timer, stats, page, settings] = await Promise.all([
         invoke<TimerStatus>('timer_status').catch(err => {
           console.error('Failed to load timer_status:', err)
           throw err
@@ -58,7 +79,7 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
           console.error('Failed to load dashboard:', err)
           throw err
         }),
-        invoke<SessionHistoryRow[]>('sessions').catch(err => {
+        invoke<SessionPage>('sessions').catch(err => {
           console.error('Failed to load sessions:', err)
           throw err
         }),
@@ -70,9 +91,11 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
       set({
         timer,
         stats,
-        sessions,
+        sessions: page.sessions,
+        sessionsTotal: page.total,
         settings,
       })
+      get().refreshTodaysSessions().catch(err => console.error('Failed to load todays_sessions:', err))
     } catch (error) {
       console.error('Error in loadInitial:', error)
       throw error
@@ -82,9 +105,22 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
     const stats = await invoke<DashboardStats>('dashboard')
     set({ stats })
   },
-  refreshSessions: async () => {
-    const sessions = await invoke<SessionHistoryRow[]>('sessions')
-    set({ sessions })
+  refreshSessions: async (filter?: SessionFilter) => {
+    // Keep however many older pages were already loaded so edits don't collapse the list
+    const limit = filter?.limit ?? Math.max(get().sessions.length, SESSION_PAGE_SIZE)
+    const page = await invoke<SessionPage>('sessions', { filter: { ...filter, limit } })
+    set({ sessions: page.sessions, sessionsTotal: page.total })
+  },
+  loadMoreSessions: async () => {
+    const loaded = get().sessions
+    const page = await invoke<SessionPage>('sessions', {
+      filter: { limit: SESSION_PAGE_SIZE, offset: loaded.length },
+    })
+    set({ sessions: [...loaded, ...page.sessions], sessionsTotal: page.total })
+  },
+  refreshTodaysSessions: async () => {
+    const todaysSessions = await invoke<SessionHistoryRow[]>('todays_sessions')
+    set({ todaysSessions })
   },
   refreshSettings: async () => {
     const settings = await invoke<AppSettings>('load_settings')
@@ -97,11 +133,24 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
     set({ timer })
   },
   stopTimer: async (payload: ReflectionInput) => {
-    await invoke<number>('stop_timer', { reflections: payload })
+    const outcome = await invoke<StopOutcome>('stop_timer', { reflections: payload })
     set({ reflectionOpen: false, reflectionDraft: {} })
-    await Promise.all([get().refreshStats(), get().refreshSessions()])
+    await Promise.all([get().refreshStats(), get().refreshSessions(), get().refreshTodaysSessions()])
     const timer = await invoke<TimerStatus>('timer_status')
     set({ timer })
+    return outcome
+  },
+  pauseTimer: async () => {
+    const timer = await invoke<TimerStatus>('pause_timer')
+    set({ timer })
+  },
+  resumeTimer: async () => {
+    const timer = await invoke<TimerStatus>('resume_timer')
+    set({ timer })
+  },
+  skipBreak: async () => {
+    const timer = await invoke<TimerStatus>('skip_break')
+    set({ timer })
   },
   saveSettings: async (settings: AppSettings) => {
     const updated = await invoke<AppSettings>('persist_settings', {
@@ -109,7 +158,7 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
     })
     set({ settings: updated })
   },
-  exportData: async (format: 'csv' | 'json', includeSettings = true) => {
+  exportData: async (format: 'csv' | 'json' | 'markdown' | 'ics' | 'json_lines', includeSettings = true) => {
     set({ exporting: true })
     try {
       const path = await invoke<string>('export_data', {
@@ -121,6 +170,26 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
       set({ exporting: false })
     }
   },
+  exportDashboard: async (format: 'csv' | 'json') => {
+    set({ exporting: true })
+    try {
+      const path = await invoke<string>('export_dashboard', { format })
+      set({ lastExportPath: path })
+      return path
+    } finally {
+      set({ exporting: false })
+    }
+  },
+  exportPdf: async (from: string, to: string) => {
+    set({ exporting: true })
+    try {
+      const path = await invoke<string>('export_pdf', { from, to })
+      set({ lastExportPath: path })
+      return path
+    } finally {
+      set({ exporting: false })
+    }
+  },
   importData: async (file: File) => {
     set({ importing: true })
     try {
@@ -153,7 +222,7 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
       })
       
       // Import data
-      await invoke('import_data', {
+      const summary = await invoke<ImportSummary>('import_data', {
         request: {
           file_path: tempPath,
           import_settings: importSettings,
@@ -166,6 +235,7 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
         get().refreshSessions(),
         get().refreshSettings(),
       ])
+      return summary
     } catch (error) {
       console.error('Import failed:', error)
       throw error
@@ -173,6 +243,20 @@ export const useTrackerStore = create<TrackerState>((set, get) => ({
       set({ importing: false })
     }
   },
+  importSessions: async (path: string) => {
+    const report = await invoke<ImportSummary>('import_sessions', { path })
+    await Promise.all([get().refreshStats(), get().refreshSessions()])
+    return report
+  },
+  restoreBackup: async (path: string) => {
+    await invoke('restore_backup', { path })
+    await get().loadInitial()
+  },
+  factoryReset: async (confirm: string, clearScreenshots: boolean) => {
+    const report = await invoke<FactoryResetReport>('factory_reset', { confirm, clearScreenshots })
+    await get().loadInitial()
+    return report
+  },
   setReflectionOpen: (open, preset) =>
     set({
       reflectionOpen: open,
@@ -1,5 +1,11 @@
 export type ISODate = string
 
+/** Rejection payload of a failed command, tagged with the `AppError` variant name. */
+export interface AppError {
+  type: string
+  message?: string
+}
+
 export interface TimerStatus {
   running: boolean
   started_at?: ISODate | null
@@ -8,6 +14,14 @@ export interface TimerStatus {
   last_reason?: string | null
 }
 
+export interface ActiveSessionInfo {
+  session_id: number
+  skill_id: number
+  skill_name: string
+  started_at: string
+  elapsed_seconds: number
+}
+
 export interface StartTimerResponse {
   session_id: number
   started_at: ISODate
@@ -18,11 +32,80 @@ export interface DashboardStats {
   week_hours: number
   month_hours: number
   total_hours: number
-  goal_progress: number
+  mastery_progress: number
   total_hours_target: number
   daily_goal_hours: number
   todays_goal_hours: number
   streak_days: number
+  total_practice_days: number
+  weekly_goal: GoalProgress
+  today_by_tag: TagHours[]
+  avg_session_minutes: number
+  longest_session_minutes: number
+  sessions_this_week: number
+  eta_date: string | null
+  eta_days_remaining: number | null
+  display: DashboardDisplay
+}
+
+export type AppMatchKind = 'process' | 'window_title'
+
+export interface AppRule {
+  pattern: string
+  kind: AppMatchKind
+  regex: boolean
+}
+
+export interface ProductivitySnapshot {
+  allowed_active: boolean
+  blocked_triggered: boolean
+  offending_processes: string[]
+  timestamp: string
+}
+
+export type StopOutcome =
+  | { status: 'saved'; minutes: number }
+  | { status: 'discarded'; minutes: number; minimum_minutes: number }
+
+export interface SessionEvent {
+  id: number
+  session_id: number
+  event_type: string
+  at: string
+  detail: string | null
+}
+
+export interface SelfTestReport {
+  screenshots_ok: boolean
+  screenshots_error: string | null
+  idle_ok: boolean
+  idle_error: string | null
+  db_writable: boolean
+  db_error: string | null
+  notifications_ok: boolean
+  notifications_error: string | null
+}
+
+export interface MaintenanceReport {
+  integrity: string
+  size_before_bytes: number
+  size_after_bytes: number
+}
+
+export interface GoalProgress {
+  goal_minutes: number
+  completed_minutes: number
+  progress: number
+  met: boolean
+}
+
+export interface DashboardDisplay {
+  today_hours: string
+  week_hours: string
+  month_hours: string
+  total_hours: string
+  daily_goal_hours: string
+  todays_goal_hours: string
 }
 
 export interface SessionHistoryRow {
@@ -34,6 +117,8 @@ export interface SessionHistoryRow {
   what_practiced?: string | null
   what_learned?: string | null
   next_focus?: string | null
+  tags: string[]
+  reflection_extra?: Record<string, string>
 }
 
 export interface ReflectionInput {
@@ -41,6 +126,13 @@ export interface ReflectionInput {
   what_practiced?: string
   what_learned?: string
   next_focus?: string
+  tags?: string[]
+  extra?: Record<string, string>
+}
+
+export interface TagHours {
+  tag: string
+  hours: number
 }
 
 export interface AppSettings {
@@ -48,21 +140,63 @@ export interface AppSettings {
   daily_goal_minutes: number
   idle_timeout_minutes: number
   productivity_mode_enabled: boolean
-  allowed_apps: string[]
-  blocked_apps: string[]
+  allowed_apps: AppRule[]
+  blocked_apps: AppRule[]
   auto_backup_path?: string | null
   screenshot_enabled: boolean
   screenshot_storage_path?: string | null
   screenshot_retention_days: number
+  screenshot_privacy_mode: boolean
   music_enabled: boolean
   music_playlist_type: string
   music_volume: number
   music_auto_play: boolean
   music_custom_playlist_url?: string | null
+  quick_note_target: 'recent_session' | 'daily_note'
+  display_hours_precision: 'one_decimal' | 'two_decimals' | 'quarter_hour'
+  weekly_goal_minutes: number
+  screenshot_interval_min_minutes: number
+  screenshot_interval_max_minutes: number
+  screenshot_format: 'jpeg' | 'png'
+  screenshot_blur: boolean
+  timezone?: string | null
+  streak_grace_days: number
+  auto_backup_interval_hours: number
+  auto_backup_keep: number
+  backup_compress: boolean
+  productivity_active_from: string | null
+  productivity_active_to: string | null
+  productivity_grace_seconds: number
+  pomodoro_enabled: boolean
+  pomodoro_work_minutes: number
+  pomodoro_break_minutes: number
+  minimum_session_minutes: number
+  api_enabled: boolean
+  api_port: number
+  api_token: string | null
+  goal_webhook_url: string | null
+  reflection_prompts: string[]
+  mastery_target_hours: number
+  goal_reminder_percent: number
+  reminder_enabled: boolean
+  reminder_time: string | null
+  idle_behavior: 'pause' | 'subtract' | 'ignore'
+  idle_prompt_timeout_minutes: number
+  active_requires_focus_app: boolean
+  screenshot_max_storage_mb: number
+  streak_milestones: number[]
+  weekly_summary_enabled: boolean
+  weekly_summary_weekday: number
+  weekly_summary_time: string | null
+  weekly_summary_delivery: 'file' | 'webhook'
+  idle_poll_seconds: number
+  week_start_day: 'monday' | 'sunday'
+  max_session_minutes: number
+  daily_goal_by_weekday: number[] | null
 }
 
 export interface ExportRequest {
-  format: 'csv' | 'json'
+  format: 'csv' | 'json' | 'markdown' | 'ics' | 'json_lines'
   target_dir?: string
   include_settings?: boolean
 }
@@ -72,7 +206,100 @@ export interface ImportRequest {
   import_settings?: boolean
 }
 
+export interface ImportSummary {
+  inserted: number
+  updated: number
+  skipped: number
+}
+
+export interface ExportPreview {
+  session_count: number
+  estimated_bytes: number
+}
+
+export interface SessionSearchHit {
+  session: SessionHistoryRow
+  snippet: string | null
+}
+
+export interface IdlePrompt {
+  timeout_minutes: number
+}
+
+export interface MilestoneNotification {
+  milestone_days: number
+  streak_days: number
+}
+
+export interface GoalApproachNotification {
+  percent: number
+  total_minutes: number
+  goal_minutes: number
+}
+
 export interface GoalNotification {
   achieved_at: ISODate
   total_minutes: number
+  goal_minutes: number
+}
+
+export interface Skill {
+  id: number
+  skill_name: string
+}
+
+export interface SkillSummary {
+  id: number
+  skill_name: string
+  total_minutes: number
+}
+
+export interface SkillMergeReport {
+  target: Skill
+  moved_sessions: number
+}
+
+// A finding from validate_settings; warnings are adjusted on save, errors block it
+export interface SettingsIssue {
+  field: keyof AppSettings
+  message: string
+  severity: 'warning' | 'error'
+}
+
+export interface FactoryResetReport {
+  sessions_removed: number
+  skills_removed: number
+  screenshots_removed: number
+}
+
+export interface SessionFilter {
+  from?: ISODate | null
+  to?: ISODate | null
+  limit?: number | null
+  offset?: number | null
+}
+
+export interface SessionPage {
+  sessions: SessionHistoryRow[]
+  total: number
+}
+
+export interface DailyTotal {
+  date: string
+  minutes: number
+}
+
+export interface HourlyTotal {
+  hour: number
+  minutes: number
+}
+
+export interface WeeklySummary {
+  from: string
+  to: string
+  total_minutes: number
+  days_practiced: number
+  best_day: DailyTotal | null
+  streak_days: number
+  days: DailyTotal[]
 }